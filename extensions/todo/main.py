@@ -0,0 +1,126 @@
+#!/usr/bin/env python3
+#
+# Reference extension for the Loungy extension protocol, a small todo list stored next to this file.
+# Copy or symlink this folder into ~/.config/loungy/extensions to try it.
+
+import json
+import os
+import sys
+
+STORE = os.path.join(os.path.dirname(os.path.abspath(__file__)), "todos.json")
+
+
+def load():
+    try:
+        with open(STORE) as f:
+            return json.load(f)
+    except (OSError, ValueError):
+        return []
+
+
+def save(todos):
+    with open(STORE, "w") as f:
+        json.dump(todos, f)
+
+
+def send(method, params=None):
+    message = {"jsonrpc": "2.0", "method": method}
+    if params is not None:
+        message["params"] = params
+    sys.stdout.write(json.dumps(message) + "\n")
+    sys.stdout.flush()
+
+
+def respond(id, result):
+    sys.stdout.write(json.dumps({"jsonrpc": "2.0", "id": id, "result": result}) + "\n")
+    sys.stdout.flush()
+
+
+def list_view(todos):
+    items = []
+    for i, todo in enumerate(todos):
+        items.append(
+            {
+                "id": str(i),
+                "title": todo["title"],
+                "icon": "CheckCircle" if todo["done"] else "Circle",
+                "accessories": [{"tag": "Done"}] if todo["done"] else [],
+                "actions": [
+                    {
+                        "id": "toggle",
+                        "title": "Mark as Open" if todo["done"] else "Mark as Done",
+                        "icon": "Check",
+                    },
+                    {"id": "details", "title": "Show Details", "icon": "Info", "shortcut": "cmd-i"},
+                    {"id": "delete", "title": "Delete", "icon": "Trash", "shortcut": "ctrl-backspace"},
+                ],
+            }
+        )
+    return {
+        "id": "list",
+        "type": "list",
+        "placeholder": "Search todos...",
+        "items": items,
+        "actions": [{"id": "add", "title": "Add Todo", "icon": "Plus", "shortcut": "cmd-n"}],
+    }
+
+
+def add_view():
+    return {
+        "id": "add",
+        "type": "form",
+        "inputs": [
+            {"id": "title", "label": "Title", "type": "text", "placeholder": "Buy milk", "required": True}
+        ],
+    }
+
+
+def detail_view(todo):
+    status = "Done" if todo["done"] else "Open"
+    return {"id": "details", "type": "detail", "text": "{}\n\nStatus: {}".format(todo["title"], status)}
+
+
+def handle(method, params, todos):
+    if method == "run":
+        if params["command"] == "list":
+            send("push", {"view": list_view(todos)})
+        else:
+            send("push", {"view": add_view()})
+    elif method == "action":
+        if params["action"] == "add":
+            send("push", {"view": add_view()})
+            return
+        index = int(params["item"])
+        if index >= len(todos):
+            return
+        if params["action"] == "toggle":
+            todos[index]["done"] = not todos[index]["done"]
+        elif params["action"] == "delete":
+            todos.pop(index)
+            send("toast", {"style": "success", "message": "Todo deleted"})
+        elif params["action"] == "details":
+            send("push", {"view": detail_view(todos[index])})
+            return
+        save(todos)
+        send("update", {"view": list_view(todos)})
+    elif method == "submit":
+        todos.append({"title": params["values"]["title"], "done": False})
+        save(todos)
+        send("pop")
+        send("update", {"view": list_view(todos)})
+        send("toast", {"style": "success", "message": "Todo added"})
+
+
+def main():
+    todos = load()
+    for line in sys.stdin:
+        message = json.loads(line)
+        method = message.get("method")
+        if method == "initialize":
+            respond(message["id"], {"name": "todo"})
+        elif method is not None:
+            handle(method, message.get("params", {}), todos)
+
+
+if __name__ == "__main__":
+    main()
@@ -11,35 +11,60 @@
 
 use gpui::*;
 
+#[cfg(feature = "expansion")]
+use crate::platform::expansion::Expansion;
+#[cfg(feature = "plugins")]
+use crate::plugins::Plugins;
 use crate::{
     assets::Assets,
-    commands::RootCommands,
+    commands::{
+        root::{
+            aliases::Aliases, apps::ApplicationIndex, frecency::Frecency, list::RootListBuilder,
+            quicklinks::Quicklinks,
+        },
+        RootCommands,
+    },
+    dmenu::DmenuListBuilder,
+    extensions::Extensions,
     hotkey::HotkeyManager,
+    ipc::{Command, Ipc},
+    settings::Settings,
     theme::Theme,
-    window::{Frontmost, Window, WindowStyle},
+    window::{Frontmost, Window, WindowPlacement, WindowStyle},
     workspace::Workspace,
 };
 
-pub fn run_app(app: gpui::App) {
+pub fn run_app(app: gpui::App, dmenu: Option<DmenuListBuilder>, command: Option<Command>) {
     app.with_assets(Assets).run(move |cx: &mut AppContext| {
+        Settings::init(cx);
+        WindowPlacement::init(cx);
         Theme::init(cx);
-        // TODO: This still only works for a single display
-        let bounds = cx.displays().first().map(|d| d.bounds()).unwrap_or(Bounds {
-            origin: Point::new(GlobalPixels::from(0.0), GlobalPixels::from(0.0)),
-            size: Size {
-                width: GlobalPixels::from(1920.0),
-                height: GlobalPixels::from(1080.0),
-            },
-        });
-        cx.open_window(WindowStyle::Main.options(bounds), |cx| {
+        cx.open_window(WindowStyle::main(cx).options(cx), |cx| {
             let theme = cx.global::<Theme>();
             cx.set_background(WindowBackground::from(
                 theme.window_background.clone().unwrap_or_default(),
             ));
+            if let Some(dmenu) = dmenu {
+                let code = dmenu.cancel_code();
+                let view = Workspace::build(dmenu, false, cx);
+                Window::init(cx);
+                Window::exit_on_close(code, cx);
+                return view;
+            }
             Frontmost::init(cx);
+            Extensions::init(cx);
             RootCommands::init(cx);
+            #[cfg(feature = "plugins")]
+            Plugins::init(cx);
+            ApplicationIndex::init(cx);
+            Frecency::init(cx);
+            Aliases::init(cx);
+            Quicklinks::init(cx);
+            #[cfg(feature = "expansion")]
+            Expansion::init(cx);
             HotkeyManager::init(cx);
-            let view = Workspace::build(cx);
+            Ipc::init(command, cx);
+            let view = Workspace::build(RootListBuilder, true, cx);
             Window::init(cx);
 
             view
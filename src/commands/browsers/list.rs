@@ -0,0 +1,253 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::collections::HashSet;
+
+use async_std::task::spawn_blocking;
+use gpui::*;
+use log::error;
+
+use crate::{
+    commands::{root::apps::ApplicationIndex, RootCommand, RootCommandBuilder},
+    components::{
+        list::{Accessory, AsyncListItems, Item, ItemBuilder, ListBuilder, ListItem},
+        shared::{Icon, Img, ImgMask},
+    },
+    date::format_date,
+    platform::open_with,
+    settings::Settings,
+    state::{Action, Actions, Shortcut, StateModel, StateViewBuilder, StateViewContext},
+    window::Window,
+};
+
+use super::profiles::{profiles, Entry, Kind, Profile};
+
+// Links open in the browser they were saved in, or the default one if it can't be found
+fn open(apps: &[&str], url: &str, actions: &mut Actions, cx: &mut WindowContext) {
+    let id = cx
+        .global::<ApplicationIndex>()
+        .apps
+        .read(cx)
+        .iter()
+        .find(|app| apps.contains(&app.id.as_str()))
+        .map(|app| app.id.clone());
+    match id {
+        Some(id) => {
+            let terminal = cx.global::<Settings>().terminal.clone();
+            if let Err(err) = open_with(&id, url, terminal.as_deref()) {
+                error!("Failed to open link: {}", err);
+                actions.toast.error(err, cx);
+                return;
+            }
+        }
+        None => cx.open_url(url),
+    }
+    Window::close(cx);
+}
+
+fn item(profile: &Profile, entry: Entry, cx: &mut WindowContext) -> Item {
+    let title = if entry.title.is_empty() {
+        entry.url.clone()
+    } else {
+        entry.title.clone()
+    };
+    let mut accessories = vec![];
+    match entry.kind {
+        Kind::Bookmark => accessories.push(Accessory::new(
+            "Bookmark",
+            Some(Img::default().icon(Icon::Bookmark)),
+        )),
+        Kind::History => {
+            if let Some(visited) = entry.visited {
+                accessories.push(Accessory::new(
+                    format_date(&visited),
+                    Some(Img::default().icon(Icon::History)),
+                ));
+            }
+        }
+    }
+    accessories.push(Accessory::new(profile.label.clone(), None));
+    let url = entry.url.clone();
+    let apps = profile.apps;
+    ItemBuilder::new(
+        (profile.key.clone(), entry.kind, entry.url.clone()),
+        ListItem::new(
+            Some(
+                Img::default()
+                    .mask(ImgMask::Rounded)
+                    .favicon(&entry.url, Icon::Globe, cx),
+            ),
+            title.clone(),
+            Some(entry.url.clone()),
+            accessories,
+        ),
+    )
+    .keywords(vec![title, entry.url])
+    .actions(vec![
+        Action::new(
+            Img::default().icon(Icon::ArrowUpRightFromSquare),
+            "Open",
+            None,
+            {
+                let url = url.clone();
+                move |actions, cx| open(apps, &url, actions, cx)
+            },
+            false,
+        ),
+        Action::new(
+            Img::default().icon(Icon::Globe),
+            "Open in Default Browser",
+            Some(Shortcut::new("enter").cmd()),
+            {
+                let url = url.clone();
+                move |_, cx| {
+                    cx.open_url(&url);
+                    Window::close(cx);
+                }
+            },
+            false,
+        ),
+        Action::new(
+            Img::default().icon(Icon::Copy),
+            "Copy URL",
+            Some(Shortcut::new("c").cmd().shift()),
+            move |actions, cx| {
+                cx.write_to_clipboard(ClipboardItem::new(url.clone()));
+                actions
+                    .toast
+                    .floating("Copied URL to clipboard", Some(Icon::Clipboard), cx);
+            },
+            false,
+        ),
+    ])
+    .build()
+}
+
+/// Bookmarks and history of every local browser profile, read again whenever the command is opened.
+pub struct BrowserIndex {
+    view: View<AsyncListItems>,
+    profiles: Vec<Profile>,
+}
+
+impl BrowserIndex {
+    pub fn init(cx: &mut WindowContext) {
+        let view = cx.new_view(|_| AsyncListItems::new());
+        cx.set_global(Self {
+            view,
+            profiles: vec![],
+        });
+    }
+    pub fn reload(cx: &mut WindowContext) {
+        let profiles = profiles();
+        let view = cx.global::<Self>().view.clone();
+        cx.global_mut::<Self>().profiles = profiles.clone();
+        let keys: HashSet<String> = profiles.iter().map(|profile| profile.key.clone()).collect();
+        view.update(cx, |view, cx| {
+            view.items.retain(|key, _| keys.contains(key));
+            // Nothing would ever finish loading otherwise
+            if profiles.is_empty() {
+                view.update("none".to_string(), vec![], cx);
+            }
+        });
+        for profile in profiles {
+            let view = view.downgrade();
+            cx.spawn(|mut cx| async move {
+                let entries = {
+                    let profile = profile.clone();
+                    spawn_blocking(move || profile.entries()).await
+                };
+                let entries = entries.unwrap_or_else(|err| {
+                    error!("Failed to read {}: {}", profile.label, err);
+                    vec![]
+                });
+                let _ = cx.update(|cx| {
+                    // The same page can be bookmarked in several folders
+                    let mut seen = HashSet::new();
+                    let items: Vec<Item> = entries
+                        .into_iter()
+                        .filter(|entry| seen.insert((entry.kind, entry.url.clone())))
+                        .map(|entry| item(&profile, entry, cx))
+                        .collect();
+                    view.update(cx, |view, cx| view.update(profile.key.clone(), items, cx))
+                });
+            })
+            .detach();
+        }
+    }
+}
+
+impl Global for BrowserIndex {}
+
+#[derive(Clone)]
+struct BrowsersBuilder;
+
+impl StateViewBuilder for BrowsersBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        context
+            .query
+            .set_placeholder("Search bookmarks and history...", cx);
+        let index = cx.global::<BrowserIndex>();
+        let view = index.view.clone();
+        let keys: Vec<String> = index
+            .profiles
+            .iter()
+            .map(|profile| profile.key.clone())
+            .collect();
+        let mut dropdown = vec![(String::new(), "All Browsers".to_string())];
+        dropdown.extend(
+            index
+                .profiles
+                .iter()
+                .map(|profile| (profile.key.clone(), profile.label.clone())),
+        );
+        context.actions.set_dropdown("", dropdown, cx);
+        AsyncListItems::loader(&view, &context.actions, cx);
+        ListBuilder::new()
+            .build(
+                move |this, _, cx| {
+                    let selected = this.actions.get_dropdown_value(cx);
+                    let items = &view.read(cx).items;
+                    // Profiles keep the order they were found in
+                    Ok(Some(
+                        keys.iter()
+                            .filter(|key| selected.is_empty() || **key == selected)
+                            .filter_map(|key| items.get(key))
+                            .flatten()
+                            .cloned()
+                            .collect(),
+                    ))
+                },
+                context,
+                cx,
+            )
+            .into()
+    }
+}
+
+pub struct BrowsersCommandBuilder;
+
+impl RootCommandBuilder for BrowsersCommandBuilder {
+    fn build(&self, cx: &mut WindowContext) -> RootCommand {
+        BrowserIndex::init(cx);
+        RootCommand::new(
+            "search_browsers",
+            "Search Bookmarks and History",
+            "Loungy",
+            Icon::Bookmark,
+            vec!["Browser", "Firefox", "Chrome", "Websites"],
+            None,
+            Box::new(|_, cx| {
+                BrowserIndex::reload(cx);
+                StateModel::update(|this, cx| this.push(BrowsersBuilder, cx), cx);
+            }),
+        )
+    }
+}
@@ -0,0 +1,730 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use rusqlite::Connection;
+use serde_json::Value;
+use time::OffsetDateTime;
+
+use crate::paths::paths;
+
+// Only the most recent visits are read, older ones are rarely searched for
+static MAX_HISTORY: usize = 1000;
+// Chromium counts microseconds since 1601 instead of 1970
+static CHROMIUM_EPOCH_OFFSET: i64 = 11_644_473_600_000_000;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Engine {
+    Firefox,
+    Chromium,
+}
+
+struct Browser {
+    name: &'static str,
+    engine: Engine,
+    // Folders holding the profiles relative to the home folder, including sandboxed installs
+    dirs: &'static [&'static str],
+    // Application ids to open links with, the first installed one is used
+    apps: &'static [&'static str],
+}
+
+#[cfg(target_os = "linux")]
+static BROWSERS: &[Browser] = &[
+    Browser {
+        name: "Firefox",
+        engine: Engine::Firefox,
+        dirs: &[
+            ".mozilla/firefox",
+            "snap/firefox/common/.mozilla/firefox",
+            ".var/app/org.mozilla.firefox/.mozilla/firefox",
+        ],
+        apps: &[
+            "firefox.desktop",
+            "firefox_firefox.desktop",
+            "org.mozilla.firefox.desktop",
+        ],
+    },
+    Browser {
+        name: "LibreWolf",
+        engine: Engine::Firefox,
+        dirs: &[
+            ".librewolf",
+            ".var/app/io.gitlab.librewolf-community/.librewolf",
+        ],
+        apps: &["librewolf.desktop", "io.gitlab.librewolf-community.desktop"],
+    },
+    Browser {
+        name: "Google Chrome",
+        engine: Engine::Chromium,
+        dirs: &[
+            ".config/google-chrome",
+            ".var/app/com.google.Chrome/config/google-chrome",
+        ],
+        apps: &["google-chrome.desktop", "com.google.Chrome.desktop"],
+    },
+    Browser {
+        name: "Chromium",
+        engine: Engine::Chromium,
+        dirs: &[
+            ".config/chromium",
+            "snap/chromium/common/chromium",
+            ".var/app/org.chromium.Chromium/config/chromium",
+        ],
+        apps: &[
+            "chromium.desktop",
+            "chromium-browser.desktop",
+            "chromium_chromium.desktop",
+            "org.chromium.Chromium.desktop",
+        ],
+    },
+    Browser {
+        name: "Brave",
+        engine: Engine::Chromium,
+        dirs: &[
+            ".config/BraveSoftware/Brave-Browser",
+            ".var/app/com.brave.Browser/config/BraveSoftware/Brave-Browser",
+        ],
+        apps: &["brave-browser.desktop", "com.brave.Browser.desktop"],
+    },
+    Browser {
+        name: "Microsoft Edge",
+        engine: Engine::Chromium,
+        dirs: &[".config/microsoft-edge"],
+        apps: &["microsoft-edge.desktop"],
+    },
+    Browser {
+        name: "Vivaldi",
+        engine: Engine::Chromium,
+        dirs: &[".config/vivaldi"],
+        apps: &["vivaldi-stable.desktop"],
+    },
+];
+
+#[cfg(target_os = "macos")]
+static BROWSERS: &[Browser] = &[
+    Browser {
+        name: "Firefox",
+        engine: Engine::Firefox,
+        dirs: &["Library/Application Support/Firefox"],
+        apps: &["org.mozilla.firefox"],
+    },
+    Browser {
+        name: "Google Chrome",
+        engine: Engine::Chromium,
+        dirs: &["Library/Application Support/Google/Chrome"],
+        apps: &["com.google.Chrome"],
+    },
+    Browser {
+        name: "Chromium",
+        engine: Engine::Chromium,
+        dirs: &["Library/Application Support/Chromium"],
+        apps: &["org.chromium.Chromium"],
+    },
+    Browser {
+        name: "Brave",
+        engine: Engine::Chromium,
+        dirs: &["Library/Application Support/BraveSoftware/Brave-Browser"],
+        apps: &["com.brave.Browser"],
+    },
+    Browser {
+        name: "Microsoft Edge",
+        engine: Engine::Chromium,
+        dirs: &["Library/Application Support/Microsoft Edge"],
+        apps: &["com.microsoft.edgemac"],
+    },
+    Browser {
+        name: "Vivaldi",
+        engine: Engine::Chromium,
+        dirs: &["Library/Application Support/Vivaldi"],
+        apps: &["com.vivaldi.Vivaldi"],
+    },
+    Browser {
+        name: "Arc",
+        engine: Engine::Chromium,
+        dirs: &["Library/Application Support/Arc/User Data"],
+        apps: &["company.thebrowser.Browser"],
+    },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Bookmark,
+    History,
+}
+
+#[derive(Clone)]
+pub struct Entry {
+    pub title: String,
+    pub url: String,
+    pub kind: Kind,
+    pub visited: Option<OffsetDateTime>,
+}
+
+#[derive(Clone)]
+pub struct Profile {
+    // Unique across browsers, used as the dropdown value
+    pub key: String,
+    pub label: String,
+    pub apps: &'static [&'static str],
+    engine: Engine,
+    path: PathBuf,
+}
+
+// Profiles listed in `profiles.ini`, skipping ones that were never opened
+fn firefox_profiles(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(ini) = fs::read_to_string(dir.join("profiles.ini")) else {
+        return vec![];
+    };
+    let mut profiles = vec![];
+    let mut section = false;
+    let mut name: Option<String> = None;
+    let mut path: Option<String> = None;
+    let mut relative = true;
+    // The trailing section flushes the last profile
+    for line in ini.lines().chain(["[End]"]) {
+        let line = line.trim();
+        if line.starts_with('[') {
+            if let (true, Some(path)) = (section, path.take()) {
+                let path = if relative {
+                    dir.join(&path)
+                } else {
+                    PathBuf::from(&path)
+                };
+                let name = name.take().unwrap_or_else(|| {
+                    path.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default()
+                });
+                profiles.push((name, path));
+            }
+            section = line.starts_with("[Profile");
+            name = None;
+            relative = true;
+            continue;
+        }
+        match line.split_once('=') {
+            Some(("Name", value)) => name = Some(value.to_string()),
+            Some(("Path", value)) => path = Some(value.to_string()),
+            Some(("IsRelative", value)) => relative = value == "1",
+            _ => {}
+        }
+    }
+    profiles.retain(|(_, path)| path.join("places.sqlite").is_file());
+    profiles
+}
+
+// `Default` and `Profile N` folders, named after what the user called them in `Local State`
+fn chromium_profiles(dir: &Path) -> Vec<(String, PathBuf)> {
+    let names: HashMap<String, String> = fs::read(dir.join("Local State"))
+        .ok()
+        .and_then(|state| serde_json::from_slice::<Value>(&state).ok())
+        .and_then(|state| {
+            let cache = state.get("profile")?.get("info_cache")?.as_object()?;
+            Some(
+                cache
+                    .iter()
+                    .filter_map(|(dir, info)| {
+                        Some((dir.clone(), info.get("name")?.as_str()?.to_string()))
+                    })
+                    .collect(),
+            )
+        })
+        .unwrap_or_default();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut profiles: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let dir = entry.file_name().to_string_lossy().to_string();
+            if dir != "Default" && !dir.starts_with("Profile ") {
+                return None;
+            }
+            let path = entry.path();
+            if !path.join("Bookmarks").is_file() && !path.join("History").is_file() {
+                return None;
+            }
+            Some((names.get(&dir).cloned().unwrap_or(dir), path))
+        })
+        .collect();
+    profiles.sort_unstable_by(|a, b| a.1.cmp(&b.1));
+    profiles
+}
+
+/// Every profile of the known browsers found in the home folder.
+pub fn profiles() -> Vec<Profile> {
+    let mut profiles = vec![];
+    for browser in BROWSERS {
+        let found: Vec<(String, PathBuf)> = browser
+            .dirs
+            .iter()
+            .map(|dir| paths().home.join(dir))
+            .flat_map(|dir| match browser.engine {
+                Engine::Firefox => firefox_profiles(&dir),
+                Engine::Chromium => chromium_profiles(&dir),
+            })
+            .collect();
+        // The profile name only matters when there is more than one
+        let single = found.len() == 1;
+        for (name, path) in found {
+            let folder = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            profiles.push(Profile {
+                key: format!("{}/{}", browser.name, folder),
+                label: if single {
+                    browser.name.to_string()
+                } else {
+                    format!("{} – {}", browser.name, name)
+                },
+                apps: browser.apps,
+                engine: browser.engine,
+                path,
+            });
+        }
+    }
+    profiles
+}
+
+// Browsers lock their databases while running, so a copy in `dir` is read instead.
+// The write-ahead log holds the latest changes, it is copied along when there is one.
+fn copy_database(source: &Path, dir: &Path, key: &str) -> anyhow::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let name: String = key
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    let copy = dir.join(format!("{}.sqlite", name));
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    let with_suffix = |path: &Path, suffix: &str| {
+        let mut path = path.as_os_str().to_owned();
+        path.push(suffix);
+        PathBuf::from(path)
+    };
+    for suffix in ["", "-wal"] {
+        let source = with_suffix(source, suffix);
+        let target = with_suffix(&copy, suffix);
+        if !source.is_file() {
+            let _ = fs::remove_file(&target);
+            continue;
+        }
+        if modified(&target) < modified(&source) {
+            fs::copy(&source, &target)?;
+        }
+    }
+    Ok(copy)
+}
+
+fn timestamp(micros: i64) -> Option<OffsetDateTime> {
+    OffsetDateTime::from_unix_timestamp_nanos(micros as i128 * 1000).ok()
+}
+
+fn firefox_entries(path: &Path, cache: &Path, key: &str) -> anyhow::Result<Vec<Entry>> {
+    let connection = Connection::open(copy_database(&path.join("places.sqlite"), cache, key)?)?;
+    let mut entries = vec![];
+    let mut statement = connection.prepare(
+        "SELECT b.title, p.url FROM moz_bookmarks b JOIN moz_places p ON p.id = b.fk
+         WHERE b.type = 1 AND p.url NOT LIKE 'place:%'",
+    )?;
+    entries.extend(
+        statement
+            .query_map([], |row| {
+                Ok(Entry {
+                    title: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                    url: row.get(1)?,
+                    kind: Kind::Bookmark,
+                    visited: None,
+                })
+            })?
+            .flatten(),
+    );
+    let mut statement = connection.prepare(
+        "SELECT title, url, last_visit_date FROM moz_places
+         WHERE hidden = 0 AND last_visit_date IS NOT NULL
+         ORDER BY last_visit_date DESC LIMIT ?1",
+    )?;
+    entries.extend(
+        statement
+            .query_map([MAX_HISTORY], |row| {
+                Ok(Entry {
+                    title: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                    url: row.get(1)?,
+                    kind: Kind::History,
+                    visited: timestamp(row.get(2)?),
+                })
+            })?
+            .flatten(),
+    );
+    Ok(entries)
+}
+
+// Bookmarks are nested in folders below the bookmark bar, other bookmarks and the synced ones
+fn chromium_bookmarks(node: &Value, entries: &mut Vec<Entry>) {
+    if node.get("type").and_then(Value::as_str) == Some("url") {
+        if let Some(url) = node.get("url").and_then(Value::as_str) {
+            entries.push(Entry {
+                title: node
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                url: url.to_string(),
+                kind: Kind::Bookmark,
+                visited: None,
+            });
+        }
+    }
+    let children = node
+        .get("children")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+    let roots = node
+        .get("roots")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|roots| roots.values());
+    for child in children.chain(roots) {
+        chromium_bookmarks(child, entries);
+    }
+}
+
+fn chromium_entries(path: &Path, cache: &Path, key: &str) -> anyhow::Result<Vec<Entry>> {
+    let mut entries = vec![];
+    if let Ok(bookmarks) = fs::read(path.join("Bookmarks")) {
+        chromium_bookmarks(&serde_json::from_slice(&bookmarks)?, &mut entries);
+    }
+    let history = path.join("History");
+    if !history.is_file() {
+        return Ok(entries);
+    }
+    let connection = Connection::open(copy_database(&history, cache, key)?)?;
+    let mut statement = connection.prepare(
+        "SELECT title, url, last_visit_time FROM urls
+         WHERE hidden = 0 ORDER BY last_visit_time DESC LIMIT ?1",
+    )?;
+    entries.extend(
+        statement
+            .query_map([MAX_HISTORY], |row| {
+                Ok(Entry {
+                    title: row.get(0)?,
+                    url: row.get(1)?,
+                    kind: Kind::History,
+                    visited: timestamp(row.get::<_, i64>(2)? - CHROMIUM_EPOCH_OFFSET),
+                })
+            })?
+            .flatten(),
+    );
+    Ok(entries)
+}
+
+impl Profile {
+    /// Bookmarks followed by the most recent history, blocks while the databases are copied and read.
+    pub fn entries(&self) -> anyhow::Result<Vec<Entry>> {
+        let cache = paths().cache.join("browsers");
+        match self.engine {
+            Engine::Firefox => firefox_entries(&self.path, &cache, &self.key),
+            Engine::Chromium => chromium_entries(&self.path, &cache, &self.key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A folder for the browser files and the database copies in `cache`
+    struct Fixture {
+        dir: PathBuf,
+        cache: PathBuf,
+        key: String,
+    }
+
+    impl Fixture {
+        fn new(name: &str) -> Self {
+            let key = format!("loungy-test-{}-{}", name, std::process::id());
+            let dir = std::env::temp_dir().join(&key);
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let cache = dir.join("cache");
+            Self { dir, cache, key }
+        }
+        fn write(&self, path: &str, contents: &str) -> PathBuf {
+            let path = self.dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, contents).unwrap();
+            path
+        }
+        fn database(&self, path: &str, sql: &str) -> Connection {
+            let path = self.dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let connection = Connection::open(path).unwrap();
+            connection.execute_batch(sql).unwrap();
+            connection
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn unix(seconds: i64) -> Option<OffsetDateTime> {
+        Some(OffsetDateTime::from_unix_timestamp(seconds).unwrap())
+    }
+
+    static PLACES: &str = "
+        CREATE TABLE moz_places (
+            id INTEGER PRIMARY KEY, url TEXT, title TEXT, hidden INTEGER, last_visit_date INTEGER
+        );
+        CREATE TABLE moz_bookmarks (id INTEGER PRIMARY KEY, type INTEGER, fk INTEGER, title TEXT);
+    ";
+
+    #[test]
+    fn firefox_profiles_from_ini() {
+        let fixture = Fixture::new("firefox-profiles");
+        let elsewhere = fixture.dir.join("elsewhere/custom");
+        fixture.write(
+            "firefox/profiles.ini",
+            &format!(
+                "[General]\nStartWithLastProfile=1\n\n\
+                 [Profile0]\nName=default\nIsRelative=1\nPath=abc.default\n\n\
+                 [Profile1]\nName=work\nIsRelative=0\nPath={}\n\n\
+                 [Profile2]\nName=unused\nIsRelative=1\nPath=never.opened\n\n\
+                 [Profile3]\nIsRelative=1\nPath=def.unnamed\n\n\
+                 [Install4F96D1932A9F858E]\nDefault=abc.default\n",
+                elsewhere.display()
+            ),
+        );
+        fixture.write("firefox/abc.default/places.sqlite", "");
+        fixture.write("elsewhere/custom/places.sqlite", "");
+        fixture.write("firefox/def.unnamed/places.sqlite", "");
+        fs::create_dir_all(fixture.dir.join("firefox/never.opened")).unwrap();
+
+        let dir = fixture.dir.join("firefox");
+        assert_eq!(
+            firefox_profiles(&dir),
+            vec![
+                ("default".to_string(), dir.join("abc.default")),
+                ("work".to_string(), elsewhere),
+                ("def.unnamed".to_string(), dir.join("def.unnamed")),
+            ]
+        );
+        assert!(firefox_profiles(&fixture.dir.join("missing")).is_empty());
+    }
+
+    #[test]
+    fn firefox_bookmarks_and_history() {
+        let fixture = Fixture::new("firefox-entries");
+        fixture.database(
+            "profile/places.sqlite",
+            &format!(
+                "{}
+                INSERT INTO moz_places VALUES
+                    (1, 'https://example.com/', 'Example', 0, 1704067200000000),
+                    (2, 'https://rust-lang.org/', NULL, 0, 1704153600000000),
+                    (3, 'https://hidden.example/', 'Hidden', 1, 1704240000000000),
+                    (4, 'place:sort=8', 'Recent Tags', 0, NULL),
+                    (5, 'https://unvisited.example/', 'Unvisited', 0, NULL);
+                INSERT INTO moz_bookmarks VALUES
+                    (1, 2, NULL, 'Toolbar'),
+                    (2, 1, 1, 'Example Bookmark'),
+                    (3, 1, 4, 'Recent Tags'),
+                    (4, 1, 5, NULL);",
+                PLACES
+            ),
+        );
+
+        let entries =
+            firefox_entries(&fixture.dir.join("profile"), &fixture.cache, &fixture.key).unwrap();
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.kind,
+                    entry.title.as_str(),
+                    entry.url.as_str(),
+                    entry.visited,
+                )
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    Kind::Bookmark,
+                    "Example Bookmark",
+                    "https://example.com/",
+                    None
+                ),
+                (Kind::Bookmark, "", "https://unvisited.example/", None),
+                (
+                    Kind::History,
+                    "",
+                    "https://rust-lang.org/",
+                    unix(1704153600)
+                ),
+                (
+                    Kind::History,
+                    "Example",
+                    "https://example.com/",
+                    unix(1704067200)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn chromium_profiles_from_local_state() {
+        let fixture = Fixture::new("chromium-profiles");
+        fixture.write(
+            "chromium/Local State",
+            r#"{"profile": {"info_cache": {
+                "Default": {"name": "Personal"},
+                "Profile 1": {"name": "Work"}
+            }}}"#,
+        );
+        fixture.write("chromium/Default/Bookmarks", "{}");
+        fixture.write("chromium/Profile 1/History", "");
+        fixture.write("chromium/Profile 2/History", "");
+        fixture.write("chromium/System Profile/History", "");
+        fs::create_dir_all(fixture.dir.join("chromium/Profile 3")).unwrap();
+
+        let dir = fixture.dir.join("chromium");
+        assert_eq!(
+            chromium_profiles(&dir),
+            vec![
+                ("Personal".to_string(), dir.join("Default")),
+                ("Work".to_string(), dir.join("Profile 1")),
+                ("Profile 2".to_string(), dir.join("Profile 2")),
+            ]
+        );
+
+        // Without `Local State` the folders are used as names
+        fs::remove_file(dir.join("Local State")).unwrap();
+        let names: Vec<String> = chromium_profiles(&dir)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["Default", "Profile 1", "Profile 2"]);
+    }
+
+    #[test]
+    fn chromium_bookmarks_and_history() {
+        let fixture = Fixture::new("chromium-entries");
+        fixture.write(
+            "profile/Bookmarks",
+            r#"{"roots": {
+                "bookmark_bar": {"type": "folder", "name": "Bar", "children": [
+                    {"type": "url", "name": "Example", "url": "https://example.com/"},
+                    {"type": "folder", "name": "Nested", "children": [
+                        {"type": "url", "name": "Rust", "url": "https://rust-lang.org/"}
+                    ]}
+                ]},
+                "other": {"type": "folder", "name": "Other", "children": []},
+                "synced": {"type": "folder", "name": "Mobile", "children": [
+                    {"type": "url", "url": "https://mobile.example/"}
+                ]}
+            }}"#,
+        );
+        // 2024-01-01 and 2024-01-02 at midnight, counted from 1601
+        fixture.database(
+            "profile/History",
+            "CREATE TABLE urls (
+                id INTEGER PRIMARY KEY, url TEXT, title TEXT, hidden INTEGER, last_visit_time INTEGER
+            );
+            INSERT INTO urls VALUES
+                (1, 'https://example.com/', 'Example', 0, 13348540800000000),
+                (2, 'https://rust-lang.org/', 'Rust', 0, 13348627200000000),
+                (3, 'https://hidden.example/', 'Hidden', 1, 13348713600000000);",
+        );
+
+        let entries =
+            chromium_entries(&fixture.dir.join("profile"), &fixture.cache, &fixture.key).unwrap();
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.kind,
+                    entry.title.as_str(),
+                    entry.url.as_str(),
+                    entry.visited,
+                )
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (Kind::Bookmark, "Example", "https://example.com/", None),
+                (Kind::Bookmark, "Rust", "https://rust-lang.org/", None),
+                (Kind::Bookmark, "", "https://mobile.example/", None),
+                (
+                    Kind::History,
+                    "Rust",
+                    "https://rust-lang.org/",
+                    unix(1704153600)
+                ),
+                (
+                    Kind::History,
+                    "Example",
+                    "https://example.com/",
+                    unix(1704067200)
+                ),
+            ]
+        );
+
+        // Bookmarks alone are enough
+        fs::remove_file(fixture.dir.join("profile/History")).unwrap();
+        let entries =
+            chromium_entries(&fixture.dir.join("profile"), &fixture.cache, &fixture.key).unwrap();
+        assert_eq!(entries.len(), 3);
+    }
+
+    #[test]
+    fn copies_the_write_ahead_log() {
+        let fixture = Fixture::new("wal");
+        // The open connection stands in for the running browser, it keeps the changes in the log
+        let browser = fixture.database(
+            "profile/places.sqlite",
+            &format!(
+                "PRAGMA journal_mode = WAL;
+                {}
+                INSERT INTO moz_places VALUES (1, 'https://example.com/', 'Example', 0, 1704067200000000);",
+                PLACES
+            ),
+        );
+        let source = fixture.dir.join("profile/places.sqlite");
+        assert!(fixture.dir.join("profile/places.sqlite-wal").is_file());
+
+        let copy = copy_database(&source, &fixture.cache, &fixture.key).unwrap();
+        let mut wal = copy.clone().into_os_string();
+        wal.push("-wal");
+        assert!(PathBuf::from(&wal).is_file());
+        let entries =
+            firefox_entries(&fixture.dir.join("profile"), &fixture.cache, &fixture.key).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].url, "https://example.com/");
+
+        // A stale log of the copy is dropped once the browser checkpointed its own
+        drop(browser);
+        assert!(!fixture.dir.join("profile/places.sqlite-wal").exists());
+        fs::write(&wal, "").unwrap();
+        copy_database(&source, &fixture.cache, &fixture.key).unwrap();
+        assert!(!PathBuf::from(&wal).exists());
+    }
+}
@@ -0,0 +1,227 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{
+    cmp::Reverse,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_std::task::{sleep, spawn_blocking};
+use gpui::*;
+use ignore::WalkBuilder;
+use nucleo::{pattern::Atom, Matcher, Utf32Str};
+
+use crate::{
+    components::list::nucleo::{fuzzy_scores_with, CaseMatching, Score},
+    paths::paths,
+    settings::Settings,
+    state::Actions,
+};
+
+static REINDEX_INTERVAL: Duration = Duration::from_secs(15 * 60);
+// Walking stops here, so a root like `/` doesn't exhaust the memory
+static MAX_FILES: usize = 1_000_000;
+static MAX_RESULTS: usize = 100;
+
+#[derive(Clone)]
+pub struct IndexedFile {
+    pub path: PathBuf,
+    pub dir: bool,
+}
+
+impl Score for &IndexedFile {
+    fn score(
+        &self,
+        pattern: &Atom,
+        buf: &mut Vec<char>,
+        matcher: &mut Matcher,
+    ) -> Option<(Self, u16)> {
+        pattern
+            .score(Utf32Str::new(&self.path.to_string_lossy(), buf), matcher)
+            .map(|score| (*self, score))
+    }
+}
+
+fn expand(root: &str) -> PathBuf {
+    if root == "~" {
+        return paths().home.clone();
+    }
+    match root.strip_prefix("~/") {
+        Some(root) => paths().home.join(root),
+        None => PathBuf::from(root),
+    }
+}
+
+fn walk(roots: Vec<PathBuf>, hidden: bool) -> Vec<IndexedFile> {
+    let mut roots = roots.into_iter().filter(|root| root.is_dir());
+    let Some(first) = roots.next() else {
+        return vec![];
+    };
+    let mut builder = WalkBuilder::new(first);
+    for root in roots {
+        builder.add(root);
+    }
+    // .gitignore files apply outside of git repositories too
+    builder
+        .hidden(!hidden)
+        .require_git(false)
+        .follow_links(false);
+    builder
+        .build()
+        .flatten()
+        .filter(|entry| entry.depth() > 0)
+        .take(MAX_FILES)
+        .map(|entry| IndexedFile {
+            dir: entry.file_type().is_some_and(|kind| kind.is_dir()),
+            path: entry.into_path(),
+        })
+        .collect()
+}
+
+fn best(query: &str, files: &[IndexedFile], removed: &[PathBuf]) -> Vec<IndexedFile> {
+    let files = files
+        .iter()
+        .filter(|file| !removed.iter().any(|path| file.path.starts_with(path)));
+    // The shared matcher would stall every other list while this runs
+    let mut matcher = Matcher::default();
+    let mut scores = fuzzy_scores_with(&mut matcher, query, files, true, CaseMatching::Smart);
+    // Only the best matches are sorted and cloned
+    if scores.len() > MAX_RESULTS {
+        scores.select_nth_unstable_by_key(MAX_RESULTS, |(_, score)| Reverse(*score));
+        scores.truncate(MAX_RESULTS);
+    }
+    scores.sort_by_key(|(_, score)| Reverse(*score));
+    scores.into_iter().map(|(file, _)| file.clone()).collect()
+}
+
+/// Files below the search roots, walked in the background every few minutes and whenever the roots change.
+/// Walking starts once the command is first opened and stops when it gets disabled.
+pub struct FileIndex {
+    walking: bool,
+    files: Arc<Vec<IndexedFile>>,
+    // Moved or deleted since the last walk, which searches skip
+    removed: Vec<PathBuf>,
+    // Bumped whenever the files change, so lists know when to filter again
+    pub generation: u64,
+    // Bumped whenever a search finished
+    pub searches: u64,
+    // The query and generation of the last finished search, shown until the pending one is done
+    results: (String, u64, Vec<IndexedFile>),
+    pending: Option<(String, u64)>,
+}
+
+impl FileIndex {
+    pub fn init(cx: &mut WindowContext) {
+        cx.set_global(Self {
+            walking: false,
+            files: Arc::new(vec![]),
+            removed: vec![],
+            generation: 0,
+            searches: 0,
+            results: (String::new(), 0, vec![]),
+            pending: None,
+        });
+    }
+    pub fn start(cx: &mut AppContext) {
+        if cx.global::<Self>().walking {
+            return;
+        }
+        cx.global_mut::<Self>().walking = true;
+        cx.spawn(|mut cx| async move {
+            let mut indexed: Option<(Vec<PathBuf>, bool, Instant)> = None;
+            loop {
+                let Ok(Some((roots, hidden))) = cx.read_global::<Settings, _>(|settings, _| {
+                    settings.enabled("search_files").then(|| {
+                        (
+                            settings
+                                .file_search_roots
+                                .iter()
+                                .map(|root| expand(root))
+                                .collect::<Vec<_>>(),
+                            settings.file_search_hidden,
+                        )
+                    })
+                }) else {
+                    // The files aren't kept around while nothing can search them
+                    let _ = cx.update_global::<Self, _>(|this, _| {
+                        this.walking = false;
+                        this.files = Arc::new(vec![]);
+                        this.removed.clear();
+                        this.generation += 1;
+                    });
+                    break;
+                };
+                let outdated = indexed.as_ref().map_or(true, |(r, h, at)| {
+                    *r != roots || *h != hidden || at.elapsed() > REINDEX_INTERVAL
+                });
+                if outdated {
+                    indexed = Some((roots.clone(), hidden, Instant::now()));
+                    let files = spawn_blocking(move || walk(roots, hidden)).await;
+                    let _ = cx.update_global::<Self, _>(|this, _| {
+                        this.files = Arc::new(files);
+                        this.removed.clear();
+                        this.generation += 1;
+                    });
+                }
+                sleep(Duration::from_secs(1)).await;
+            }
+        })
+        .detach();
+    }
+    /// The best matches for the query, nothing while it is empty.
+    /// Searching runs in the background, the list is updated through `actions` once it finished.
+    pub fn search(query: &str, actions: &Actions, cx: &mut AppContext) -> Vec<IndexedFile> {
+        if query.trim().is_empty() {
+            return vec![];
+        }
+        let this = cx.global::<Self>();
+        let search = (query.to_string(), this.generation);
+        let (q, g, results) = &this.results;
+        let done = *q == search.0 && *g == search.1;
+        if done || this.pending.as_ref() == Some(&search) {
+            return results.clone();
+        }
+        let results = results.clone();
+        let files = this.files.clone();
+        let removed = this.removed.clone();
+        cx.global_mut::<Self>().pending = Some(search.clone());
+        let actions = actions.clone();
+        cx.spawn(|mut cx| async move {
+            let found = {
+                let query = search.0.clone();
+                spawn_blocking(move || best(&query, &files, &removed)).await
+            };
+            let _ = cx.update_global::<Self, _>(|this, _| {
+                // A newer search was started meanwhile
+                if this.pending.as_ref() != Some(&search) {
+                    return;
+                }
+                this.pending = None;
+                this.results = (search.0, search.1, found);
+                this.searches += 1;
+                actions.update();
+            });
+        })
+        .detach();
+        results
+    }
+    /// Hides a file that was moved or deleted from searches, until the next walk.
+    pub fn remove(path: &Path, cx: &mut AppContext) {
+        cx.update_global::<Self, _>(|this, _| {
+            this.removed.push(path.to_path_buf());
+            this.generation += 1;
+        });
+    }
+}
+
+impl Global for FileIndex {}
@@ -0,0 +1,266 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{
+    cell::Cell,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use gpui::*;
+use log::error;
+use url::Url;
+
+use crate::{
+    commands::{root::apps::ApplicationIndex, RootCommand, RootCommandBuilder},
+    components::{
+        list::{Item, ItemBuilder, ListBuilder, ListItem},
+        shared::{Icon, Img},
+    },
+    paths::paths,
+    platform::{move_to_trash, open_with, reveal_in_file_manager},
+    settings::Settings,
+    state::{Action, Shortcut, StateItem, StateModel, StateViewBuilder, StateViewContext},
+    window::Window,
+};
+
+use super::{
+    index::{FileIndex, IndexedFile},
+    preview::{is_image, FilePreview},
+};
+
+// Paths in the home folder are shown relative to it
+fn display_path(path: &Path) -> String {
+    match path.strip_prefix(&paths().home) {
+        Ok(path) => format!("~/{}", path.display()),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+#[derive(Clone)]
+struct OpenWithBuilder {
+    path: PathBuf,
+}
+
+impl StateViewBuilder for OpenWithBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        context.query.set_placeholder("Search applications...", cx);
+        let path = self.path.clone();
+        ListBuilder::new()
+            .build(
+                move |_, _, cx| {
+                    let apps = cx.global::<ApplicationIndex>().apps.read(cx).clone();
+                    Ok(Some(
+                        apps.into_iter()
+                            .map(|app| {
+                                let path = path.clone();
+                                let id = app.id.clone();
+                                ItemBuilder::new(
+                                    app.id.clone(),
+                                    ListItem::new(
+                                        Some(app.icon.clone()),
+                                        app.name.clone(),
+                                        None,
+                                        vec![],
+                                    ),
+                                )
+                                .keywords(vec![app.name.clone()])
+                                .actions(vec![Action::new(
+                                    Img::default().icon(Icon::ArrowUpRightFromSquare),
+                                    "Open",
+                                    None,
+                                    move |actions, cx| {
+                                        let terminal = cx.global::<Settings>().terminal.clone();
+                                        let target = path.to_string_lossy();
+                                        if let Err(err) =
+                                            open_with(&id, &target, terminal.as_deref())
+                                        {
+                                            error!("Failed to open file: {}", err);
+                                            actions.toast.error(err, cx);
+                                            return;
+                                        }
+                                        Window::close(cx);
+                                    },
+                                    false,
+                                )])
+                                .build()
+                            })
+                            .collect(),
+                    ))
+                },
+                context,
+                cx,
+            )
+            .into()
+    }
+}
+
+fn item(file: IndexedFile) -> Item {
+    let path = file.path.clone();
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| display_path(&path));
+    let icon = if file.dir {
+        Icon::Folder
+    } else if is_image(&path) {
+        Icon::FileImage
+    } else {
+        Icon::File
+    };
+    ItemBuilder::new(
+        display_path(&path),
+        ListItem::new(
+            Some(Img::default().icon(icon)),
+            name,
+            path.parent().map(display_path),
+            vec![],
+        ),
+    )
+    .keywords(vec![display_path(&path)])
+    .preview(0.5, {
+        let path = path.clone();
+        move |cx| StateItem::init(FilePreview::init(path.clone()), false, cx)
+    })
+    .actions(vec![
+        Action::new(
+            Img::default().icon(Icon::ArrowUpRightFromSquare),
+            "Open",
+            None,
+            {
+                let path = path.clone();
+                move |actions, cx| match Url::from_file_path(&path) {
+                    Ok(url) => {
+                        cx.open_url(url.as_str());
+                        Window::close(cx);
+                    }
+                    Err(_) => actions.toast.error("Invalid path", cx),
+                }
+            },
+            false,
+        ),
+        Action::new(
+            Img::default().icon(Icon::AppWindow),
+            "Open With",
+            Some(Shortcut::new("o").cmd()),
+            {
+                let path = path.clone();
+                move |_, cx| {
+                    let builder = OpenWithBuilder { path: path.clone() };
+                    StateModel::update(|this, cx| this.push(builder, cx), cx);
+                }
+            },
+            false,
+        ),
+        Action::new(
+            Img::default().icon(Icon::FolderOpen),
+            "Reveal in File Manager",
+            Some(Shortcut::new("enter").cmd()),
+            {
+                let path = path.clone();
+                move |actions, cx| {
+                    if let Err(err) = reveal_in_file_manager(&path) {
+                        error!("Failed to reveal file: {}", err);
+                        actions.toast.error(err, cx);
+                        return;
+                    }
+                    Window::close(cx);
+                }
+            },
+            false,
+        ),
+        Action::new(
+            Img::default().icon(Icon::Copy),
+            "Copy Path",
+            Some(Shortcut::new("c").cmd().shift()),
+            {
+                let path = path.clone();
+                move |actions, cx| {
+                    cx.write_to_clipboard(ClipboardItem::new(path.to_string_lossy().to_string()));
+                    actions
+                        .toast
+                        .floating("Copied path to clipboard", Some(Icon::Clipboard), cx);
+                }
+            },
+            false,
+        ),
+        Action::new(
+            Img::default().icon(Icon::Trash),
+            "Move to Trash",
+            Some(Shortcut::new("backspace").cmd()),
+            move |actions, cx| {
+                if let Err(err) = move_to_trash(&path) {
+                    error!("Failed to move {} to the trash: {}", path.display(), err);
+                    actions.toast.error("Failed to move to the trash", cx);
+                    return;
+                }
+                FileIndex::remove(&path, cx);
+                actions.toast.success("Moved to the trash", cx);
+                actions.update();
+            },
+            false,
+        ),
+    ])
+    .build()
+}
+
+#[derive(Clone)]
+struct FilesBuilder;
+
+impl StateViewBuilder for FilesBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        context.query.set_placeholder("Search files...", cx);
+        FileIndex::start(cx);
+        // The index is walked and searched in the background, the list is filtered again once either finished
+        let version = Cell::new(None);
+        ListBuilder::new()
+            .interval(Duration::from_secs(1))
+            .filter(|this, cx| {
+                let query = this.query.get_text(cx);
+                FileIndex::search(&query, &this.actions, cx)
+                    .into_iter()
+                    .map(item)
+                    .collect()
+            })
+            .build(
+                move |_, _, cx| {
+                    let index = cx.global::<FileIndex>();
+                    let current = Some((index.generation, index.searches));
+                    if version.replace(current) == current {
+                        return Ok(None);
+                    }
+                    Ok(Some(vec![]))
+                },
+                context,
+                cx,
+            )
+            .into()
+    }
+}
+
+pub struct FilesCommandBuilder;
+
+impl RootCommandBuilder for FilesCommandBuilder {
+    fn build(&self, cx: &mut WindowContext) -> RootCommand {
+        FileIndex::init(cx);
+        RootCommand::new(
+            "search_files",
+            "Search Files",
+            "Loungy",
+            Icon::FileSearch,
+            vec!["Find", "Folders", "Documents"],
+            None,
+            Box::new(|_, cx| {
+                StateModel::update(|this, cx| this.push(FilesBuilder, cx), cx);
+            }),
+        )
+    }
+}
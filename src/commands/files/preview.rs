@@ -0,0 +1,219 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use gpui::*;
+use time::OffsetDateTime;
+
+use crate::{
+    components::shared::{Icon, Img, ImgSize},
+    date::format_date,
+    state::{StateViewBuilder, StateViewContext},
+    theme::Theme,
+};
+
+static IMAGES: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp", "svg"];
+// Only the start of text files is read, the preview can't show more anyway
+static MAX_TEXT: u64 = 16 * 1024;
+static MAX_ENTRIES: usize = 100;
+
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| IMAGES.contains(&ext.as_str()))
+}
+
+fn format_size(bytes: u64) -> String {
+    let mut size = bytes as f64;
+    for unit in ["B", "KB", "MB", "GB"] {
+        if size < 1024.0 {
+            return if unit == "B" {
+                format!("{} {}", bytes, unit)
+            } else {
+                format!("{:.1} {}", size, unit)
+            };
+        }
+        size /= 1024.0;
+    }
+    format!("{:.1} TB", size)
+}
+
+#[derive(Clone)]
+enum Content {
+    Text(String),
+    Image,
+    // Names of the entries along with whether they are folders
+    Directory(Vec<(String, bool)>),
+    None,
+}
+
+fn read_text(path: &Path) -> Option<String> {
+    let mut buf = vec![];
+    fs::File::open(path)
+        .ok()?
+        .take(MAX_TEXT)
+        .read_to_end(&mut buf)
+        .ok()?;
+    // Binary files usually have a NUL byte early on
+    if buf.contains(&0) {
+        return None;
+    }
+    // The limit can cut a character in half
+    Some(String::from_utf8_lossy(&buf).to_string())
+}
+
+fn read_dir(path: &Path) -> Vec<(String, bool)> {
+    let mut entries: Vec<(String, bool)> = fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| {
+                    (
+                        entry.file_name().to_string_lossy().to_string(),
+                        entry.file_type().is_ok_and(|kind| kind.is_dir()),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // Folders first
+    entries.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    entries
+}
+
+#[derive(Clone)]
+pub struct FilePreview {
+    path: PathBuf,
+    content: Content,
+    table: Vec<(String, String)>,
+}
+
+impl FilePreview {
+    pub fn init(path: PathBuf) -> Self {
+        let metadata = fs::metadata(&path).ok();
+        let dir = metadata.as_ref().is_some_and(|metadata| metadata.is_dir());
+        let content = if dir {
+            Content::Directory(read_dir(&path))
+        } else if is_image(&path) {
+            Content::Image
+        } else {
+            read_text(&path).map(Content::Text).unwrap_or(Content::None)
+        };
+        let mut table = vec![];
+        if let Content::Directory(entries) = &content {
+            table.push(("Items".to_string(), entries.len().to_string()));
+        } else if let Some(metadata) = &metadata {
+            table.push(("Size".to_string(), format_size(metadata.len())));
+        }
+        if let Some(modified) = metadata.and_then(|metadata| metadata.modified().ok()) {
+            table.push((
+                "Modified".to_string(),
+                format_date(&OffsetDateTime::from(modified)),
+            ));
+        }
+        Self {
+            path,
+            content,
+            table,
+        }
+    }
+}
+
+impl Render for FilePreview {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let content = match &self.content {
+            Content::Text(text) => div()
+                .p_2()
+                .w_full()
+                .font(theme.font_mono.clone())
+                .child(text.clone())
+                .into_any_element(),
+            Content::Image => div()
+                .p_2()
+                .size_full()
+                .child(
+                    img(ImageSource::File(Arc::new(self.path.clone())))
+                        .object_fit(ObjectFit::Contain)
+                        .size_full(),
+                )
+                .into_any_element(),
+            Content::Directory(entries) => div()
+                .p_2()
+                .flex()
+                .flex_col()
+                .children(entries.iter().take(MAX_ENTRIES).map(|(name, dir)| {
+                    div()
+                        .flex()
+                        .items_center()
+                        .child(
+                            div().mr_1().child(
+                                Img::default()
+                                    .icon(if *dir { Icon::Folder } else { Icon::File })
+                                    .size(ImgSize::XS),
+                            ),
+                        )
+                        .child(name.clone())
+                }))
+                .into_any_element(),
+            Content::None => div()
+                .size_full()
+                .flex()
+                .items_center()
+                .justify_center()
+                .text_color(theme.subtext0)
+                .child("No preview available")
+                .into_any_element(),
+        };
+        div()
+            .ml_2()
+            .pl_2()
+            .border_l_1()
+            .border_color(theme.surface0)
+            .h_full()
+            .flex()
+            .flex_col()
+            .justify_between()
+            .text_xs()
+            .child(div().flex_1().overflow_hidden().child(content))
+            .child(
+                div()
+                    .border_t_1()
+                    .border_color(theme.surface0)
+                    .mt_auto()
+                    .p_2()
+                    .children(self.table.iter().map(|(key, value)| {
+                        div()
+                            .flex()
+                            .justify_between()
+                            .child(
+                                div()
+                                    .font_weight(FontWeight::SEMIBOLD)
+                                    .text_color(theme.subtext0)
+                                    .child(key.clone()),
+                            )
+                            .child(div().child(value.clone()).font(theme.font_mono.clone()))
+                    })),
+            )
+    }
+}
+
+impl StateViewBuilder for FilePreview {
+    fn build(&self, _context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        cx.new_view(|_| self.clone()).into()
+    }
+}
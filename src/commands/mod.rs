@@ -20,18 +20,27 @@ use crate::{
         list::{Accessory, Item, ItemBuilder, ListItem},
         shared::{Icon, Img},
     },
+    extensions::Extensions,
     hotkey::HotkeyManager,
-    state::{Action, CloneableFn, Shortcut, StateModel, StateViewBuilder, StateViewContext},
+    settings::Settings,
+    state::{
+        Action, Actions, CloneableFn, Shortcut, StateModel, StateViewBuilder, StateViewContext,
+    },
 };
 
+#[cfg(feature = "plugins")]
+use crate::plugins::manage::ManagePluginsCommandBuilder;
+
 #[cfg(target_os = "macos")]
 use self::root::menu;
-use self::root::{list, process, theme};
+use self::root::{list, manage, process, quicklinks, scripts, snippets, theme};
 
 #[cfg(feature = "bitwarden")]
 mod bitwarden;
+mod browsers;
 #[cfg(feature = "clipboard")]
 mod clipboard;
+mod files;
 #[cfg(feature = "matrix")]
 mod matrix;
 pub mod root;
@@ -41,11 +50,12 @@ mod tailscale;
 #[derive(Clone)]
 pub struct RootCommand {
     id: String,
-    title: String,
+    pub title: String,
     subtitle: String,
-    icon: Icon,
+    icon: Img,
     keywords: Vec<String>,
     shortcut: Option<Shortcut>,
+    accessory: Option<Accessory>,
     pub action: Box<dyn CloneableFn>,
 }
 impl RootCommand {
@@ -53,20 +63,39 @@ impl RootCommand {
         id: impl ToString,
         title: impl ToString,
         subtitle: impl ToString,
-        icon: Icon,
+        icon: impl Into<Img>,
         keywords: Vec<impl ToString>,
         shortcut: Option<Shortcut>,
         action: Box<dyn CloneableFn>,
     ) -> Self {
+        let id = id.to_string();
         Self {
-            id: id.to_string(),
+            id: id.clone(),
             title: title.to_string(),
             subtitle: subtitle.to_string(),
-            icon,
+            icon: icon.into(),
             keywords: keywords.into_iter().map(|s| s.to_string()).collect(),
             shortcut,
-            action,
+            accessory: None,
+            action: Box::new(track(id, action)),
+        }
+    }
+    /// Shown instead of the generic "Command" tag if the command has no hotkey.
+    pub fn accessory(mut self, accessory: Accessory) -> Self {
+        self.accessory = Some(accessory);
+        self
+    }
+}
+
+// Run through the state, so the views the command pushes use its query history
+fn track(id: String, action: Box<dyn CloneableFn>) -> impl CloneableFn + Clone {
+    move |actions: &mut Actions, cx: &mut WindowContext| {
+        if !cx.has_global::<StateModel>() {
+            action(actions, cx);
+            return;
         }
+        let state = cx.global::<StateModel>().clone();
+        state.run(&id, |cx| action(actions, cx), cx);
     }
 }
 
@@ -81,11 +110,16 @@ pub struct RootCommands {
 
 impl RootCommands {
     pub fn init(cx: &mut WindowContext) {
-        let commands: Vec<Box<dyn RootCommandBuilder>> = vec![
+        let mut commands: Vec<Box<dyn RootCommandBuilder>> = vec![
             Box::new(list::LoungyCommandBuilder),
+            Box::new(manage::ManageCommandsCommandBuilder),
+            Box::new(files::list::FilesCommandBuilder),
+            Box::new(browsers::list::BrowsersCommandBuilder),
             #[cfg(target_os = "macos")]
             Box::new(menu::MenuCommandBuilder),
             Box::new(process::ProcessCommandBuilder),
+            Box::new(quicklinks::QuicklinksCommandBuilder),
+            Box::new(snippets::SnippetsCommandBuilder),
             Box::new(theme::ThemeCommandBuilder),
             #[cfg(feature = "tailscale")]
             Box::new(tailscale::list::TailscaleCommandBuilder),
@@ -95,38 +129,70 @@ impl RootCommands {
             Box::new(matrix::list::MatrixCommandBuilder),
             #[cfg(feature = "clipboard")]
             Box::new(clipboard::list::ClipboardCommandBuilder),
+            #[cfg(feature = "plugins")]
+            Box::new(ManagePluginsCommandBuilder),
         ];
+        commands.extend(scripts::ScriptCommandBuilder::discover());
+        commands.extend(Extensions::builders(cx));
         let mut map = HashMap::new();
         for command in commands {
             let command = command.build(cx);
             map.insert(command.id.clone(), command);
         }
         cx.set_global(Self { commands: map });
+        cx.observe_global::<Settings>(Self::refresh).detach();
+    }
+    /// Registers a command after startup, e.g. once a plugin finished loading.
+    #[allow(dead_code)]
+    pub fn insert(command: RootCommand, cx: &mut WindowContext) {
+        cx.update_global::<Self, _>(|this, _| {
+            this.commands.insert(command.id.clone(), command);
+        });
+        Self::refresh(cx);
+    }
+    #[allow(dead_code)]
+    pub fn remove(id: &str, cx: &mut WindowContext) {
+        cx.update_global::<Self, _>(|this, _| {
+            this.commands.remove(id);
+        });
+        Self::refresh(cx);
+    }
+    fn refresh(cx: &mut WindowContext) {
+        if !cx.has_global::<StateModel>() {
+            return;
+        }
+        if let Some(root) = cx.global::<StateModel>().inner.read(cx).stack.first() {
+            root.actions.read(cx).update();
+        }
     }
     pub fn list(cx: &mut WindowContext) -> Vec<Item> {
         let commands = cx.global::<Self>().commands.clone();
+        let settings = cx.global::<Settings>();
         let items: Vec<Item> = commands
             .values()
+            .filter(|command| settings.enabled(&command.id))
             .map(|command| {
                 let mut keywords = vec![command.title.clone(), command.subtitle.clone()];
                 keywords.append(&mut command.keywords.clone());
                 ItemBuilder::new(
                     command.id.clone(),
                     ListItem::new(
-                        Some(Img::default().icon(command.icon.clone())),
+                        Some(command.icon.clone()),
                         command.title.clone(),
                         Some(command.subtitle.clone()),
                         command
                             .shortcut
                             .clone()
-                            .map(|shortcut| vec![Accessory::shortcut(shortcut)])
+                            .map(Accessory::shortcut)
+                            .or(command.accessory.clone())
+                            .map(|accessory| vec![accessory])
                             .unwrap_or(vec![Accessory::new("Command", None)]),
                     ),
                 )
                 .keywords(keywords)
                 .actions(vec![
                     Action::new(
-                        Img::default().icon(command.icon.clone()),
+                        command.icon.clone(),
                         command.title.clone(),
                         None,
                         command.action.clone(),
@@ -164,26 +230,56 @@ pub struct HotkeyBuilder {
     id: String,
 }
 
+impl HotkeyBuilder {
+    pub fn new(id: impl ToString) -> Self {
+        Self { id: id.to_string() }
+    }
+}
+
 impl StateViewBuilder for HotkeyBuilder {
     fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
         let id = self.id.clone();
         let value = HotkeyManager::get(&id).map(Shortcut::new);
+        let leader_key = HotkeyManager::get_leader_key(&id).unwrap_or_default();
         Form::new(
-            vec![Input::new(
-                "hotkey",
-                "Hotkey",
-                InputKind::Shortcut {
-                    tmp: value.clone(),
-                    value,
-                },
-                cx,
-            )],
+            vec![
+                Input::new(
+                    "hotkey",
+                    "Hotkey",
+                    InputKind::Shortcut {
+                        tmp: value.clone(),
+                        value,
+                    },
+                    cx,
+                ),
+                Input::new(
+                    "leader_key",
+                    "Leader Key",
+                    InputKind::TextField {
+                        placeholder: "Key pressed after the leader hotkey, e.g. c".to_string(),
+                        value: leader_key,
+                        password: false,
+                        validate: Some(|v| {
+                            v.trim()
+                                .contains(char::is_whitespace)
+                                .then_some("Single key without modifiers")
+                        }),
+                    },
+                    cx,
+                ),
+            ],
             move |values, actions, cx| {
+                let leader_key = values["leader_key"].value::<String>();
+                if let Err(err) = HotkeyManager::set_leader_key(&id, &leader_key, cx) {
+                    error!("Failed to set leader key: {}", err);
+                    actions.toast.error(err, cx);
+                    return;
+                }
                 let shortcut = values["hotkey"].value::<Option<Shortcut>>();
                 if let Some(shortcut) = shortcut {
                     if let Err(err) = HotkeyManager::set(&id, shortcut.get(), cx) {
                         error!("Failed to set hotkey: {}", err);
-                        actions.toast.error("Failed to set hotkey", cx);
+                        actions.toast.error(err, cx);
                     } else {
                         actions.toast.success("Hotkey set", cx);
                     }
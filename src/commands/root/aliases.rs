@@ -0,0 +1,100 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use anyhow::anyhow;
+use bonsaidb::{
+    core::schema::{Collection, SerializedCollection},
+    local::Database,
+};
+use gpui::*;
+use serde::{Deserialize, Serialize};
+
+use crate::db::Db;
+
+#[derive(Clone, Serialize, Deserialize, Collection)]
+#[collection(name = "aliases")]
+struct Alias {
+    // ID of the command or application
+    #[natural_id]
+    id: String,
+    alias: String,
+}
+
+fn db() -> &'static Database {
+    static DB: OnceLock<Database> = OnceLock::new();
+    DB.get_or_init(Db::init_collection::<Alias>)
+}
+
+fn normalize(alias: &str) -> String {
+    alias.trim().to_lowercase()
+}
+
+/// Short names for root list items like "ff" for Firefox, which are matched exactly before the fuzzy results.
+pub struct Aliases {
+    aliases: HashMap<String, String>,
+}
+
+impl Aliases {
+    pub fn init(cx: &mut AppContext) {
+        let aliases = Alias::all(db())
+            .query()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|doc| (doc.contents.id, doc.contents.alias))
+            .collect();
+        cx.set_global(Self { aliases });
+    }
+    pub fn get<'a>(id: &str, cx: &'a AppContext) -> Option<&'a String> {
+        cx.global::<Self>().aliases.get(id)
+    }
+    /// Returns the ID of the item with this alias.
+    pub fn find<'a>(query: &str, cx: &'a AppContext) -> Option<&'a String> {
+        let query = normalize(query);
+        if query.is_empty() {
+            return None;
+        }
+        cx.global::<Self>()
+            .aliases
+            .iter()
+            .find(|(_, alias)| **alias == query)
+            .map(|(id, _)| id)
+    }
+    /// Sets the alias of an item, an empty alias removes it.
+    pub fn set(id: &str, alias: &str, cx: &mut AppContext) -> anyhow::Result<()> {
+        let alias = normalize(alias);
+        if Self::find(&alias, cx).is_some_and(|other| other != id) {
+            return Err(anyhow!("Alias {} is already in use", alias));
+        }
+        if alias.is_empty() {
+            if let Some(doc) = Alias::get(&id.to_string(), db())? {
+                doc.delete(db())?;
+            }
+        } else {
+            Alias {
+                id: id.to_string(),
+                alias: alias.clone(),
+            }
+            .overwrite_into(&id.to_string(), db())?;
+        }
+        cx.update_global::<Self, _>(|this, _| {
+            if alias.is_empty() {
+                this.aliases.remove(id);
+            } else {
+                this.aliases.insert(id.to_string(), alias);
+            }
+        });
+        Ok(())
+    }
+}
+
+impl Global for Aliases {}
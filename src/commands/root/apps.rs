@@ -0,0 +1,383 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    thread,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use async_std::task::{sleep, spawn_blocking};
+use bonsaidb::{
+    core::schema::{Collection, SerializedCollection},
+    local::Database,
+};
+use crossbeam_channel::Receiver;
+use gpui::*;
+use log::error;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commands::HotkeyBuilder,
+    components::{
+        list::{Accessory, AsyncListItems, Item, ItemBuilder, ListItem},
+        shared::{Icon, Img},
+    },
+    db::Db,
+    platform::{
+        get_application_data, get_application_environment, get_application_files,
+        get_application_folders, AppAction, AppData,
+    },
+    state::{Action, StateModel},
+    window::Window,
+};
+#[cfg(target_os = "linux")]
+use crate::{
+    platform::launch_application,
+    settings::Settings,
+    state::{Actions, CloneableFn},
+};
+
+// Folders are rescanned this often even without changes, in case the watcher missed one
+static RESCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Serialize, Deserialize)]
+struct IndexedAppData {
+    id: String,
+    name: String,
+    description: Option<String>,
+    icon_path: PathBuf,
+    keywords: Vec<String>,
+    actions: Vec<AppAction>,
+    tag: String,
+}
+
+impl From<AppData> for IndexedAppData {
+    fn from(data: AppData) -> Self {
+        Self {
+            id: data.id,
+            name: data.name,
+            description: data.description,
+            icon_path: data.icon_path,
+            keywords: data.keywords,
+            actions: data.actions,
+            tag: data.tag,
+        }
+    }
+}
+
+impl From<IndexedAppData> for AppData {
+    fn from(data: IndexedAppData) -> Self {
+        let icon = if data.icon_path.as_os_str().is_empty() {
+            Img::default().icon(Icon::AppWindow)
+        } else {
+            Img::default().file(data.icon_path.clone())
+        };
+        Self {
+            id: data.id,
+            name: data.name,
+            description: data.description,
+            icon,
+            icon_path: data.icon_path,
+            keywords: data.keywords,
+            actions: data.actions,
+            tag: data.tag,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Collection)]
+#[collection(name = "applications")]
+struct IndexedApplication {
+    #[natural_id]
+    path: String,
+    // Modification time in nanoseconds since the unix epoch
+    modified: u64,
+    // What the parsed data depends on besides the file, like the locale
+    environment: String,
+    data: IndexedAppData,
+}
+
+fn db() -> &'static Database {
+    static DB: OnceLock<Database> = OnceLock::new();
+    DB.get_or_init(Db::init_collection::<IndexedApplication>)
+}
+
+fn modified(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64)
+}
+
+/// Brings the index up to date with the application folders and returns all indexed applications.
+/// Only files that are new or were modified since they were last indexed get parsed.
+/// Files that aren't launchable applications aren't indexed, as that can change without them changing.
+fn index() -> Vec<(PathBuf, AppData)> {
+    let environment = get_application_environment();
+    let mut indexed: HashMap<String, IndexedApplication> = IndexedApplication::all(db())
+        .query()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|doc| (doc.contents.path.clone(), doc.contents))
+        .collect();
+    let mut apps = vec![];
+    for path in get_application_files() {
+        let Some(modified) = modified(&path) else {
+            continue;
+        };
+        let key = path.to_string_lossy().to_string();
+        if let Some(app) = indexed
+            .get(&key)
+            .filter(|app| app.modified == modified && app.environment == environment)
+        {
+            apps.push((path, app.data.clone().into()));
+            indexed.remove(&key);
+            continue;
+        }
+        let Some(data) = get_application_data(&path) else {
+            continue;
+        };
+        indexed.remove(&key);
+        let app = IndexedApplication {
+            path: key.clone(),
+            modified,
+            environment: environment.clone(),
+            data: data.into(),
+        };
+        if let Err(err) = app.clone().overwrite_into(&key, db()) {
+            error!("Failed to index application: {}", err);
+        }
+        apps.push((path, app.data.into()));
+    }
+    // Whatever wasn't found anymore has been uninstalled, is shadowed or isn't launchable now
+    for key in indexed.into_keys() {
+        if let Ok(Some(app)) = IndexedApplication::get(&key, db()) {
+            if let Err(err) = app.delete(db()) {
+                error!("Failed to remove application from index: {}", err);
+            }
+        }
+    }
+    apps
+}
+
+fn watch(
+    watcher: &mut Option<RecommendedWatcher>,
+    watched: &mut HashSet<PathBuf>,
+) -> Option<Receiver<()>> {
+    let mut receiver = None;
+    if watcher.is_none() {
+        let (sender, r) = crossbeam_channel::unbounded();
+        match notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if event.is_ok_and(|event| !event.kind.is_access()) {
+                let _ = sender.send(());
+            }
+        }) {
+            Ok(w) => {
+                *watcher = Some(w);
+                receiver = Some(r);
+            }
+            Err(err) => {
+                error!("Failed to watch application folders: {}", err);
+                return None;
+            }
+        }
+    }
+    let watcher = watcher.as_mut()?;
+    // Folders that don't exist yet can't be watched, so they are retried on every rescan
+    for folder in get_application_folders() {
+        if !watched.contains(&folder)
+            && folder.exists()
+            && watcher.watch(&folder, RecursiveMode::Recursive).is_ok()
+        {
+            watched.insert(folder);
+        }
+    }
+    receiver
+}
+
+/// Waits until something changed in the application folders, or for the periodic rescan to be due.
+async fn changed(receiver: &Option<Receiver<()>>) {
+    let Some(receiver) = receiver.clone() else {
+        sleep(RESCAN_INTERVAL).await;
+        return;
+    };
+    spawn_blocking(move || {
+        if receiver.recv_timeout(RESCAN_INTERVAL).is_ok() {
+            // Installers usually touch several files in a row, let them settle first
+            thread::sleep(Duration::from_millis(250));
+            while receiver.try_recv().is_ok() {}
+        }
+    })
+    .await;
+}
+
+#[cfg(target_os = "linux")]
+fn launch(path: PathBuf, action: Option<String>) -> impl CloneableFn + Clone {
+    move |actions: &mut Actions, cx: &mut WindowContext| {
+        let terminal = cx.global::<Settings>().terminal.clone();
+        if let Err(err) = launch_application(&path, action.as_deref(), None, terminal.as_deref()) {
+            error!("Failed to launch application: {}", err);
+            actions.toast.error("Failed to launch application", cx);
+        } else {
+            Window::close(cx);
+        }
+    }
+}
+
+fn items(apps: Vec<(PathBuf, AppData)>) -> Vec<Item> {
+    let mut items = HashMap::<String, Item>::new();
+    #[cfg_attr(target_os = "macos", allow(unused_variables))]
+    for (path, data) in apps {
+        let mut actions = vec![Action::new(
+            Img::default().icon(Icon::ArrowUpRightFromSquare),
+            format!("Open {}", data.tag.clone()),
+            None,
+            {
+                #[cfg(target_os = "macos")]
+                {
+                    let id = data.id.clone();
+                    let ex = data.tag == "System Setting";
+                    move |_, cx| {
+                        Window::close(cx);
+                        let id = id.clone();
+                        let mut command = std::process::Command::new("open");
+                        if ex {
+                            command.arg(format!("x-apple.systempreferences:{}", id));
+                        } else {
+                            command.arg("-b");
+                            command.arg(id);
+                        }
+                        let _ = command.spawn();
+                    }
+                }
+                #[cfg(target_os = "linux")]
+                {
+                    launch(path.clone(), None)
+                }
+            },
+            false,
+        )];
+        #[cfg(target_os = "linux")]
+        for action in data.actions.iter() {
+            let open = launch(path.clone(), Some(action.id.clone()));
+            actions.push(Action::new(
+                Img::default().icon(Icon::ArrowUpRightFromSquare),
+                action.name.clone(),
+                None,
+                open.clone(),
+                false,
+            ));
+            // Desktop actions are also searchable on their own
+            let title = format!("{}: {}", data.name, action.name);
+            let id = format!("{}:{}", data.id, action.id);
+            let item = ItemBuilder::new(
+                id.clone(),
+                ListItem::new(
+                    Some(data.icon.clone()),
+                    title.clone(),
+                    None,
+                    vec![Accessory::new("Action", None)],
+                ),
+            )
+            .keywords(vec![title, action.name.clone()])
+            .actions(vec![Action::new(
+                Img::default().icon(Icon::ArrowUpRightFromSquare),
+                action.name.clone(),
+                None,
+                open,
+                false,
+            )])
+            .build();
+            items.insert(id, item);
+        }
+        actions.push(Action::new(
+            Img::default().icon(Icon::Keyboard),
+            "Change Hotkey",
+            None,
+            {
+                let id = data.id.clone();
+                move |_, cx| {
+                    let builder = HotkeyBuilder::new(&id);
+                    StateModel::update(|this, cx| this.push(builder, cx), cx);
+                }
+            },
+            false,
+        ));
+        let app = ItemBuilder::new(
+            data.id.clone(),
+            ListItem::new(
+                Some(data.icon.clone()),
+                data.name.clone(),
+                data.description.clone(),
+                vec![Accessory::new(data.tag.clone(), None)],
+            ),
+        )
+        .keywords({
+            let mut keywords = vec![data.name.clone()];
+            keywords.extend(data.keywords.clone());
+            keywords
+        })
+        .actions(actions)
+        .build();
+        items.insert(data.id, app);
+    }
+    let mut items: Vec<Item> = items.into_values().collect();
+    items.sort_unstable_by_key(|a| a.get_keywords()[0].clone());
+    items
+}
+
+/// Installed applications, kept in sync with the application folders by a filesystem watcher.
+pub struct ApplicationIndex {
+    pub view: View<AsyncListItems>,
+    pub apps: Model<Vec<AppData>>,
+}
+
+impl ApplicationIndex {
+    pub fn init(cx: &mut WindowContext) {
+        let apps = cx.new_model(|_| vec![]);
+        let view = cx.new_view(|cx| {
+            let model = apps.downgrade();
+            cx.spawn(|view, mut cx| async move {
+                let mut watcher = None;
+                let mut watched = HashSet::new();
+                let receiver = watch(&mut watcher, &mut watched);
+                loop {
+                    let apps = spawn_blocking(index).await;
+                    let _ = model.update(&mut cx, |model: &mut Vec<AppData>, cx| {
+                        *model = apps.iter().map(|(_, data)| data.clone()).collect();
+                        cx.notify();
+                    });
+                    if view
+                        .update(&mut cx, |view: &mut AsyncListItems, cx| {
+                            view.update("apps".to_string(), items(apps), cx);
+                        })
+                        .is_err()
+                    {
+                        break;
+                    }
+                    changed(&receiver).await;
+                    watch(&mut watcher, &mut watched);
+                }
+            })
+            .detach();
+            AsyncListItems::new()
+        });
+        cx.set_global(Self { view, apps });
+    }
+    pub fn view(cx: &WindowContext) -> View<AsyncListItems> {
+        cx.global::<Self>().view.clone()
+    }
+}
+
+impl Global for ApplicationIndex {}
@@ -0,0 +1,173 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use bonsaidb::{
+    core::schema::{Collection, SerializedCollection},
+    local::Database,
+};
+use gpui::*;
+use log::error;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    components::list::Item,
+    db::Db,
+    state::{Actions, CloneableFn},
+};
+
+// Usage loses half of its weight every week
+static HALF_LIFE: f64 = 60.0 * 60.0 * 24.0 * 7.0;
+// Launching an item from a query is a much stronger signal for that query than overall usage
+static PREFIX_WEIGHT: f64 = 4.0;
+// How many points of fuzzy score usage is worth, on a logarithmic scale
+static USAGE_WEIGHT: f64 = 16.0;
+static MAX_PREFIX_LENGTH: usize = 16;
+
+#[derive(Clone, Serialize, Deserialize, Default)]
+struct Usage {
+    score: f64,
+    // Unix timestamp of the last time the score was updated
+    updated: i64,
+}
+
+impl Usage {
+    fn current(&self, now: i64) -> f64 {
+        let age = (now - self.updated).max(0) as f64;
+        self.score * 0.5f64.powf(age / HALF_LIFE)
+    }
+    fn record(&mut self, now: i64) {
+        self.score = self.current(now) + 1.0;
+        self.updated = now;
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Collection)]
+#[collection(name = "frecency")]
+struct ItemUsage {
+    #[natural_id]
+    id: u64,
+    usage: Usage,
+    prefixes: HashMap<String, Usage>,
+}
+
+fn db() -> &'static Database {
+    static DB: OnceLock<Database> = OnceLock::new();
+    DB.get_or_init(Db::init_collection::<ItemUsage>)
+}
+
+fn now() -> i64 {
+    OffsetDateTime::now_utc().unix_timestamp()
+}
+
+fn normalize(query: &str) -> String {
+    query
+        .trim()
+        .to_lowercase()
+        .chars()
+        .take(MAX_PREFIX_LENGTH)
+        .collect()
+}
+
+/// Launch history of the root list items, used to rank the items that get used often and recently higher.
+pub struct Frecency {
+    items: HashMap<u64, ItemUsage>,
+}
+
+impl Frecency {
+    pub fn init(cx: &mut AppContext) {
+        let items = ItemUsage::all(db())
+            .query()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|doc| (doc.contents.id, doc.contents))
+            .collect();
+        cx.set_global(Self { items });
+    }
+    fn record(id: u64, query: &str, cx: &mut WindowContext) {
+        let now = now();
+        let query = normalize(query);
+        cx.update_global::<Self, _>(|this, _| {
+            let item = this.items.entry(id).or_insert_with(|| ItemUsage {
+                id,
+                usage: Usage::default(),
+                prefixes: HashMap::new(),
+            });
+            item.usage.record(now);
+            // "fir" also counts towards "f" and "fi", so the item surfaces while the query is still being typed
+            for (i, c) in query.char_indices() {
+                item.prefixes
+                    .entry(query[..i + c.len_utf8()].to_string())
+                    .or_default()
+                    .record(now);
+            }
+            item.prefixes.retain(|_, usage| usage.current(now) > 0.01);
+            if let Err(err) = item.clone().overwrite_into(&id, db()) {
+                error!("Failed to save item usage: {}", err);
+            }
+        });
+    }
+    pub fn reset(cx: &mut WindowContext) -> anyhow::Result<()> {
+        for doc in ItemUsage::all(db()).query()? {
+            doc.delete(db())?;
+        }
+        cx.update_global::<Self, _>(|this, _| {
+            this.items.clear();
+        });
+        Ok(())
+    }
+    /// Sorts fuzzy matched items by their score combined with their usage, and makes the default action of every
+    /// item record its usage for the given query.
+    pub fn rank(query: &str, items: Vec<(Item, u16)>, cx: &AppContext) -> Vec<Item> {
+        let now = now();
+        let normalized = normalize(query);
+        let usages = &cx.global::<Self>().items;
+        let mut items: Vec<(Item, f64)> = items
+            .into_iter()
+            .map(|(item, score)| {
+                let usage = usages
+                    .get(&item.get_id())
+                    .map(|usage| {
+                        usage.usage.current(now)
+                            + usage
+                                .prefixes
+                                .get(&normalized)
+                                .map(|prefix| PREFIX_WEIGHT * prefix.current(now))
+                                .unwrap_or_default()
+                    })
+                    .unwrap_or_default();
+                (item, score as f64 + USAGE_WEIGHT * usage.ln_1p())
+            })
+            .collect();
+        items.sort_by(|a, b| b.1.total_cmp(&a.1));
+        items
+            .into_iter()
+            .map(|(mut item, _)| {
+                let id = item.get_id();
+                if let Some(action) = item.get_actions_mut().first_mut() {
+                    action.action = Box::new(track(id, query.to_string(), action.action.clone()));
+                }
+                item
+            })
+            .collect()
+    }
+}
+
+fn track(id: u64, query: String, action: Box<dyn CloneableFn>) -> impl CloneableFn + Clone {
+    move |actions: &mut Actions, cx: &mut WindowContext| {
+        Frecency::record(id, &query, cx);
+        action(actions, cx);
+    }
+}
+
+impl Global for Frecency {}
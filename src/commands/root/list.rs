@@ -9,22 +9,71 @@
  *
  */
 
-use std::{collections::HashMap, path::PathBuf, time::Duration};
+use std::collections::HashSet;
 
 use gpui::*;
+use log::error;
 
 use crate::{
     commands::{RootCommand, RootCommandBuilder, RootCommands},
     components::{
-        list::{nucleo::fuzzy_match, Accessory, Item, ItemBuilder, ListBuilder, ListItem},
+        list::{
+            item_id,
+            nucleo::{fuzzy_scores, CaseMatching},
+            AsyncListItems, ItemBuilder, ListBuilder,
+        },
         shared::{Icon, Img},
     },
-    platform::get_application_data,
-    state::{Action, StateViewBuilder, StateViewContext},
+    history::History,
+    settings::{SearchScope, Settings},
+    state::{Action, Actions, StateModel, StateViewBuilder, StateViewContext},
     window::Window,
 };
 
-use super::numbat::{Numbat, NumbatWrapper};
+use super::{
+    aliases::Aliases,
+    apps::ApplicationIndex,
+    frecency::Frecency,
+    numbat::{Numbat, NumbatWrapper},
+    preferences::PreferencesBuilder,
+    quicklinks::Quicklinks,
+};
+
+fn reopen_last(actions: &mut Actions, cx: &mut WindowContext) {
+    let last = match History::last() {
+        Ok(last) => last,
+        Err(err) => {
+            error!("Failed to load query history: {}", err);
+            actions.toast.error("Failed to load query history", cx);
+            return;
+        }
+    };
+    let Some((command, query)) = last.and_then(|(id, query)| {
+        cx.global::<RootCommands>()
+            .commands
+            .get(&id)
+            .cloned()
+            .map(|command| (command, query))
+    }) else {
+        actions.toast.error("No command to reopen", cx);
+        return;
+    };
+    (command.action)(actions, cx);
+    let Some(query) = query else {
+        return;
+    };
+    // Only if the command opened a view, rather than e.g. running a script
+    let item = cx
+        .global::<StateModel>()
+        .inner
+        .read(cx)
+        .stack
+        .last()
+        .cloned();
+    if let Some(item) = item.filter(|item| item.command.as_ref() == Some(&command.id)) {
+        item.query.downgrade().set_text(query, cx);
+    }
+}
 
 #[derive(Clone)]
 pub struct RootListBuilder;
@@ -35,18 +84,95 @@ impl StateViewBuilder for RootListBuilder {
             .query
             .set_placeholder("Search for apps and commands...", cx);
         let numbat = Numbat::init(&context.query, cx);
-        let commands = RootCommands::list(cx);
+        let scope = cx.global::<Settings>().search_scope;
+        context
+            .actions
+            .set_dropdown(scope.as_str(), SearchScope::options(), cx);
+        context.actions.update_global(
+            vec![
+                Action::new(
+                    Img::default().icon(Icon::History),
+                    "Reopen Last Command",
+                    None,
+                    reopen_last,
+                    false,
+                ),
+                Action::new(
+                    Img::default().icon(Icon::RotateCcw),
+                    "Reset Ranking",
+                    None,
+                    |actions, cx| {
+                        if let Err(err) = Frecency::reset(cx) {
+                            error!("Failed to reset ranking: {}", err);
+                            actions.toast.error("Failed to reset ranking", cx);
+                        } else {
+                            actions.toast.success("Ranking reset", cx);
+                            actions.update();
+                        }
+                    },
+                    false,
+                ),
+            ],
+            cx,
+        );
+        let apps = ApplicationIndex::view(cx);
+        AsyncListItems::loader(&apps, &context.actions, cx);
         ListBuilder::new()
-            .interval(Duration::from_secs(60))
             .filter(move |this, cx| {
-                let mut items = this.items_all.clone();
-                items.append(&mut commands.clone());
+                let scope = SearchScope::parse(&this.actions.get_dropdown_value(cx));
+                let mut items = match scope {
+                    Some(SearchScope::Commands) => vec![],
+                    _ => this.items_all.clone(),
+                };
+                if scope != Some(SearchScope::Applications) {
+                    // Commands can be added and removed at runtime, e.g. by plugins
+                    items.append(&mut RootCommands::list(cx));
+                    items.append(&mut Quicklinks::list(cx));
+                }
+                let settings = cx.global::<Settings>();
+                let mut disabled: HashSet<u64> = settings
+                    .commands
+                    .iter()
+                    .filter(|(_, enabled)| !**enabled)
+                    .map(|(id, _)| item_id(id))
+                    .collect();
+                // Desktop actions of a disabled application are listed on their own too
+                for app in cx.global::<ApplicationIndex>().apps.read(cx) {
+                    if !settings.enabled(&app.id) {
+                        disabled.extend(
+                            app.actions
+                                .iter()
+                                .map(|action| item_id(format!("{}:{}", app.id, action.id))),
+                        );
+                    }
+                }
+                items.retain(|item| !disabled.contains(&item.get_id()));
                 let query = this.query.view.upgrade();
                 if query.is_none() {
                     return vec![];
                 }
                 let query = query.unwrap().read(cx).text.clone();
-                let mut items = fuzzy_match(&query, items, false);
+                let aliased = Aliases::find(&query, cx)
+                    .map(item_id)
+                    .and_then(|id| items.iter().find(|item| item.get_id() == id).cloned());
+                // A quicklink keyword takes the rest of the query as argument, so it can't be fuzzy matched
+                let quicklink = Quicklinks::find(&query, cx).filter(|quicklink| {
+                    items.iter().any(|item| item.get_id() == quicklink.get_id())
+                });
+                let mut items = Frecency::rank(
+                    &query,
+                    fuzzy_scores(&query, items, false, CaseMatching::Smart),
+                    cx,
+                );
+                // An exact alias match comes before everything else
+                if let Some(aliased) = aliased {
+                    items.retain(|item| item.get_id() != aliased.get_id());
+                    items.splice(0..0, Frecency::rank(&query, vec![(aliased, 0)], cx));
+                }
+                if let Some(quicklink) = quicklink {
+                    items.retain(|item| item.get_id() != quicklink.get_id());
+                    items.splice(0..0, Frecency::rank(&query, vec![(quicklink, 0)], cx));
+                }
                 if items.is_empty() {
                     if let Some(result) = numbat.read(cx).result.clone() {
                         items.push(
@@ -82,110 +208,10 @@ impl StateViewBuilder for RootListBuilder {
                 items
             })
             .build(
-                |_, _, _cx| {
-                    {
-                        let user_dir = PathBuf::from("/Users")
-                            .join(whoami::username())
-                            .join("Applications");
-
-                        #[cfg(target_os = "macos")]
-                        let applications_folders = vec![
-                            PathBuf::from("/Applications"),
-                            PathBuf::from("/Applications/Chromium Apps"),
-                            PathBuf::from("/System/Applications/Utilities"),
-                            PathBuf::from("/System/Applications"),
-                            PathBuf::from("/System/Library/CoreServices/Applications"),
-                            PathBuf::from("/Library/PreferencePanes"),
-                            PathBuf::from("/System/Library/ExtensionKit/Extensions"),
-                            user_dir.clone(),
-                            user_dir.clone().join("Chromium Apps.localized"),
-                            // Not sure about the correct path for PWAs
-                            user_dir.clone().join("Chrome Apps.localized"),
-                            user_dir.clone().join("Brave Apps.localized"),
-                        ];
-                        #[cfg(target_os = "linux")]
-                        let applications_folders = vec![
-                            PathBuf::from("/usr/share/applications"),
-                            PathBuf::from("/usr/local/share/applications"),
-                            PathBuf::from("/home")
-                                .join(whoami::username())
-                                .join(".local/share/applications"),
-                        ];
-
-                        let mut apps = HashMap::<String, Item>::new();
-
-                        for applications_folder in applications_folders {
-                            let dir = applications_folder.read_dir();
-                            if dir.is_err() {
-                                continue;
-                            }
-                            for entry in dir.unwrap().flatten() {
-                                let path = entry.path();
-
-                                // search for .icns in Contents/Resources
-                                let data = get_application_data(&path);
-                                if data.is_none() {
-                                    continue;
-                                }
-                                let data = data.unwrap();
-                                let app = ItemBuilder::new(
-                                    data.id.clone(),
-                                    ListItem::new(
-                                        Some(data.icon.clone()),
-                                        data.name.clone(),
-                                        None,
-                                        vec![Accessory::new(data.tag.clone(), None)],
-                                    ),
-                                )
-                                .keywords(vec![data.name.clone()])
-                                .actions(vec![Action::new(
-                                    Img::default().icon(Icon::ArrowUpRightFromSquare),
-                                    format!("Open {}", data.tag.clone()),
-                                    None,
-                                    {
-                                        let id = data.id.clone();
-
-                                        #[cfg(target_os = "macos")]
-                                        {
-                                            let ex = data.tag == "System Setting";
-                                            move |_, cx| {
-                                                Window::close(cx);
-                                                let id = id.clone();
-                                                let mut command =
-                                                    std::process::Command::new("open");
-                                                if ex {
-                                                    command.arg(format!(
-                                                        "x-apple.systempreferences:{}",
-                                                        id
-                                                    ));
-                                                } else {
-                                                    command.arg("-b");
-                                                    command.arg(id);
-                                                }
-                                                let _ = command.spawn();
-                                            }
-                                        }
-                                        #[cfg(target_os = "linux")]
-                                        {
-                                            move |_, cx| {
-                                                Window::close(cx);
-                                                let mut command =
-                                                    std::process::Command::new("gtk-launch");
-                                                command.arg(id.clone());
-                                                let _ = command.spawn();
-                                            }
-                                        }
-                                    },
-                                    false,
-                                )])
-                                .build();
-                                apps.insert(data.id, app);
-                            }
-                        }
-                        let mut apps: Vec<Item> = apps.values().cloned().collect();
-                        apps.sort_unstable_by_key(|a| a.get_keywords()[0].clone());
-                        Ok(Some(apps))
-                    }
+                move |_, _, cx| {
+                    Ok(Some(
+                        apps.read(cx).items.values().flatten().cloned().collect(),
+                    ))
                 },
                 context,
                 cx,
@@ -205,8 +231,8 @@ impl RootCommandBuilder for LoungyCommandBuilder {
             Icon::Rocket,
             vec!["Settings"],
             None,
-            Box::new(|actions, cx| {
-                actions.toast.error("Preferences not yet implemented", cx);
+            Box::new(|_, cx| {
+                StateModel::update(|this, cx| this.push(PreferencesBuilder, cx), cx);
             }),
         )
     }
@@ -0,0 +1,263 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::collections::HashMap;
+
+use gpui::*;
+use log::error;
+
+use crate::{
+    commands::{HotkeyBuilder, RootCommand, RootCommandBuilder, RootCommands},
+    components::{
+        form::{Form, Input, InputKind},
+        list::{Accessory, AsyncListItems, Item, ItemBuilder, ListBuilder, ListItem},
+        shared::{Icon, Img},
+    },
+    hotkey::HotkeyManager,
+    settings::Settings,
+    state::{Action, Actions, Shortcut, StateModel, StateViewBuilder, StateViewContext},
+};
+
+use super::{aliases::Aliases, apps::ApplicationIndex};
+
+#[derive(Clone)]
+struct AliasBuilder {
+    id: String,
+    alias: String,
+    // The list to go back to once the alias is saved
+    parent: Actions,
+}
+
+impl StateViewBuilder for AliasBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        let id = self.id.clone();
+        let parent = self.parent.clone();
+        Form::new(
+            vec![Input::new(
+                "alias",
+                "Alias",
+                InputKind::TextField {
+                    placeholder: "Leave empty to remove the alias...".to_string(),
+                    value: self.alias.clone(),
+                    password: false,
+                    validate: Some(|v| v.contains(' ').then_some("Alias can't contain spaces")),
+                },
+                cx,
+            )],
+            move |values, actions, cx| {
+                let alias = values["alias"].value::<String>();
+                if let Err(err) = Aliases::set(&id, &alias, cx) {
+                    error!("Failed to set alias: {}", err);
+                    actions.toast.error(err, cx);
+                    return;
+                }
+                StateModel::update(|this, cx| this.pop(cx), cx);
+                let mut parent = parent.clone();
+                if alias.trim().is_empty() {
+                    parent.toast.success("Alias removed", cx);
+                } else {
+                    parent.toast.success("Alias set", cx);
+                }
+                parent.update();
+            },
+            context,
+            cx,
+        )
+        .into()
+    }
+}
+
+// Hotkeys and leader keys of every item, read once per list build
+struct Bindings {
+    hotkeys: HashMap<String, Keystroke>,
+    leader_keys: HashMap<String, String>,
+}
+
+impl Bindings {
+    fn load() -> Self {
+        Self {
+            hotkeys: HotkeyManager::get_all(),
+            leader_keys: HotkeyManager::get_all_leader_keys(),
+        }
+    }
+}
+
+fn item(
+    id: String,
+    icon: Img,
+    title: String,
+    subtitle: String,
+    bindings: &Bindings,
+    cx: &AppContext,
+) -> Item {
+    let enabled = cx.global::<Settings>().enabled(&id);
+    let alias = Aliases::get(&id, cx).cloned();
+    let mut accessories = vec![];
+    if let Some(alias) = alias.clone() {
+        accessories.push(Accessory::new(
+            alias,
+            Some(Img::default().icon(Icon::AtSign)),
+        ));
+    }
+    if let Some(hotkey) = bindings.hotkeys.get(&id) {
+        accessories.push(Accessory::shortcut(Shortcut::from(hotkey)));
+    }
+    if let Some(key) = bindings.leader_keys.get(&id) {
+        accessories.push(Accessory::new(
+            format!("Leader {}", key),
+            Some(Img::default().icon(Icon::Keyboard)),
+        ));
+    }
+    if !enabled {
+        accessories.push(Accessory::new(
+            "Disabled",
+            Some(Img::default().icon(Icon::EyeOff)),
+        ));
+    }
+    let actions = vec![
+        Action::new(
+            Img::default().icon(if enabled { Icon::EyeOff } else { Icon::Eye }),
+            if enabled { "Disable" } else { "Enable" },
+            None,
+            {
+                let id = id.clone();
+                move |actions, cx| {
+                    let mut settings = cx.global::<Settings>().clone();
+                    settings.commands.insert(id.clone(), !enabled);
+                    if let Err(err) = settings.save(cx) {
+                        error!("Failed to save preferences: {}", err);
+                        actions.toast.error("Failed to save preferences", cx);
+                    } else if enabled {
+                        actions.toast.success("Disabled", cx);
+                    } else {
+                        actions.toast.success("Enabled", cx);
+                    }
+                    actions.update();
+                }
+            },
+            false,
+        ),
+        Action::new(
+            Img::default().icon(Icon::AtSign),
+            "Set Alias",
+            Some(Shortcut::new("l").cmd()),
+            {
+                let id = id.clone();
+                move |actions, cx| {
+                    let builder = AliasBuilder {
+                        id: id.clone(),
+                        alias: alias.clone().unwrap_or_default(),
+                        parent: actions.clone(),
+                    };
+                    StateModel::update(|this, cx| this.push(builder, cx), cx);
+                }
+            },
+            false,
+        ),
+        Action::new(
+            Img::default().icon(Icon::Keyboard),
+            "Change Hotkey",
+            Some(Shortcut::new("k").cmd()),
+            {
+                let id = id.clone();
+                move |_, cx| {
+                    let builder = HotkeyBuilder::new(&id);
+                    StateModel::update(|this, cx| this.push(builder, cx), cx);
+                }
+            },
+            false,
+        ),
+    ];
+    ItemBuilder::new(
+        id.clone(),
+        ListItem::new(
+            Some(icon),
+            title.clone(),
+            Some(subtitle.clone()),
+            accessories,
+        ),
+    )
+    .keywords(vec![title, subtitle, id])
+    .actions(actions)
+    .build()
+}
+
+#[derive(Clone)]
+struct ManageCommandsBuilder;
+
+impl StateViewBuilder for ManageCommandsBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        context
+            .query
+            .set_placeholder("Search commands and applications...", cx);
+        let apps = cx.global::<ApplicationIndex>().apps.clone();
+        AsyncListItems::loader(&ApplicationIndex::view(cx), &context.actions, cx);
+        ListBuilder::new()
+            .build(
+                move |_, _, cx| {
+                    let cx: &AppContext = cx;
+                    let bindings = Bindings::load();
+                    let mut commands: Vec<RootCommand> = cx
+                        .global::<RootCommands>()
+                        .commands
+                        .values()
+                        .cloned()
+                        .collect();
+                    commands.sort_unstable_by(|a, b| a.title.cmp(&b.title));
+                    let mut items: Vec<Item> = commands
+                        .into_iter()
+                        .map(|command| {
+                            item(
+                                command.id,
+                                command.icon,
+                                command.title,
+                                command.subtitle,
+                                &bindings,
+                                cx,
+                            )
+                        })
+                        .collect();
+                    items.extend(apps.read(cx).iter().map(|app| {
+                        item(
+                            app.id.clone(),
+                            app.icon.clone(),
+                            app.name.clone(),
+                            app.tag.clone(),
+                            &bindings,
+                            cx,
+                        )
+                    }));
+                    Ok(Some(items))
+                },
+                context,
+                cx,
+            )
+            .into()
+    }
+}
+
+pub struct ManageCommandsCommandBuilder;
+
+impl RootCommandBuilder for ManageCommandsCommandBuilder {
+    fn build(&self, _cx: &mut WindowContext) -> RootCommand {
+        RootCommand::new(
+            "manage_commands",
+            "Manage Commands",
+            "Loungy",
+            Icon::ListChecks,
+            vec!["Aliases", "Disable", "Enable", "Hotkeys"],
+            None,
+            Box::new(|_, cx| {
+                StateModel::update(|this, cx| this.push(ManageCommandsBuilder, cx), cx);
+            }),
+        )
+    }
+}
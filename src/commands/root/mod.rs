@@ -9,9 +9,17 @@
  *
  */
 
+pub mod aliases;
+pub mod apps;
+pub mod frecency;
 pub mod list;
+pub mod manage;
 #[cfg(target_os = "macos")]
 pub mod menu;
 pub mod numbat;
+pub mod preferences;
 pub mod process;
+pub mod quicklinks;
+pub mod scripts;
+pub mod snippets;
 pub mod theme;
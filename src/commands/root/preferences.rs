@@ -0,0 +1,231 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use gpui::*;
+use log::error;
+
+use crate::{
+    components::form::{Form, Input, InputKind},
+    hotkey::{HotkeyManager, LEADER},
+    settings::{Placement, SearchScope, Settings, WindowMode},
+    state::{Shortcut, StateViewBuilder, StateViewContext},
+    window::{display_key, WindowPlacement},
+};
+
+fn size(value: &str) -> Option<&str> {
+    match value.parse::<f64>() {
+        Ok(size) if size >= 100.0 => None,
+        _ => Some("At least 100"),
+    }
+}
+
+fn seconds(value: &str) -> Option<&str> {
+    value.parse::<u64>().is_err().then_some("Whole seconds")
+}
+
+fn pixels(value: &str) -> Option<&str> {
+    value
+        .parse::<f64>()
+        .is_err()
+        .then_some("Pixels, negative moves up")
+}
+
+fn text(
+    placeholder: &str,
+    value: impl ToString,
+    validate: Option<fn(&str) -> Option<&str>>,
+) -> InputKind {
+    InputKind::TextField {
+        placeholder: placeholder.to_string(),
+        value: value.to_string(),
+        password: false,
+        validate,
+    }
+}
+
+fn dropdown(value: &str, options: Vec<(&str, &str)>) -> InputKind {
+    InputKind::Dropdown {
+        value: value.to_string(),
+        options: options
+            .into_iter()
+            .map(|(value, label)| (value.to_string(), label.to_string()))
+            .collect(),
+    }
+}
+
+#[derive(Clone)]
+pub struct PreferencesBuilder;
+
+impl StateViewBuilder for PreferencesBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        let settings = cx.global::<Settings>().clone();
+        let hotkey = Keystroke::parse(&settings.hotkey)
+            .ok()
+            .map(|keystroke| Shortcut::from(&keystroke));
+        let leader = settings
+            .leader
+            .as_deref()
+            .and_then(|leader| Keystroke::parse(leader).ok())
+            .map(|keystroke| Shortcut::from(&keystroke));
+        // Offsets are remembered for the display the window is on right now
+        let display = cx.display().map(|display| display_key(display.as_ref()));
+        let offset = display
+            .as_ref()
+            .map(|display| WindowPlacement::offset(display, cx))
+            .unwrap_or_default();
+        // Where the window was resized to takes precedence over the preferences
+        let (width, height) = WindowPlacement::size(cx);
+        Form::new(
+            vec![
+                Input::new("width", "Window Width", text("800", width, Some(size)), cx),
+                Input::new(
+                    "height",
+                    "Window Height",
+                    text("450", height, Some(size)),
+                    cx,
+                ),
+                Input::new(
+                    "window_mode",
+                    "Window Mode",
+                    dropdown(settings.window_mode.as_str(), WindowMode::options()),
+                    cx,
+                ),
+                Input::new(
+                    "reset_timeout",
+                    "Reset After Seconds",
+                    text("90", settings.reset_timeout, Some(seconds)),
+                    cx,
+                ),
+                Input::new(
+                    "hotkey",
+                    "Hotkey",
+                    InputKind::Shortcut {
+                        tmp: hotkey.clone(),
+                        value: hotkey,
+                    },
+                    cx,
+                ),
+                Input::new(
+                    "leader",
+                    "Leader Hotkey",
+                    InputKind::Shortcut {
+                        tmp: leader.clone(),
+                        value: leader,
+                    },
+                    cx,
+                ),
+                Input::new(
+                    "leader_timeout",
+                    "Leader Timeout Seconds",
+                    text("3", settings.leader_timeout, Some(seconds)),
+                    cx,
+                ),
+                Input::new(
+                    "terminal",
+                    "Terminal",
+                    text(
+                        "Detected automatically",
+                        settings.terminal.clone().unwrap_or_default(),
+                        None,
+                    ),
+                    cx,
+                ),
+                Input::new(
+                    "search_scope",
+                    "Search Scope",
+                    dropdown(settings.search_scope.as_str(), SearchScope::options()),
+                    cx,
+                ),
+                Input::new(
+                    "placement",
+                    "Open On Display With",
+                    dropdown(settings.placement.as_str(), Placement::options()),
+                    cx,
+                ),
+                Input::new(
+                    "offset",
+                    "Vertical Offset On This Display",
+                    text("0", offset, Some(pixels)),
+                    cx,
+                ),
+            ],
+            move |values, actions, cx| {
+                let mut settings = cx.global::<Settings>().clone();
+                let default = Settings::default();
+                settings.width = values["width"]
+                    .value::<String>()
+                    .parse()
+                    .unwrap_or(default.width);
+                settings.height = values["height"]
+                    .value::<String>()
+                    .parse()
+                    .unwrap_or(default.height);
+                settings.window_mode =
+                    WindowMode::parse(&values["window_mode"].value::<String>()).unwrap_or_default();
+                settings.reset_timeout = values["reset_timeout"]
+                    .value::<String>()
+                    .parse()
+                    .unwrap_or(default.reset_timeout);
+                // The window can't be opened without the main hotkey
+                settings.hotkey = match values["hotkey"].value::<Option<Shortcut>>() {
+                    Some(shortcut) => {
+                        if let Err(err) = HotkeyManager::check(None, &shortcut.get(), cx) {
+                            actions.toast.error(err, cx);
+                            return;
+                        }
+                        shortcut.to_string()
+                    }
+                    None => default.hotkey,
+                };
+                settings.leader = match values["leader"].value::<Option<Shortcut>>() {
+                    Some(shortcut) => {
+                        if let Err(err) = HotkeyManager::check(Some(LEADER), &shortcut.get(), cx) {
+                            actions.toast.error(err, cx);
+                            return;
+                        }
+                        Some(shortcut.to_string())
+                    }
+                    None => None,
+                };
+                settings.leader_timeout = values["leader_timeout"]
+                    .value::<String>()
+                    .parse()
+                    .unwrap_or(default.leader_timeout);
+                let terminal = values["terminal"].value::<String>();
+                settings.terminal = (!terminal.trim().is_empty()).then_some(terminal);
+                settings.search_scope =
+                    SearchScope::parse(&values["search_scope"].value::<String>())
+                        .unwrap_or_default();
+                settings.placement =
+                    Placement::parse(&values["placement"].value::<String>()).unwrap_or_default();
+                if let Some(display) = display.clone() {
+                    let offset = values["offset"].value::<String>().parse().unwrap_or(0.0);
+                    if offset == 0.0 {
+                        settings.display_offsets.remove(&display);
+                    } else {
+                        settings.display_offsets.insert(display, offset);
+                    }
+                }
+                if let Err(err) = settings.save(cx) {
+                    error!("Failed to save preferences: {}", err);
+                    actions.toast.error("Failed to save preferences", cx);
+                } else {
+                    // Where the window was resized and dragged to was shown in the form, so it is saved now
+                    WindowPlacement::reset(display.as_deref(), cx);
+                    actions.toast.success("Preferences saved", cx);
+                }
+            },
+            context,
+            cx,
+        )
+        .into()
+    }
+}
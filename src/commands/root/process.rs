@@ -141,9 +141,11 @@ impl StateViewBuilder for ProcessListBuilder {
                                     get_application_data(&PathBuf::from(path)).unwrap_or(AppData {
                                         id: "".to_string(),
                                         name: p.name.split('/').last().unwrap().to_string(),
+                                        description: None,
                                         icon: Img::default().icon(Icon::Cpu),
                                         icon_path: PathBuf::new(),
                                         keywords: vec![],
+                                        actions: vec![],
                                         tag: "".to_string(),
                                     });
                                 ItemBuilder::new(p.pid, {
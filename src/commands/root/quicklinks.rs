@@ -0,0 +1,480 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::sync::OnceLock;
+
+use anyhow::anyhow;
+use bonsaidb::{
+    core::schema::{Collection, SerializedCollection},
+    local::Database,
+};
+use gpui::*;
+use log::error;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    commands::{RootCommand, RootCommandBuilder, RootCommands},
+    components::{
+        form::{Form, Input, InputKind},
+        list::{Accessory, Item, ItemBuilder, ListBuilder, ListItem},
+        shared::{Icon, Img, ImgMask},
+    },
+    db::Db,
+    platform::{open_with, run_in_terminal},
+    settings::Settings,
+    state::{Action, Actions, Shortcut, StateModel, StateViewBuilder, StateViewContext},
+    window::Window,
+};
+
+use super::apps::ApplicationIndex;
+
+#[derive(Clone, Serialize, Deserialize, Collection)]
+#[collection(name = "quicklinks")]
+struct Quicklink {
+    #[natural_id]
+    id: String,
+    name: String,
+    keyword: String,
+    // A URL or a shell command, with placeholders like `{query}` for the argument
+    link: String,
+    // ID of the application to open the link with instead of the default one
+    app: Option<String>,
+}
+
+fn db() -> &'static Database {
+    static DB: OnceLock<Database> = OnceLock::new();
+    DB.get_or_init(Db::init_collection::<Quicklink>)
+}
+
+fn placeholder() -> &'static Regex {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER.get_or_init(|| Regex::new(r"\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap())
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+impl Quicklink {
+    fn placeholders(&self) -> Vec<String> {
+        let mut names: Vec<String> = vec![];
+        for capture in placeholder().captures_iter(&self.link) {
+            let name = capture[1].to_string();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+    // Anything that doesn't parse as a URL is run as a shell command
+    fn is_url(&self) -> bool {
+        Url::parse(&placeholder().replace_all(&self.link, "x")).is_ok()
+    }
+    /// Fills in the placeholders in order with the words of the argument, the last one gets the rest of it.
+    fn fill(&self, argument: &str) -> String {
+        let names = self.placeholders();
+        let mut words = argument.trim().splitn(names.len().max(1), ' ');
+        let url = self.is_url();
+        let mut link = self.link.clone();
+        for name in names {
+            let word = words.next().unwrap_or_default().trim();
+            let value = if url {
+                percent_encode(word)
+            } else {
+                shell_quote(word)
+            };
+            link = link.replace(&format!("{{{}}}", name), &value);
+        }
+        link
+    }
+    fn open(&self, argument: Option<&str>, actions: &mut Actions, cx: &mut WindowContext) {
+        // Without an argument yet, the keyword is typed into the root list to start one
+        if argument.is_none() && !self.placeholders().is_empty() {
+            let keyword = format!("{} ", self.keyword);
+            StateModel::update(
+                |this, cx| {
+                    this.reset(cx);
+                    let query = this.inner.read(cx).stack[0].query.downgrade();
+                    query.set_text(keyword, cx);
+                },
+                cx,
+            );
+            return;
+        }
+        let link = self.fill(argument.unwrap_or_default());
+        let terminal = cx.global::<Settings>().terminal.clone();
+        let result = match (&self.app, self.is_url()) {
+            (Some(app), _) => open_with(app, &link, terminal.as_deref()),
+            (None, true) => {
+                cx.open_url(&link);
+                Ok(())
+            }
+            (None, false) => run_in_terminal(&link, terminal.as_deref()),
+        };
+        if let Err(err) = result {
+            error!("Failed to open quicklink: {}", err);
+            actions.toast.error("Failed to open quicklink", cx);
+        } else {
+            Window::close(cx);
+        }
+    }
+    fn item(&self, icon: Img, argument: Option<&str>) -> Item {
+        let subtitle = match argument {
+            Some(argument) => self.fill(argument),
+            None => self.link.clone(),
+        };
+        ItemBuilder::new(
+            format!("quicklink:{}", self.id),
+            ListItem::new(
+                Some(icon),
+                self.name.clone(),
+                Some(subtitle),
+                vec![Accessory::new(
+                    self.keyword.clone(),
+                    Some(Img::default().icon(Icon::Link)),
+                )],
+            ),
+        )
+        .keywords(vec![self.name.clone(), self.keyword.clone()])
+        .actions(vec![Action::new(
+            Img::default().icon(Icon::ArrowUpRightFromSquare),
+            "Open",
+            None,
+            {
+                let quicklink = self.clone();
+                let argument = argument.map(String::from);
+                move |actions, cx| quicklink.open(argument.as_deref(), actions, cx)
+            },
+            false,
+        )])
+        .build()
+    }
+}
+
+/// Named link and command templates, which show up in the root list and take the text after their keyword as argument.
+pub struct Quicklinks {
+    links: Vec<(Quicklink, Img)>,
+}
+
+impl Quicklinks {
+    pub fn init(cx: &mut WindowContext) {
+        let links = Self::load(cx);
+        cx.set_global(Self { links });
+    }
+    fn load(cx: &mut WindowContext) -> Vec<(Quicklink, Img)> {
+        let mut links: Vec<Quicklink> = Quicklink::all(db())
+            .query()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|doc| doc.contents)
+            .collect();
+        links.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        links
+            .into_iter()
+            .map(|link| {
+                let icon = if link.is_url() {
+                    Img::default()
+                        .mask(ImgMask::Rounded)
+                        .favicon(&link.link, Icon::Link, cx)
+                } else {
+                    Img::default().icon(Icon::Terminal)
+                };
+                (link, icon)
+            })
+            .collect()
+    }
+    fn reload(cx: &mut WindowContext) {
+        let links = Self::load(cx);
+        cx.update_global::<Self, _>(|this, _| this.links = links);
+        RootCommands::refresh(cx);
+    }
+    /// All quicklinks as root list items, without an argument.
+    pub fn list(cx: &AppContext) -> Vec<Item> {
+        cx.global::<Self>()
+            .links
+            .iter()
+            .map(|(link, icon)| link.item(icon.clone(), None))
+            .collect()
+    }
+    /// The quicklink whose keyword the query starts with, with the rest of the query as argument.
+    pub fn find(query: &str, cx: &AppContext) -> Option<Item> {
+        let query = query.trim_start();
+        let (keyword, argument) = match query.split_once(' ') {
+            Some((keyword, argument)) => (keyword, Some(argument)),
+            None => (query, None),
+        };
+        cx.global::<Self>()
+            .links
+            .iter()
+            .find(|(link, _)| link.keyword.eq_ignore_ascii_case(keyword))
+            .map(|(link, icon)| link.item(icon.clone(), argument))
+    }
+    fn save(quicklink: Quicklink, cx: &mut WindowContext) -> anyhow::Result<()> {
+        let taken = cx.global::<Self>().links.iter().any(|(link, _)| {
+            link.id != quicklink.id && link.keyword.eq_ignore_ascii_case(&quicklink.keyword)
+        });
+        if taken {
+            return Err(anyhow!("Keyword {} is already in use", quicklink.keyword));
+        }
+        let id = quicklink.id.clone();
+        quicklink.overwrite_into(&id, db())?;
+        Self::reload(cx);
+        Ok(())
+    }
+    fn delete(id: &str, cx: &mut WindowContext) -> anyhow::Result<()> {
+        if let Some(doc) = Quicklink::get(&id.to_string(), db())? {
+            doc.delete(db())?;
+        }
+        Self::reload(cx);
+        Ok(())
+    }
+}
+
+impl Global for Quicklinks {}
+
+// The installed applications by name, keeping the current one even if it was uninstalled since
+fn app_options(current: Option<&String>, cx: &AppContext) -> Vec<(String, String)> {
+    let mut apps: Vec<(String, String)> = cx
+        .global::<ApplicationIndex>()
+        .apps
+        .read(cx)
+        .iter()
+        .map(|app| (app.id.clone(), app.name.clone()))
+        .collect();
+    apps.sort_by_key(|(_, name)| name.to_lowercase());
+    apps.dedup_by(|a, b| a.0 == b.0);
+    if let Some(id) = current.filter(|id| !apps.iter().any(|(app, _)| app == *id)) {
+        apps.push((id.clone(), id.clone()));
+    }
+    let mut options = vec![(String::new(), "Default browser or terminal".to_string())];
+    options.extend(apps);
+    options
+}
+
+#[derive(Clone)]
+struct QuicklinkFormBuilder {
+    quicklink: Option<Quicklink>,
+    // The list to go back to once the quicklink is saved
+    parent: Actions,
+}
+
+impl StateViewBuilder for QuicklinkFormBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        let quicklink = self.quicklink.clone();
+        let parent = self.parent.clone();
+        let app = quicklink.as_ref().and_then(|link| link.app.as_ref());
+        let options = app_options(app, cx);
+        let app = app.cloned().unwrap_or_default();
+        Form::new(
+            vec![
+                Input::new(
+                    "name",
+                    "Name",
+                    InputKind::TextField {
+                        placeholder: "Jira Ticket".to_string(),
+                        value: quicklink
+                            .as_ref()
+                            .map(|link| link.name.clone())
+                            .unwrap_or_default(),
+                        password: false,
+                        validate: Some(|v| v.trim().is_empty().then_some("Name is required")),
+                    },
+                    cx,
+                ),
+                Input::new(
+                    "keyword",
+                    "Keyword",
+                    InputKind::TextField {
+                        placeholder: "jira".to_string(),
+                        value: quicklink
+                            .as_ref()
+                            .map(|link| link.keyword.clone())
+                            .unwrap_or_default(),
+                        password: false,
+                        validate: Some(|v| {
+                            if v.trim().is_empty() {
+                                return Some("Keyword is required");
+                            }
+                            v.trim()
+                                .contains(' ')
+                                .then_some("Keyword can't contain spaces")
+                        }),
+                    },
+                    cx,
+                ),
+                Input::new(
+                    "link",
+                    "Link",
+                    InputKind::TextField {
+                        placeholder: "https://jira/browse/{query} or ssh {host}".to_string(),
+                        value: quicklink
+                            .as_ref()
+                            .map(|link| link.link.clone())
+                            .unwrap_or_default(),
+                        password: false,
+                        validate: Some(|v| v.trim().is_empty().then_some("Link is required")),
+                    },
+                    cx,
+                ),
+                Input::new(
+                    "app",
+                    "Open With",
+                    InputKind::Dropdown {
+                        value: app,
+                        options,
+                    },
+                    cx,
+                ),
+            ],
+            move |values, actions, cx| {
+                let app = Some(values["app"].value::<String>()).filter(|app| !app.is_empty());
+                let link = Quicklink {
+                    id: quicklink
+                        .as_ref()
+                        .map(|link| link.id.clone())
+                        .unwrap_or_else(|| format!("{:08x}", rand::random::<u32>())),
+                    name: values["name"].value::<String>().trim().to_string(),
+                    keyword: values["keyword"].value::<String>().trim().to_lowercase(),
+                    link: values["link"].value::<String>().trim().to_string(),
+                    app,
+                };
+                if let Err(err) = Quicklinks::save(link, cx) {
+                    error!("Failed to save quicklink: {}", err);
+                    actions.toast.error(err, cx);
+                    return;
+                }
+                StateModel::update(|this, cx| this.pop(cx), cx);
+                let mut parent = parent.clone();
+                parent.toast.success("Quicklink saved", cx);
+                parent.update();
+            },
+            context,
+            cx,
+        )
+        .into()
+    }
+}
+
+#[derive(Clone)]
+struct QuicklinksBuilder;
+
+impl StateViewBuilder for QuicklinksBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        context.query.set_placeholder("Search quicklinks...", cx);
+        context.actions.update_global(
+            vec![Action::new(
+                Img::default().icon(Icon::PlusSquare),
+                "Create Quicklink",
+                Some(Shortcut::new("n").cmd()),
+                |actions, cx| {
+                    let builder = QuicklinkFormBuilder {
+                        quicklink: None,
+                        parent: actions.clone(),
+                    };
+                    StateModel::update(|this, cx| this.push(builder, cx), cx);
+                },
+                false,
+            )],
+            cx,
+        );
+        ListBuilder::new()
+            .build(
+                |_, _, cx| {
+                    let items = cx
+                        .global::<Quicklinks>()
+                        .links
+                        .iter()
+                        .map(|(link, icon)| {
+                            let mut item = link.item(icon.clone(), None);
+                            item.get_actions_mut().extend([
+                                Action::new(
+                                    Img::default().icon(Icon::Pen),
+                                    "Edit",
+                                    Some(Shortcut::new("e").cmd()),
+                                    {
+                                        let link = link.clone();
+                                        move |actions, cx| {
+                                            let builder = QuicklinkFormBuilder {
+                                                quicklink: Some(link.clone()),
+                                                parent: actions.clone(),
+                                            };
+                                            StateModel::update(
+                                                |this, cx| this.push(builder, cx),
+                                                cx,
+                                            );
+                                        }
+                                    },
+                                    false,
+                                ),
+                                Action::new(
+                                    Img::default().icon(Icon::Trash),
+                                    "Delete",
+                                    Some(Shortcut::new("backspace").cmd()),
+                                    {
+                                        let id = link.id.clone();
+                                        move |actions, cx| {
+                                            if let Err(err) = Quicklinks::delete(&id, cx) {
+                                                error!("Failed to delete quicklink: {}", err);
+                                                actions
+                                                    .toast
+                                                    .error("Failed to delete quicklink", cx);
+                                            } else {
+                                                actions.toast.success("Quicklink deleted", cx);
+                                                actions.update();
+                                            }
+                                        }
+                                    },
+                                    false,
+                                ),
+                            ]);
+                            item
+                        })
+                        .collect();
+                    Ok(Some(items))
+                },
+                context,
+                cx,
+            )
+            .into()
+    }
+}
+
+pub struct QuicklinksCommandBuilder;
+
+impl RootCommandBuilder for QuicklinksCommandBuilder {
+    fn build(&self, _cx: &mut WindowContext) -> RootCommand {
+        RootCommand::new(
+            "quicklinks",
+            "Manage Quicklinks",
+            "Loungy",
+            Icon::Link,
+            vec!["Bookmarks", "URL", "Search"],
+            None,
+            Box::new(|_, cx| {
+                StateModel::update(|this, cx| this.push(QuicklinksBuilder, cx), cx);
+            }),
+        )
+    }
+}
@@ -0,0 +1,803 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use async_std::task::{sleep, spawn_blocking};
+use gpui::*;
+use log::error;
+use serde::Deserialize;
+
+use crate::{
+    commands::{RootCommand, RootCommandBuilder},
+    components::{
+        form::{Form, Input, InputKind},
+        list::{Accessory, AsyncListItems, ItemBuilder, ListBuilder, ListItem},
+        shared::{Icon, Img},
+    },
+    paths::paths,
+    platform::output_with_timeout,
+    state::{Action, Actions, StateModel, StateViewBuilder, StateViewContext},
+    theme::Theme,
+    window::Window,
+};
+
+/// How the output of a script is presented, named after Raycast's modes with Loungy's names as aliases.
+#[derive(Clone, Copy, PartialEq)]
+enum ScriptMode {
+    Silent,
+    Toast,
+    List,
+    Inline,
+}
+
+impl ScriptMode {
+    fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "silent" => Some(Self::Silent),
+            "compact" | "toast" => Some(Self::Toast),
+            "fullOutput" | "list" => Some(Self::List),
+            "inline" => Some(Self::Inline),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum ScriptArgumentKind {
+    Text,
+    Password,
+    Dropdown,
+}
+
+#[derive(Deserialize, Clone)]
+struct ScriptDropdownItem {
+    title: String,
+    value: String,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ScriptArgument {
+    #[serde(rename = "type")]
+    kind: ScriptArgumentKind,
+    #[serde(default)]
+    placeholder: String,
+    #[serde(default)]
+    optional: bool,
+    #[serde(default)]
+    percent_encoded: bool,
+    #[serde(default)]
+    data: Vec<ScriptDropdownItem>,
+}
+
+#[derive(Clone)]
+struct Script {
+    id: String,
+    path: PathBuf,
+    title: String,
+    mode: ScriptMode,
+    icon: Option<String>,
+    package_name: Option<String>,
+    description: Option<String>,
+    keywords: Vec<String>,
+    arguments: Vec<ScriptArgument>,
+    current_directory: Option<PathBuf>,
+    needs_confirmation: bool,
+    refresh_time: Option<Duration>,
+}
+
+// Durations like "30s", "5m", "1h" or "1d", inline scripts are refreshed at most every 10 seconds
+fn parse_refresh_time(value: &str) -> Option<Duration> {
+    let split = value.len().checked_sub(1)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    let seconds = match unit {
+        "s" => amount,
+        "m" => amount * 60,
+        "h" => amount * 60 * 60,
+        "d" => amount * 60 * 60 * 24,
+        _ => return None,
+    };
+    Some(Duration::from_secs(seconds.max(10)))
+}
+
+// Same as Javascript's encodeURIComponent, which Raycast uses for percent encoded arguments
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'!'
+            | b'~'
+            | b'*'
+            | b'\''
+            | b'('
+            | b')' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+// Scripts are killed after this, so a hanging one doesn't keep its view waiting
+static SCRIPT_TIMEOUT: Duration = Duration::from_secs(60);
+
+// Comment prefixes of the languages script commands are usually written in
+static COMMENTS: &[&str] = &["#", "//", "--"];
+
+fn last_line(output: &str) -> Option<String> {
+    output
+        .lines()
+        .rev()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty())
+        .map(|line| line.to_string())
+}
+
+impl Script {
+    /// Reads the metadata comments, e.g. `# @raycast.title Hello World`, from the script.
+    /// Only the comments at the top count, anything after the first line of code is ignored.
+    fn parse(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let mut meta = BTreeMap::<String, String>::new();
+        let mut arguments = BTreeMap::<u32, ScriptArgument>::new();
+        let header = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map_while(|line| COMMENTS.iter().find_map(|prefix| line.strip_prefix(prefix)));
+        for line in header {
+            let line = line.trim_start();
+            let Some(rest) = line
+                .strip_prefix("@raycast.")
+                .or_else(|| line.strip_prefix("@loungy."))
+            else {
+                continue;
+            };
+            let (key, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let value = value.trim();
+            if let Some(Ok(n)) = key.strip_prefix("argument").map(|n| n.parse::<u32>()) {
+                arguments.insert(n, serde_json::from_str(value)?);
+            } else if !value.is_empty() {
+                meta.insert(key.to_string(), value.to_string());
+            }
+        }
+        let file_name = path
+            .file_name()
+            .ok_or(anyhow!("Invalid path"))?
+            .to_string_lossy();
+        let title = meta
+            .remove("title")
+            .ok_or(anyhow!("No script command metadata"))?;
+        let mode = meta
+            .get("mode")
+            .and_then(|mode| ScriptMode::parse(mode))
+            .ok_or(anyhow!("Missing or invalid mode"))?;
+        let home = &paths().home;
+        Ok(Self {
+            id: format!("script_{}", file_name),
+            path: path.to_path_buf(),
+            title,
+            mode,
+            icon: meta.remove("icon"),
+            package_name: meta.remove("packageName"),
+            description: meta.remove("description"),
+            keywords: meta
+                .remove("keywords")
+                .map(|keywords| {
+                    keywords
+                        .split(',')
+                        .map(|k| k.trim().to_string())
+                        .filter(|k| !k.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            arguments: arguments.into_values().collect(),
+            current_directory: meta.remove("currentDirectoryPath").map(|dir| {
+                dir.strip_prefix("~/")
+                    .map(|dir| home.join(dir))
+                    .unwrap_or(PathBuf::from(dir))
+            }),
+            needs_confirmation: meta
+                .get("needsConfirmation")
+                .is_some_and(|value| value == "true"),
+            refresh_time: meta
+                .get("refreshTime")
+                .and_then(|value| parse_refresh_time(value)),
+        })
+    }
+    fn icon(&self) -> Img {
+        let Some(icon) = &self.icon else {
+            return Img::default().icon(Icon::Terminal);
+        };
+        if icon.starts_with("http://") || icon.starts_with("https://") {
+            return Img::default().url(icon);
+        }
+        let dir = self.path.parent().unwrap_or(Path::new("/"));
+        let path = dir.join(icon);
+        if path.is_file() {
+            Img::default().file(path)
+        } else {
+            Img::default().emoji(icon.clone())
+        }
+    }
+    async fn output(&self, args: Vec<String>) -> anyhow::Result<String> {
+        let mut command = Command::new(&self.path);
+        command.args(args);
+        // Scripts run from their own folder unless they ask otherwise
+        let dir = self.path.parent().unwrap_or(Path::new("/"));
+        command.current_dir(
+            self.current_directory
+                .as_ref()
+                .map(|current| dir.join(current))
+                .unwrap_or(dir.to_path_buf()),
+        );
+        let output =
+            spawn_blocking(move || output_with_timeout(&mut command, SCRIPT_TIMEOUT)).await?;
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!(last_line(&stderr)
+                .or(last_line(&stdout))
+                .unwrap_or(format!("{} failed", self.title))));
+        }
+        Ok(stdout)
+    }
+    // Scripts marked with `needsConfirmation` are only run once the user confirms
+    fn confirm(&self, args: Vec<String>, actions: &Actions, cx: &mut WindowContext) {
+        if !self.needs_confirmation {
+            self.run(args, actions, cx);
+            return;
+        }
+        let builder = ScriptConfirmBuilder {
+            script: self.clone(),
+            args,
+        };
+        StateModel::update(|this, cx| this.push(builder, cx), cx);
+    }
+    fn run(&self, args: Vec<String>, actions: &Actions, cx: &mut WindowContext) {
+        let script = self.clone();
+        let mut actions = actions.clone();
+        match self.mode {
+            ScriptMode::Silent => {
+                Window::close(cx);
+                // Like Raycast, the last line of the output is shown in a small window
+                cx.spawn(|mut cx| async move {
+                    let (message, icon) = match script.output(args).await {
+                        Ok(output) => match last_line(&output) {
+                            Some(line) => (line, Icon::Terminal),
+                            None => return,
+                        },
+                        Err(err) => {
+                            error!("Script {} failed: {}", script.title, err);
+                            (err.to_string(), Icon::AlertCircle)
+                        }
+                    };
+                    let _ = cx.update(|cx| actions.toast.floating(message, Some(icon), cx));
+                })
+                .detach();
+            }
+            ScriptMode::Toast | ScriptMode::Inline => {
+                actions
+                    .toast
+                    .loading(format!("Running {}...", self.title), cx);
+                cx.spawn(|mut cx| async move {
+                    match script.output(args).await {
+                        Ok(output) => actions.toast.success(
+                            last_line(&output).unwrap_or(format!("{} finished", script.title)),
+                            &mut cx,
+                        ),
+                        Err(err) => actions.toast.error(err, &mut cx),
+                    }
+                })
+                .detach();
+            }
+            ScriptMode::List => {
+                StateModel::update(
+                    |this, cx| this.push(ScriptOutputBuilder { script, args }, cx),
+                    cx,
+                );
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ScriptFormBuilder {
+    script: Script,
+}
+
+impl StateViewBuilder for ScriptFormBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        let inputs = self
+            .script
+            .arguments
+            .iter()
+            .enumerate()
+            .map(|(i, argument)| {
+                let label = if argument.placeholder.is_empty() {
+                    format!("Argument {}", i + 1)
+                } else {
+                    argument.placeholder.clone()
+                };
+                let kind = if argument.kind == ScriptArgumentKind::Dropdown {
+                    let mut options: Vec<(String, String)> = argument
+                        .data
+                        .iter()
+                        .map(|item| (item.value.clone(), item.title.clone()))
+                        .collect();
+                    if argument.optional {
+                        options.insert(0, ("".to_string(), "None".to_string()));
+                    }
+                    InputKind::Dropdown {
+                        value: options
+                            .first()
+                            .map(|(value, _)| value.clone())
+                            .unwrap_or_default(),
+                        options,
+                    }
+                } else {
+                    InputKind::TextField {
+                        placeholder: label.clone(),
+                        value: "".to_string(),
+                        password: argument.kind == ScriptArgumentKind::Password,
+                        validate: if argument.optional {
+                            None
+                        } else {
+                            Some(|v| v.is_empty().then_some("Argument is required"))
+                        },
+                    }
+                };
+                Input::new(format!("argument{}", i), label, kind, cx)
+            })
+            .collect();
+        let script = self.script.clone();
+        Form::new(
+            inputs,
+            move |values, actions, cx| {
+                let mut args = vec![];
+                for (i, argument) in script.arguments.iter().enumerate() {
+                    let mut value = values[&format!("argument{}", i)].value::<String>();
+                    if argument.percent_encoded {
+                        value = percent_encode(&value);
+                    }
+                    args.push(value);
+                }
+                script.confirm(args, actions, cx);
+            },
+            context,
+            cx,
+        )
+        .into()
+    }
+}
+
+#[derive(Clone)]
+struct ScriptConfirmBuilder {
+    script: Script,
+    args: Vec<String>,
+}
+
+impl StateViewBuilder for ScriptConfirmBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        context
+            .query
+            .set_placeholder(format!("Run {}?", self.script.title), cx);
+        let script = self.script.clone();
+        let args = self.args.clone();
+        ListBuilder::new()
+            .build(
+                move |_, _, _| {
+                    let run = {
+                        let script = script.clone();
+                        let args = args.clone();
+                        move |actions: &mut Actions, cx: &mut WindowContext| {
+                            script.run(args.clone(), actions, cx)
+                        }
+                    };
+                    let cancel = |_: &mut Actions, cx: &mut WindowContext| {
+                        StateModel::update(|this, cx| this.pop(cx), cx)
+                    };
+                    Ok(Some(vec![
+                        ItemBuilder::new(
+                            "run",
+                            ListItem::new(
+                                Some(Img::default().icon(Icon::Play)),
+                                format!("Run {}", script.title),
+                                script.description.clone(),
+                                vec![],
+                            ),
+                        )
+                        .actions(vec![Action::new(
+                            Img::default().icon(Icon::Play),
+                            "Run",
+                            None,
+                            run,
+                            false,
+                        )])
+                        .build(),
+                        ItemBuilder::new(
+                            "cancel",
+                            ListItem::new(
+                                Some(Img::default().icon(Icon::X)),
+                                "Cancel",
+                                None,
+                                vec![],
+                            ),
+                        )
+                        .actions(vec![Action::new(
+                            Img::default().icon(Icon::X),
+                            "Cancel",
+                            None,
+                            cancel,
+                            false,
+                        )])
+                        .build(),
+                    ]))
+                },
+                context,
+                cx,
+            )
+            .into()
+    }
+}
+
+#[derive(Clone)]
+struct ScriptOutputBuilder {
+    script: Script,
+    args: Vec<String>,
+}
+
+impl StateViewBuilder for ScriptOutputBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        context.query.set_placeholder("Search output...", cx);
+        let script = self.script.clone();
+        let args = self.args.clone();
+        let actions = context
+            .actions
+            .inner
+            .upgrade()
+            .map(|actions| actions.read(cx).clone());
+        let view = cx.new_view(|cx| {
+            cx.spawn(|view, mut cx| async move {
+                let lines = match script.output(args).await {
+                    Ok(output) => output
+                        .lines()
+                        .map(|line| line.to_string())
+                        .collect::<Vec<String>>(),
+                    Err(err) => {
+                        if let Some(mut actions) = actions {
+                            actions.toast.error(err, &mut cx);
+                        }
+                        vec![]
+                    }
+                };
+                let items = lines
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, line)| !line.trim().is_empty())
+                    .map(|(i, line)| {
+                        ItemBuilder::new(i, ListItem::new(None, line, None, vec![]))
+                            .keywords(vec![line.clone()])
+                            .actions(vec![
+                                Action::new(
+                                    Img::default().icon(Icon::Copy),
+                                    "Copy",
+                                    None,
+                                    {
+                                        let line = line.clone();
+                                        move |actions, cx| {
+                                            cx.write_to_clipboard(ClipboardItem::new(line.clone()));
+                                            actions.toast.success("Copied to clipboard", cx);
+                                        }
+                                    },
+                                    false,
+                                ),
+                                Action::new(
+                                    Img::default().icon(Icon::ClipboardCopy),
+                                    "Copy All",
+                                    None,
+                                    {
+                                        let output = lines.join("\n");
+                                        move |actions, cx| {
+                                            cx.write_to_clipboard(ClipboardItem::new(
+                                                output.clone(),
+                                            ));
+                                            actions.toast.success("Copied to clipboard", cx);
+                                        }
+                                    },
+                                    false,
+                                ),
+                            ])
+                            .build()
+                    })
+                    .collect();
+                let _ = view.update(&mut cx, |view: &mut AsyncListItems, cx| {
+                    view.update("output".to_string(), items, cx);
+                });
+            })
+            .detach();
+            AsyncListItems::new()
+        });
+        AsyncListItems::loader(&view, &context.actions, cx);
+        ListBuilder::new()
+            .build(
+                move |_, _, cx| {
+                    Ok(Some(
+                        view.read(cx).items.values().flatten().cloned().collect(),
+                    ))
+                },
+                context,
+                cx,
+            )
+            .into()
+    }
+}
+
+/// Latest output of an inline script, shown next to its root list item.
+struct ScriptInlineOutput {
+    output: Option<String>,
+}
+
+impl Render for ScriptInlineOutput {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        div()
+            .text_color(theme.subtext0)
+            .font(theme.font_mono.clone())
+            .child(self.output.clone().unwrap_or_default())
+    }
+}
+
+pub struct ScriptCommandBuilder {
+    script: Script,
+}
+
+impl ScriptCommandBuilder {
+    /// Finds the executable scripts with command metadata in the config folder.
+    pub fn discover() -> Vec<Box<dyn RootCommandBuilder>> {
+        let dir = paths().config.join("scripts");
+        let Ok(entries) = dir.read_dir() else {
+            return vec![];
+        };
+        let mut builders: Vec<Box<dyn RootCommandBuilder>> = vec![];
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let executable = fs::metadata(&path)
+                .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0);
+            if !executable {
+                continue;
+            }
+            match Script::parse(&path) {
+                Ok(script) => builders.push(Box::new(Self { script })),
+                Err(err) => error!("Failed to load script {}: {}", path.display(), err),
+            }
+        }
+        builders
+    }
+}
+
+impl RootCommandBuilder for ScriptCommandBuilder {
+    fn build(&self, cx: &mut WindowContext) -> RootCommand {
+        let script = self.script.clone();
+        let mut keywords = script.keywords.clone();
+        keywords.extend(script.description.clone());
+        let command = RootCommand::new(
+            &script.id,
+            &script.title,
+            script
+                .package_name
+                .clone()
+                .unwrap_or("Script Command".to_string()),
+            script.icon(),
+            keywords,
+            None,
+            Box::new({
+                let script = script.clone();
+                move |actions, cx| {
+                    if script.arguments.is_empty() {
+                        script.confirm(vec![], actions, cx);
+                    } else {
+                        let script = script.clone();
+                        StateModel::update(
+                            |this, cx| this.push(ScriptFormBuilder { script }, cx),
+                            cx,
+                        );
+                    }
+                }
+            }),
+        );
+        if script.mode != ScriptMode::Inline {
+            return command.accessory(Accessory::new("Script", None));
+        }
+        let view = cx.new_view(|cx| {
+            cx.spawn(|view, mut cx| async move {
+                loop {
+                    let output = match script.output(vec![]).await {
+                        Ok(output) => output.lines().next().map(|line| line.to_string()),
+                        Err(err) => Some(err.to_string()),
+                    };
+                    let result = view.update(&mut cx, |view: &mut ScriptInlineOutput, cx| {
+                        view.output = output;
+                        cx.notify();
+                    });
+                    let Some(refresh_time) = script.refresh_time else {
+                        break;
+                    };
+                    if result.is_err() {
+                        break;
+                    }
+                    sleep(refresh_time).await;
+                }
+            })
+            .detach();
+            ScriptInlineOutput { output: None }
+        });
+        command.accessory(Accessory::view(view))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(name: &str, content: &str) -> anyhow::Result<Script> {
+        let dir =
+            std::env::temp_dir().join(format!("loungy-script-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        let script = Script::parse(&path);
+        let _ = fs::remove_dir_all(dir);
+        script
+    }
+
+    #[test]
+    fn modes() {
+        for (mode, expected) in [
+            ("silent", ScriptMode::Silent),
+            ("compact", ScriptMode::Toast),
+            ("toast", ScriptMode::Toast),
+            ("fullOutput", ScriptMode::List),
+            ("list", ScriptMode::List),
+            ("inline", ScriptMode::Inline),
+        ] {
+            let script = parse(
+                "mode.sh",
+                &format!(
+                    "#!/bin/bash\n# @raycast.title Mode\n# @raycast.mode {}\n",
+                    mode
+                ),
+            )
+            .unwrap();
+            assert!(script.mode == expected, "{}", mode);
+        }
+        assert!(parse(
+            "mode.sh",
+            "# @raycast.title Mode\n# @raycast.mode detached\n"
+        )
+        .is_err());
+        assert!(parse("mode.sh", "# @raycast.title Mode\n").is_err());
+    }
+
+    #[test]
+    fn metadata() {
+        let script = parse(
+            "weather.py",
+            r#"#!/usr/bin/env python3
+
+# Required parameters:
+# @raycast.schemaVersion 1
+# @raycast.title Weather
+# @raycast.mode inline
+# @raycast.refreshTime 5s
+
+# Optional parameters:
+# @raycast.icon ☀️
+# @raycast.packageName Weather
+# @raycast.needsConfirmation true
+# @raycast.currentDirectoryPath ~/Downloads
+# @loungy.keywords forecast, rain ,
+# @loungy.description Current weather
+
+print("Sunny")
+# @raycast.title Not metadata
+"#,
+        )
+        .unwrap();
+        assert_eq!(script.id, "script_weather.py");
+        assert_eq!(script.title, "Weather");
+        assert!(script.mode == ScriptMode::Inline);
+        assert_eq!(script.refresh_time, Some(Duration::from_secs(10)));
+        assert_eq!(script.icon.as_deref(), Some("☀️"));
+        assert_eq!(script.package_name.as_deref(), Some("Weather"));
+        assert!(script.needs_confirmation);
+        assert_eq!(
+            script.current_directory,
+            Some(paths().home.join("Downloads"))
+        );
+        assert_eq!(script.keywords, vec!["forecast", "rain"]);
+        assert_eq!(script.description.as_deref(), Some("Current weather"));
+    }
+
+    #[test]
+    fn arguments() {
+        let script = parse(
+            "search.js",
+            r#"#!/usr/bin/env node
+// @raycast.title Search
+// @raycast.mode silent
+// @raycast.argument2 { "type": "password", "placeholder": "Token", "optional": true }
+// @raycast.argument1 { "type": "text", "placeholder": "Query", "percentEncoded": true }
+// @raycast.argument3 { "type": "dropdown", "placeholder": "Site", "data": [{"title": "Docs", "value": "docs"}] }
+"#,
+        )
+        .unwrap();
+        let arguments = script.arguments;
+        assert_eq!(arguments.len(), 3);
+        assert!(arguments[0].kind == ScriptArgumentKind::Text);
+        assert_eq!(arguments[0].placeholder, "Query");
+        assert!(arguments[0].percent_encoded && !arguments[0].optional);
+        assert!(arguments[1].kind == ScriptArgumentKind::Password);
+        assert!(arguments[1].optional);
+        assert!(arguments[2].kind == ScriptArgumentKind::Dropdown);
+        assert_eq!(arguments[2].data[0].title, "Docs");
+        assert_eq!(arguments[2].data[0].value, "docs");
+
+        assert!(parse(
+            "search.js",
+            "// @raycast.title Search\n// @raycast.mode silent\n// @raycast.argument1 { \"type\": \"file\" }\n",
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn refresh_times_and_encoding() {
+        assert_eq!(parse_refresh_time("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_refresh_time("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_refresh_time("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_refresh_time("1d"), Some(Duration::from_secs(86400)));
+        assert_eq!(parse_refresh_time("1"), None);
+        assert_eq!(parse_refresh_time("s"), None);
+        assert_eq!(percent_encode("a b&c/ü~"), "a%20b%26c%2F%C3%BC~");
+    }
+
+    #[test]
+    fn kills_hanging_scripts() {
+        let mut command = Command::new("sleep");
+        command.arg("5");
+        let err = output_with_timeout(&mut command, Duration::from_millis(100)).unwrap_err();
+        assert_eq!(err.to_string(), "Timed out after 100ms");
+        let output = output_with_timeout(
+            Command::new("sh").args(["-c", "echo out; echo err >&2; exit 3"]),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+        assert_eq!(output.status.code(), Some(3));
+    }
+}
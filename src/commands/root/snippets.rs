@@ -0,0 +1,606 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{collections::HashMap, fs, path::PathBuf, sync::OnceLock};
+
+use anyhow::anyhow;
+use bonsaidb::{
+    core::schema::{Collection, SerializedCollection},
+    local::Database,
+};
+use gpui::*;
+use log::error;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use time::{format_description, OffsetDateTime};
+
+#[cfg(feature = "expansion")]
+use crate::platform::expansion::Expansion;
+use crate::{
+    commands::{RootCommand, RootCommandBuilder},
+    components::{
+        form::{Form, Input, InputKind},
+        list::{Accessory, Item, ItemBuilder, ListBuilder, ListItem},
+        shared::{Icon, Img},
+    },
+    date::get_offset,
+    db::Db,
+    paths::paths,
+    platform::close_and_paste_at,
+    state::{Action, Actions, Shortcut, StateModel, StateViewBuilder, StateViewContext},
+};
+
+#[derive(Clone, Serialize, Deserialize, Collection)]
+#[collection(name = "snippets")]
+pub struct Snippet {
+    #[natural_id]
+    #[serde(skip_serializing, default)]
+    id: String,
+    pub name: String,
+    // Typing it anywhere expands the snippet, can be empty
+    #[serde(default)]
+    pub keyword: String,
+    pub text: String,
+}
+
+fn db() -> &'static Database {
+    static DB: OnceLock<Database> = OnceLock::new();
+    DB.get_or_init(Db::init_collection::<Snippet>)
+}
+
+fn placeholder() -> &'static Regex {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER.get_or_init(|| {
+        Regex::new(r"\{(date|time|clipboard|cursor|uuid|argument:([^{}]+))\}").unwrap()
+    })
+}
+
+fn now(format: &str) -> String {
+    let format = format_description::parse(format).unwrap();
+    OffsetDateTime::now_utc()
+        .checked_add(time::Duration::seconds(*get_offset() as i64))
+        .unwrap_or(OffsetDateTime::now_utc())
+        .format(&format)
+        .unwrap_or_default()
+}
+
+fn uuid() -> String {
+    let mut bytes = rand::random::<[u8; 16]>();
+    // Version 4, variant 1
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// A snippet with its placeholders filled in.
+pub struct Expanded {
+    pub text: String,
+    // How many characters from the end the cursor should be placed
+    pub cursor: usize,
+}
+
+impl Snippet {
+    /// Names of the `{argument:name}` placeholders, which have to be asked for before expanding.
+    pub fn arguments(&self) -> Vec<String> {
+        let mut names: Vec<String> = vec![];
+        for capture in placeholder().captures_iter(&self.text) {
+            if let Some(name) = capture.get(2) {
+                let name = name.as_str().trim().to_string();
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+    pub fn expand(&self, arguments: &HashMap<String, String>, cx: &AppContext) -> Expanded {
+        let mut text = String::new();
+        let mut cursor = None;
+        let mut last = 0;
+        for capture in placeholder().captures_iter(&self.text) {
+            let whole = capture.get(0).unwrap();
+            text.push_str(&self.text[last..whole.start()]);
+            last = whole.end();
+            match &capture[1] {
+                "date" => text.push_str(&now("[year]-[month]-[day]")),
+                "time" => text.push_str(&now("[hour]:[minute]")),
+                "clipboard" => {
+                    if let Some(clipboard) = cx.read_from_clipboard() {
+                        text.push_str(clipboard.text());
+                    }
+                }
+                "cursor" => {
+                    cursor.get_or_insert(text.chars().count());
+                }
+                "uuid" => text.push_str(&uuid()),
+                _ => {
+                    let name = capture[2].trim();
+                    text.push_str(arguments.get(name).map(String::as_str).unwrap_or_default());
+                }
+            }
+        }
+        text.push_str(&self.text[last..]);
+        let cursor = cursor
+            .map(|cursor| text.chars().count() - cursor)
+            .unwrap_or_default();
+        Expanded { text, cursor }
+    }
+    fn paste(&self, arguments: &HashMap<String, String>, cx: &mut WindowContext) {
+        let expanded = self.expand(arguments, cx);
+        close_and_paste_at(&expanded.text, false, expanded.cursor, cx);
+    }
+    fn copy(
+        &self,
+        arguments: &HashMap<String, String>,
+        actions: &mut Actions,
+        cx: &mut WindowContext,
+    ) {
+        let expanded = self.expand(arguments, cx);
+        cx.write_to_clipboard(ClipboardItem::new(expanded.text));
+        actions
+            .toast
+            .floating("Copied to clipboard", Some(Icon::Clipboard), cx);
+    }
+    /// Shows a form asking for the `{argument:name}` placeholders, which pastes the snippet once submitted,
+    /// or copies it to the clipboard with `copy`.
+    pub fn ask(&self, copy: bool, cx: &mut WindowContext) {
+        let builder = SnippetArgumentsBuilder {
+            snippet: self.clone(),
+            copy,
+        };
+        StateModel::update(|this, cx| this.push(builder, cx), cx);
+    }
+}
+
+// Keywords are expanded from a copy of the snippets, which has to be refreshed
+#[cfg(feature = "expansion")]
+fn changed(cx: &mut WindowContext) {
+    Expansion::reload(cx);
+}
+
+#[cfg(not(feature = "expansion"))]
+fn changed(_: &mut WindowContext) {}
+
+/// Reusable pieces of text, pasted into the frontmost application.
+pub struct Snippets;
+
+impl Snippets {
+    pub fn all() -> Vec<Snippet> {
+        let mut snippets: Vec<Snippet> = Snippet::all(db())
+            .query()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|doc| doc.contents)
+            .collect();
+        snippets.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        snippets
+    }
+    fn save(mut snippet: Snippet) -> anyhow::Result<()> {
+        snippet.keyword = snippet.keyword.trim().to_string();
+        if !snippet.keyword.is_empty()
+            && Self::all()
+                .iter()
+                .any(|other| other.id != snippet.id && other.keyword == snippet.keyword)
+        {
+            return Err(anyhow!("Keyword {} is already in use", snippet.keyword));
+        }
+        let id = snippet.id.clone();
+        snippet.overwrite_into(&id, db())?;
+        Ok(())
+    }
+    fn delete(id: &str) -> anyhow::Result<()> {
+        if let Some(doc) = Snippet::get(&id.to_string(), db())? {
+            doc.delete(db())?;
+        }
+        Ok(())
+    }
+    fn export(path: &PathBuf) -> anyhow::Result<usize> {
+        let snippets = Self::all();
+        fs::write(path, serde_json::to_string_pretty(&snippets)?)?;
+        Ok(snippets.len())
+    }
+    /// Snippets with the name of an existing one replace it. Returns how many were imported.
+    fn import(path: &PathBuf) -> anyhow::Result<usize> {
+        let snippets: Vec<Snippet> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let existing = Self::all();
+        let mut count = 0;
+        for mut snippet in snippets {
+            snippet.id = existing
+                .iter()
+                .find(|other| other.name == snippet.name)
+                .map(|other| other.id.clone())
+                .unwrap_or_else(new_id);
+            if let Err(err) = Self::save(snippet) {
+                error!("Failed to import snippet: {}", err);
+                continue;
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+fn new_id() -> String {
+    format!("{:08x}", rand::random::<u32>())
+}
+
+/// Asks for the values of the `{argument:name}` placeholders, then pastes or copies the snippet.
+#[derive(Clone)]
+struct SnippetArgumentsBuilder {
+    snippet: Snippet,
+    copy: bool,
+}
+
+impl StateViewBuilder for SnippetArgumentsBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        let snippet = self.snippet.clone();
+        let copy = self.copy;
+        Form::new(
+            self.snippet
+                .arguments()
+                .into_iter()
+                .map(|name| {
+                    Input::new(
+                        name.clone(),
+                        name.clone(),
+                        InputKind::TextField {
+                            placeholder: format!("Enter {}...", name),
+                            value: "".to_string(),
+                            password: false,
+                            validate: None,
+                        },
+                        cx,
+                    )
+                })
+                .collect(),
+            move |values, actions, cx| {
+                let arguments: HashMap<String, String> = values
+                    .iter()
+                    .map(|(name, input)| (name.clone(), input.value::<String>()))
+                    .collect();
+                if copy {
+                    snippet.copy(&arguments, actions, cx);
+                } else {
+                    snippet.paste(&arguments, cx);
+                }
+            },
+            context,
+            cx,
+        )
+        .into()
+    }
+}
+
+#[derive(Clone)]
+struct SnippetFormBuilder {
+    snippet: Option<Snippet>,
+    // The list to go back to once the snippet is saved
+    parent: Actions,
+}
+
+impl StateViewBuilder for SnippetFormBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        let snippet = self.snippet.clone();
+        let parent = self.parent.clone();
+        Form::new(
+            vec![
+                Input::new(
+                    "name",
+                    "Name",
+                    InputKind::TextField {
+                        placeholder: "Email Signature".to_string(),
+                        value: snippet
+                            .as_ref()
+                            .map(|snippet| snippet.name.clone())
+                            .unwrap_or_default(),
+                        password: false,
+                        validate: Some(|v| v.trim().is_empty().then_some("Name is required")),
+                    },
+                    cx,
+                ),
+                Input::new(
+                    "text",
+                    "Snippet",
+                    InputKind::TextField {
+                        placeholder: "Hi {argument:name}, {cursor}".to_string(),
+                        value: snippet
+                            .as_ref()
+                            .map(|snippet| snippet.text.clone())
+                            .unwrap_or_default(),
+                        password: false,
+                        validate: Some(|v| v.is_empty().then_some("Snippet is required")),
+                    },
+                    cx,
+                ),
+                Input::new(
+                    "keyword",
+                    "Keyword",
+                    InputKind::TextField {
+                        placeholder: "Optional, e.g. ;sig".to_string(),
+                        value: snippet
+                            .as_ref()
+                            .map(|snippet| snippet.keyword.clone())
+                            .unwrap_or_default(),
+                        password: false,
+                        validate: Some(|v| {
+                            v.trim()
+                                .contains(char::is_whitespace)
+                                .then_some("Keyword can't contain spaces")
+                        }),
+                    },
+                    cx,
+                ),
+            ],
+            move |values, actions, cx| {
+                let saved = Snippet {
+                    id: snippet
+                        .as_ref()
+                        .map(|snippet| snippet.id.clone())
+                        .unwrap_or_else(new_id),
+                    name: values["name"].value::<String>().trim().to_string(),
+                    keyword: values["keyword"].value::<String>(),
+                    text: values["text"].value::<String>(),
+                };
+                if let Err(err) = Snippets::save(saved) {
+                    error!("Failed to save snippet: {}", err);
+                    actions.toast.error(err, cx);
+                    return;
+                }
+                changed(cx);
+                StateModel::update(|this, cx| this.pop(cx), cx);
+                let mut parent = parent.clone();
+                parent.toast.success("Snippet saved", cx);
+                parent.update();
+            },
+            context,
+            cx,
+        )
+        .into()
+    }
+}
+
+#[derive(Clone)]
+struct SnippetFileBuilder {
+    import: bool,
+    parent: Actions,
+}
+
+impl StateViewBuilder for SnippetFileBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        let import = self.import;
+        let parent = self.parent.clone();
+        Form::new(
+            vec![Input::new(
+                "path",
+                "File",
+                InputKind::TextField {
+                    placeholder: "Path to a JSON file...".to_string(),
+                    value: paths()
+                        .home
+                        .join("snippets.json")
+                        .to_string_lossy()
+                        .to_string(),
+                    password: false,
+                    validate: Some(|v| v.trim().is_empty().then_some("File is required")),
+                },
+                cx,
+            )],
+            move |values, actions, cx| {
+                let path = PathBuf::from(values["path"].value::<String>().trim());
+                let result = if import {
+                    Snippets::import(&path).map(|count| format!("Imported {} snippets", count))
+                } else {
+                    Snippets::export(&path).map(|count| format!("Exported {} snippets", count))
+                };
+                match result {
+                    Ok(message) => {
+                        changed(cx);
+                        StateModel::update(|this, cx| this.pop(cx), cx);
+                        let mut parent = parent.clone();
+                        parent.toast.success(message, cx);
+                        parent.update();
+                    }
+                    Err(err) => {
+                        error!(
+                            "Failed to {} snippets: {}",
+                            if import { "import" } else { "export" },
+                            err
+                        );
+                        actions.toast.error(err, cx);
+                    }
+                }
+            },
+            context,
+            cx,
+        )
+        .into()
+    }
+}
+
+fn item(snippet: Snippet) -> Item {
+    let mut accessories = vec![];
+    if !snippet.keyword.is_empty() {
+        accessories.push(Accessory::new(
+            snippet.keyword.clone(),
+            Some(Img::default().icon(Icon::Type)),
+        ));
+    }
+    ItemBuilder::new(
+        format!("snippet:{}", snippet.id),
+        ListItem::new(
+            Some(Img::default().icon(Icon::TextCursorInput)),
+            snippet.name.clone(),
+            snippet.text.lines().next().map(String::from),
+            accessories,
+        ),
+    )
+    .keywords(vec![
+        snippet.name.clone(),
+        snippet.keyword.clone(),
+        snippet.text.clone(),
+    ])
+    .actions(vec![
+        Action::new(
+            Img::default().icon(Icon::ClipboardPaste),
+            "Paste",
+            None,
+            {
+                let snippet = snippet.clone();
+                move |_, cx| {
+                    if snippet.arguments().is_empty() {
+                        snippet.paste(&HashMap::new(), cx);
+                    } else {
+                        snippet.ask(false, cx);
+                    }
+                }
+            },
+            false,
+        ),
+        Action::new(
+            Img::default().icon(Icon::Copy),
+            "Copy to Clipboard",
+            Some(Shortcut::new("enter").shift()),
+            {
+                let snippet = snippet.clone();
+                move |actions, cx| {
+                    if snippet.arguments().is_empty() {
+                        snippet.copy(&HashMap::new(), actions, cx);
+                    } else {
+                        snippet.ask(true, cx);
+                    }
+                }
+            },
+            false,
+        ),
+        Action::new(
+            Img::default().icon(Icon::Pen),
+            "Edit",
+            Some(Shortcut::new("e").cmd()),
+            {
+                let snippet = snippet.clone();
+                move |actions, cx| {
+                    let builder = SnippetFormBuilder {
+                        snippet: Some(snippet.clone()),
+                        parent: actions.clone(),
+                    };
+                    StateModel::update(|this, cx| this.push(builder, cx), cx);
+                }
+            },
+            false,
+        ),
+        Action::new(
+            Img::default().icon(Icon::Trash),
+            "Delete",
+            Some(Shortcut::new("backspace").cmd()),
+            {
+                let id = snippet.id.clone();
+                move |actions, cx| {
+                    if let Err(err) = Snippets::delete(&id) {
+                        error!("Failed to delete snippet: {}", err);
+                        actions.toast.error("Failed to delete snippet", cx);
+                    } else {
+                        changed(cx);
+                        actions.toast.success("Snippet deleted", cx);
+                        actions.update();
+                    }
+                }
+            },
+            false,
+        ),
+    ])
+    .build()
+}
+
+#[derive(Clone)]
+struct SnippetsBuilder;
+
+impl StateViewBuilder for SnippetsBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        context.query.set_placeholder("Search snippets...", cx);
+        context.actions.update_global(
+            vec![
+                Action::new(
+                    Img::default().icon(Icon::PlusSquare),
+                    "Create Snippet",
+                    Some(Shortcut::new("n").cmd()),
+                    |actions, cx| {
+                        let builder = SnippetFormBuilder {
+                            snippet: None,
+                            parent: actions.clone(),
+                        };
+                        StateModel::update(|this, cx| this.push(builder, cx), cx);
+                    },
+                    false,
+                ),
+                Action::new(
+                    Img::default().icon(Icon::Import),
+                    "Import Snippets",
+                    None,
+                    |actions, cx| {
+                        let builder = SnippetFileBuilder {
+                            import: true,
+                            parent: actions.clone(),
+                        };
+                        StateModel::update(|this, cx| this.push(builder, cx), cx);
+                    },
+                    false,
+                ),
+                Action::new(
+                    Img::default().icon(Icon::FileDown),
+                    "Export Snippets",
+                    None,
+                    |actions, cx| {
+                        let builder = SnippetFileBuilder {
+                            import: false,
+                            parent: actions.clone(),
+                        };
+                        StateModel::update(|this, cx| this.push(builder, cx), cx);
+                    },
+                    false,
+                ),
+            ],
+            cx,
+        );
+        ListBuilder::new()
+            .build(
+                |_, _, _| Ok(Some(Snippets::all().into_iter().map(item).collect())),
+                context,
+                cx,
+            )
+            .into()
+    }
+}
+
+pub struct SnippetsCommandBuilder;
+
+impl RootCommandBuilder for SnippetsCommandBuilder {
+    fn build(&self, _cx: &mut WindowContext) -> RootCommand {
+        RootCommand::new(
+            "snippets",
+            "Search Snippets",
+            "Loungy",
+            Icon::TextCursorInput,
+            vec!["Text", "Templates", "Paste"],
+            None,
+            Box::new(|_, cx| {
+                StateModel::update(|this, cx| this.push(SnippetsBuilder, cx), cx);
+            }),
+        )
+    }
+}
@@ -56,6 +56,7 @@ impl Input {
         let value: Box<dyn Any> = match self.kind.clone() {
             InputKind::TextField { value, .. } => Box::new(value),
             InputKind::Shortcut { value, .. } => Box::new(value),
+            InputKind::Dropdown { value, .. } => Box::new(value),
         };
         value.downcast_ref::<V>().unwrap().clone()
     }
@@ -136,6 +137,39 @@ impl Render for InputView {
                                 )
                                 .child("Recording...")
                                 .into_any_element(),
+                            InputKind::Dropdown { value, options } => div()
+                                .relative()
+                                .child(
+                                    div()
+                                        .absolute()
+                                        .top_full()
+                                        .mt_3()
+                                        .neg_left_2()
+                                        .neg_right_2()
+                                        .p_2()
+                                        .shadow_md()
+                                        .rounded_lg()
+                                        .bg(theme.mantle)
+                                        .border_1()
+                                        .border_color(theme.crust)
+                                        .children(options.into_iter().map(|(option, label)| {
+                                            let item =
+                                                div().px_2().py_1().rounded_md().child(label);
+                                            if option == value {
+                                                item.bg(theme.surface0).text_color(theme.text)
+                                            } else {
+                                                item
+                                            }
+                                        })),
+                                )
+                                .child(
+                                    self.input
+                                        .view
+                                        .upgrade()
+                                        .map(|q| q.into_any_element())
+                                        .unwrap_or(div().into_any_element()),
+                                )
+                                .into_any_element(),
                         }
                     } else {
                         match self.inner.kind.clone() {
@@ -170,6 +204,12 @@ impl Render for InputView {
                                     "Record Hotkey".into_any_element()
                                 }
                             }
+                            InputKind::Dropdown { value, options } => options
+                                .into_iter()
+                                .find(|(option, _)| *option == value)
+                                .map(|(_, label)| label)
+                                .unwrap_or(value)
+                                .into_any_element(),
                         }
                     })
                     .w_1_2()
@@ -219,6 +259,12 @@ impl InputView {
                 self.input.set_text(value, cx);
             }
             InputKind::Shortcut { .. } => self.input.set_text("Record hotkey", cx),
+            InputKind::Dropdown { .. } => {
+                self.input.set_masked(false, cx);
+                self.input
+                    .set_placeholder("Type to choose, or use up and down", cx);
+                self.input.set_text("", cx);
+            }
         };
     }
     pub fn on_blur(&mut self, _: &mut ViewContext<Self>) {
@@ -280,6 +326,28 @@ impl InputView {
                     }
                 }
             }
+            InputKind::Dropdown { value, options } => {
+                let current = options.iter().position(|(option, _)| *option == value);
+                let selected = match event {
+                    // Typing picks the first option containing the text
+                    TextEvent::Input { text } if !text.is_empty() => {
+                        let text = text.to_lowercase();
+                        options
+                            .iter()
+                            .position(|(_, label)| label.to_lowercase().contains(&text))
+                    }
+                    TextEvent::KeyDown(e) => match e.keystroke.key.as_str() {
+                        "up" => Some(current.map_or(0, |i| i.saturating_sub(1))),
+                        "down" => Some(current.map_or(0, |i| (i + 1).min(options.len() - 1))),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if let Some((value, _)) = selected.and_then(|i| options.get(i)).cloned() {
+                    self.inner.kind = InputKind::Dropdown { value, options };
+                    cx.notify();
+                }
+            }
         }
         if let TextEvent::KeyDown(e) = event {
             if (Shortcut::new("tab").shift().get()).eq(&e.keystroke) {
@@ -356,6 +424,11 @@ pub enum InputKind {
         value: Option<Shortcut>,
         tmp: Option<Shortcut>,
     },
+    Dropdown {
+        value: String,
+        // Values along with their labels
+        options: Vec<(String, String)>,
+    },
 }
 
 pub trait SubmitFn: Fn(HashMap<String, Input>, &mut Actions, &mut WindowContext) {
@@ -10,7 +10,7 @@
  */
 
 use std::{
-    collections::{hash_map::DefaultHasher, HashMap},
+    collections::HashMap,
     hash::{Hash, Hasher},
     sync::mpsc::channel,
     time::Duration,
@@ -37,6 +37,7 @@ use super::shared::{Img, ImgSource};
 pub enum Accessory {
     Tag { tag: String, img: Option<Img> },
     Shortcut(Shortcut),
+    View(AnyView),
 }
 
 impl Accessory {
@@ -49,6 +50,9 @@ impl Accessory {
     pub fn shortcut(shortcut: Shortcut) -> Self {
         Self::Shortcut(shortcut)
     }
+    pub fn view(view: impl Into<AnyView>) -> Self {
+        Self::View(view.into())
+    }
 }
 
 impl RenderOnce for Accessory {
@@ -76,6 +80,7 @@ impl RenderOnce for Accessory {
                 el.child(tag).ml_6()
             }
             Accessory::Shortcut(shortcut) => div().child(shortcut),
+            Accessory::View(view) => div().ml_6().child(view),
         }
     }
 }
@@ -168,13 +173,32 @@ pub struct ItemBuilder {
     meta: Option<AnyModel>,
 }
 
+// FNV-1a, unlike `DefaultHasher` its algorithm is fixed, so persisted IDs survive toolchain upgrades
+struct StableHasher(u64);
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+/// The ID of items built from `id`, which stays the same across launches.
+pub fn item_id(id: impl Hash) -> u64 {
+    let mut s = StableHasher(0xcbf29ce484222325);
+    id.hash(&mut s);
+    s.finish()
+}
+
 impl ItemBuilder {
     pub fn new(id: impl Hash, component: impl ItemComponent + 'static) -> Self {
-        let mut s = DefaultHasher::new();
-        id.hash(&mut s);
-        let id = s.finish();
         Self {
-            id,
+            id: item_id(id),
             preview: None,
             actions: vec![],
             weight: None,
@@ -254,6 +278,15 @@ impl Item {
     pub fn get_keywords(&self) -> &Vec<String> {
         self.keywords.as_ref()
     }
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+    pub fn get_actions(&self) -> &Vec<Action> {
+        &self.actions
+    }
+    pub fn get_actions_mut(&mut self) -> &mut Vec<Action> {
+        &mut self.actions
+    }
 }
 
 pub trait Preview: Fn(&mut WindowContext) -> StateItem {
@@ -791,11 +824,7 @@ impl AsyncListItems {
     }
     pub fn remove(&mut self, key: String, id: impl Hash, cx: &mut ViewContext<Self>) {
         if let Some(items) = self.items.get_mut(&key) {
-            let hash = {
-                let mut s = DefaultHasher::new();
-                id.hash(&mut s);
-                s.finish()
-            };
+            let hash = item_id(id);
             if let Some(i) = items.iter().position(|i| i.id.eq(&hash)) {
                 items.remove(i);
                 cx.emit(AsyncListItemsEvent::Update);
@@ -835,3 +864,19 @@ pub enum AsyncListItemsEvent {
 }
 
 impl EventEmitter<AsyncListItemsEvent> for AsyncListItems {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn item_ids_are_stable() {
+        // Reference vector of FNV-1a
+        let mut hasher = StableHasher(0xcbf29ce484222325);
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63dc4c8601ec8c);
+        // Frecency is stored by these, they must never change
+        assert_eq!(item_id("loungy"), 16477561785835117828);
+        assert_eq!(item_id(42u64), 18391255480883862255);
+    }
+}
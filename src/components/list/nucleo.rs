@@ -11,7 +11,8 @@
 
 use std::cmp::Reverse;
 
-use nucleo::pattern::{Atom, AtomKind, CaseMatching, Normalization};
+pub use nucleo::pattern::CaseMatching;
+use nucleo::pattern::{Atom, AtomKind, Normalization};
 use nucleo::{Config, Matcher, Utf32Str};
 
 use crate::components::list::Item;
@@ -20,25 +21,49 @@ use crate::state::LazyMutex;
 pub static MATCHER: LazyMutex<nucleo::Matcher> = LazyMutex::new(nucleo::Matcher::default);
 
 pub fn fuzzy_match<T: Score>(pattern: &str, items: Vec<T>, path: bool) -> Vec<T> {
-    let mut matcher = MATCHER.lock();
+    fuzzy_match_case(pattern, items, path, CaseMatching::Smart)
+}
+
+pub fn fuzzy_match_case<T: Score>(
+    pattern: &str,
+    items: Vec<T>,
+    path: bool,
+    case: CaseMatching,
+) -> Vec<T> {
+    let mut items = fuzzy_scores(pattern, items, path, case);
+    items.sort_by_key(|(_, score)| Reverse(*score));
+    items.into_iter().map(|item| item.0).collect()
+}
+
+/// Like `fuzzy_match`, but returns the matching items unsorted along with their score,
+/// for callers that factor in more than the match quality.
+pub fn fuzzy_scores<T: Score>(
+    pattern: &str,
+    items: Vec<T>,
+    path: bool,
+    case: CaseMatching,
+) -> Vec<(T, u16)> {
+    fuzzy_scores_with(&mut MATCHER.lock(), pattern, items, path, case)
+}
+
+/// Like `fuzzy_scores`, but with a matcher of its own, so long searches don't hold up the shared one.
+pub fn fuzzy_scores_with<T: Score>(
+    matcher: &mut Matcher,
+    pattern: &str,
+    items: impl IntoIterator<Item = T>,
+    path: bool,
+    case: CaseMatching,
+) -> Vec<(T, u16)> {
     matcher.config = Config::DEFAULT;
     if path {
         matcher.config.set_match_paths();
     }
-    let pattern = Atom::new(
-        pattern,
-        CaseMatching::Smart,
-        Normalization::Smart,
-        AtomKind::Fuzzy,
-        false,
-    );
+    let pattern = Atom::new(pattern, case, Normalization::Smart, AtomKind::Fuzzy, false);
     let mut buf = Vec::new();
-    let mut items: Vec<(T, u16)> = items
+    items
         .into_iter()
-        .filter_map(|item| item.score(&pattern, &mut buf, &mut matcher))
-        .collect();
-    items.sort_by_key(|(_, score)| Reverse(*score));
-    items.into_iter().map(|item| item.0).collect()
+        .filter_map(|item| item.score(&pattern, &mut buf, matcher))
+        .collect()
 }
 
 pub trait Score {
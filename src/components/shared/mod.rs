@@ -47,6 +47,7 @@ pub enum ImgSource {
     Base(ImageSource),
     Icon { icon: Icon, color: Option<Hsla> },
     Dot(Hsla),
+    Emoji(SharedString),
     Favicon(View<Favicon>),
     None,
 }
@@ -130,6 +131,11 @@ impl Img {
         self.src = ImgSource::Favicon(favicon);
         self
     }
+    pub fn emoji(mut self, emoji: impl Into<SharedString>) -> Self {
+        self.src = ImgSource::Emoji(emoji.into());
+        self.mask = ImgMask::Rounded;
+        self
+    }
     pub fn file(mut self, src: PathBuf) -> Self {
         self.src = ImgSource::Base(ImageSource::File(Arc::new(src)));
         self
@@ -148,6 +154,12 @@ impl Img {
     }
 }
 
+impl From<Icon> for Img {
+    fn from(icon: Icon) -> Self {
+        Img::default().icon(icon)
+    }
+}
+
 impl RenderOnce for Img {
     fn render(self, cx: &mut WindowContext) -> impl IntoElement {
         if let ImgSource::Favicon(favicon) = &self.src {
@@ -211,6 +223,7 @@ impl RenderOnce for Img {
                 img.into_any_element()
             }
             ImgSource::Dot(color) => div().rounded_full().bg(color).size_1_2().into_any_element(),
+            ImgSource::Emoji(emoji) => div().text_sm().child(emoji).into_any_element(),
             ImgSource::Favicon(_) => unreachable!(),
             ImgSource::None => div().into_any_element(),
         };
@@ -0,0 +1,208 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{
+    collections::BTreeSet,
+    io::{self, BufRead, Write},
+    sync::Arc,
+};
+
+use clap::Args;
+use gpui::*;
+
+use crate::{
+    components::{
+        list::{
+            nucleo::{fuzzy_match_case, CaseMatching},
+            Accessory, ItemBuilder, ListBuilder, ListItem,
+        },
+        shared::{Icon, Img},
+    },
+    state::{Action, Shortcut, StateViewBuilder, StateViewContext},
+};
+
+#[derive(Args, Clone, Debug)]
+pub struct DmenuArgs {
+    /// Read newline separated entries from stdin and print the chosen one to stdout
+    #[arg(long)]
+    pub dmenu: bool,
+    /// Placeholder text of the query
+    #[arg(short, long, requires = "dmenu")]
+    pub prompt: Option<String>,
+    /// Allow selecting multiple entries with tab, which are printed one per line
+    #[arg(short, long, requires = "dmenu")]
+    pub multi_select: bool,
+    /// Match case insensitively
+    #[arg(
+        short = 'i',
+        long,
+        requires = "dmenu",
+        conflicts_with = "case_sensitive"
+    )]
+    pub case_insensitive: bool,
+    /// Match case sensitively, by default the query is only case sensitive if it contains uppercase characters
+    #[arg(long, requires = "dmenu")]
+    pub case_sensitive: bool,
+    /// Print the query instead with shift+enter, or with enter if no entry matches
+    #[arg(long, requires = "dmenu")]
+    pub print_query: bool,
+    /// Exit with status 1 instead of 0 if the prompt gets cancelled
+    #[arg(long, requires = "dmenu")]
+    pub fail_on_cancel: bool,
+}
+
+impl DmenuArgs {
+    fn case_matching(&self) -> CaseMatching {
+        if self.case_insensitive {
+            CaseMatching::Ignore
+        } else if self.case_sensitive {
+            CaseMatching::Respect
+        } else {
+            CaseMatching::Smart
+        }
+    }
+    fn cancel_code(&self) -> i32 {
+        if self.fail_on_cancel {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+fn finish(lines: Vec<String>) -> ! {
+    let mut stdout = io::stdout().lock();
+    for line in lines {
+        let _ = writeln!(stdout, "{}", line);
+    }
+    let _ = stdout.flush();
+    std::process::exit(0);
+}
+
+#[derive(Clone)]
+pub struct DmenuListBuilder {
+    args: DmenuArgs,
+    entries: Arc<Vec<String>>,
+}
+
+impl DmenuListBuilder {
+    pub fn cancel_code(&self) -> i32 {
+        self.args.cancel_code()
+    }
+    pub fn new(args: DmenuArgs) -> Self {
+        let entries = io::stdin()
+            .lock()
+            .lines()
+            .map_while(Result::ok)
+            .filter(|line| !line.is_empty())
+            .collect();
+        Self {
+            args,
+            entries: Arc::new(entries),
+        }
+    }
+}
+
+impl StateViewBuilder for DmenuListBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        context
+            .query
+            .set_placeholder(self.args.prompt.as_deref().unwrap_or("Search..."), cx);
+        if self.args.print_query {
+            // Global actions are the default once no entry matches
+            let query = context.query.clone();
+            context.actions.update_global(
+                vec![Action::new(
+                    Img::default().icon(Icon::TextCursorInput),
+                    "Print Query",
+                    Some(Shortcut::new("enter").shift()),
+                    move |_, cx| finish(vec![query.get_text(cx)]),
+                    false,
+                )],
+                cx,
+            );
+        }
+        // Indices of the selected entries, so duplicate entries can be told apart
+        let selection = cx.new_model(|_| BTreeSet::<usize>::new());
+        let entries = self.entries.clone();
+        let multi_select = self.args.multi_select;
+        let case = self.args.case_matching();
+        ListBuilder::new()
+            .filter(move |this, cx| {
+                let query = this.query.get_text(cx);
+                fuzzy_match_case(&query, this.items_all.clone(), false, case)
+            })
+            .build(
+                move |_, _, cx| {
+                    let selected = selection.read(cx).clone();
+                    let items = entries
+                        .iter()
+                        .enumerate()
+                        .map(|(i, entry)| {
+                            let mut actions = vec![Action::new(
+                                Img::default().icon(Icon::Check),
+                                "Select",
+                                None,
+                                {
+                                    let entries = entries.clone();
+                                    let selected = selected.clone();
+                                    move |_, _| {
+                                        if selected.is_empty() {
+                                            finish(vec![entries[i].clone()]);
+                                        }
+                                        finish(
+                                            selected.iter().map(|i| entries[*i].clone()).collect(),
+                                        );
+                                    }
+                                },
+                                false,
+                            )];
+                            if multi_select {
+                                actions.push(Action::new(
+                                    Img::default().icon(Icon::ListChecks),
+                                    "Toggle Selection",
+                                    Some(Shortcut::new("tab")),
+                                    {
+                                        let selection = selection.clone();
+                                        move |actions, cx| {
+                                            selection.update(cx, |this, _| {
+                                                if !this.remove(&i) {
+                                                    this.insert(i);
+                                                }
+                                            });
+                                            actions.update();
+                                        }
+                                    },
+                                    false,
+                                ));
+                            }
+                            let accessories = if selected.contains(&i) {
+                                vec![Accessory::new(
+                                    "Selected",
+                                    Some(Img::default().icon(Icon::Check)),
+                                )]
+                            } else {
+                                vec![]
+                            };
+                            ItemBuilder::new(i, ListItem::new(None, entry, None, accessories))
+                                .keywords(vec![entry.clone()])
+                                .actions(actions)
+                                .build()
+                        })
+                        .collect();
+                    Ok(Some(items))
+                },
+                context,
+                cx,
+            )
+            .into()
+    }
+}
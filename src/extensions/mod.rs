@@ -0,0 +1,290 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use futures::{channel::mpsc::UnboundedReceiver, StreamExt};
+use gpui::*;
+use log::error;
+use serde_json::Value;
+
+use crate::{
+    commands::{RootCommand, RootCommandBuilder},
+    components::shared::{Icon, Img},
+    paths::paths,
+    state::{ActionsModel, StateModel},
+    window::Window,
+};
+
+use self::{
+    process::{decode, Extension},
+    protocol::{Event, ManifestCommand, Message, Request, ToastStyle},
+    view::ExtensionViewBuilder,
+};
+
+mod process;
+pub mod protocol;
+#[cfg(test)]
+mod tests;
+mod view;
+
+/// Resolves an icon of the protocol, which is either the name of an icon, an URL, a path relative to the
+/// extension's folder or an emoji.
+pub fn icon(icon: &Option<String>, dir: &Path) -> Option<Img> {
+    let icon = icon.as_ref()?;
+    if let Ok(icon) = serde_json::from_value::<Icon>(Value::String(icon.clone())) {
+        return Some(Img::default().icon(icon));
+    }
+    if icon.starts_with("http://") || icon.starts_with("https://") {
+        return Some(Img::default().url(icon));
+    }
+    let path = dir.join(icon);
+    Some(if path.is_file() {
+        Img::default().file(path)
+    } else {
+        Img::default().emoji(icon.clone())
+    })
+}
+
+#[derive(Clone)]
+struct OpenView {
+    view: WeakModel<protocol::View>,
+    actions: ActionsModel,
+}
+
+/// Out of process extensions from the config folder, which get started the first time one of their commands runs.
+pub struct Extensions {
+    extensions: HashMap<String, Extension>,
+    views: HashMap<(String, String), OpenView>,
+}
+
+impl Extensions {
+    pub fn init(cx: &mut AppContext) {
+        let mut extensions = HashMap::new();
+        if let Ok(entries) = paths().config.join("extensions").read_dir() {
+            for entry in entries.flatten() {
+                let dir = entry.path();
+                if !dir.is_dir() {
+                    continue;
+                }
+                match Extension::load(dir.clone()) {
+                    Ok(extension) => {
+                        extensions.insert(extension.manifest.id.clone(), extension);
+                    }
+                    Err(err) => error!("Failed to load extension {}: {}", dir.display(), err),
+                }
+            }
+        }
+        cx.set_global(Self {
+            extensions,
+            views: HashMap::new(),
+        });
+    }
+    pub fn builders(cx: &AppContext) -> Vec<Box<dyn RootCommandBuilder>> {
+        let mut builders: Vec<Box<dyn RootCommandBuilder>> = vec![];
+        for extension in cx.global::<Self>().extensions.values() {
+            for command in &extension.manifest.commands {
+                builders.push(Box::new(ExtensionCommandBuilder {
+                    extension: extension.manifest.id.clone(),
+                    dir: extension.dir.clone(),
+                    command: command.clone(),
+                }));
+            }
+        }
+        builders
+    }
+    fn dir(extension: &str, cx: &AppContext) -> PathBuf {
+        cx.global::<Self>()
+            .extensions
+            .get(extension)
+            .map(|extension| extension.dir.clone())
+            .unwrap_or_default()
+    }
+    /// Sends an event to the extension, starting it first if it isn't running.
+    pub fn send(extension: &str, event: Event, cx: &mut WindowContext) -> anyhow::Result<()> {
+        let message = Message::notification(&event)?;
+        let receiver = cx.update_global::<Self, _>(|this, _| {
+            let extension = this
+                .extensions
+                .get_mut(extension)
+                .ok_or(anyhow::anyhow!("Unknown extension"))?;
+            let receiver = if extension.exited() {
+                Some(extension.start()?)
+            } else {
+                None
+            };
+            extension.write(&message)?;
+            anyhow::Ok(receiver)
+        })?;
+        if let Some(receiver) = receiver {
+            Self::listen(extension.to_string(), receiver, cx);
+        }
+        Ok(())
+    }
+    fn listen(extension: String, mut receiver: UnboundedReceiver<String>, cx: &mut WindowContext) {
+        cx.spawn(|mut cx| async move {
+            while let Some(line) = receiver.next().await {
+                let _ = cx.update(|cx| Self::handle(&extension, &line, cx));
+            }
+            // Stdout got closed, so make sure the extension gets restarted next time
+            let _ = cx.update_global::<Self, _>(|this, _| {
+                if let Some(extension) = this.extensions.get_mut(&extension) {
+                    extension.reap();
+                }
+            });
+        })
+        .detach();
+    }
+    fn handle(extension: &str, line: &str, cx: &mut WindowContext) {
+        let call = match decode(line) {
+            Ok(Some(call)) => call,
+            Ok(None) => return,
+            Err(err) => {
+                error!("Invalid message from extension {}: {}", extension, err);
+                return;
+            }
+        };
+        let result = call.request.map(|request| {
+            Self::apply(extension, request, cx);
+            Value::Null
+        });
+        if let Some(id) = call.id {
+            let _ = cx.update_global::<Self, _>(|this, _| {
+                this.extensions
+                    .get_mut(extension)
+                    .map(|e| e.write(&Message::response(id, result)))
+            });
+        } else if let Err(err) = result {
+            error!("Invalid call from extension {}: {}", extension, err.message);
+        }
+    }
+    fn apply(extension: &str, request: Request, cx: &mut WindowContext) {
+        let builder = |view| ExtensionViewBuilder {
+            extension: extension.to_string(),
+            view,
+        };
+        match request {
+            Request::Push { view } => {
+                StateModel::update(|this, cx| this.push(builder(view), cx), cx);
+            }
+            Request::Replace { view } => {
+                StateModel::update(|this, cx| this.replace(builder(view), cx), cx);
+            }
+            Request::Update { view } => {
+                let key = (extension.to_string(), view.id.clone());
+                let Some(open) = cx.global::<Self>().views.get(&key).cloned() else {
+                    return;
+                };
+                let Some(model) = open.view.upgrade() else {
+                    return;
+                };
+                open.actions
+                    .update_global(view::actions(extension, &view.id, &view.actions, cx), cx);
+                model.update(cx, |this, cx| {
+                    *this = view;
+                    cx.notify();
+                });
+                if let Some(actions) = open.actions.inner.upgrade() {
+                    actions.read(cx).update();
+                }
+            }
+            Request::Pop => {
+                StateModel::update(|this, cx| this.pop(cx), cx);
+            }
+            Request::Toast { style, message } => {
+                let Some(item) = cx.global::<StateModel>().inner.read(cx).stack.last() else {
+                    return;
+                };
+                let mut actions = item.actions.read(cx).clone();
+                match style {
+                    ToastStyle::Loading => actions.toast.loading(message, cx),
+                    ToastStyle::Success => actions.toast.success(message, cx),
+                    ToastStyle::Error => actions.toast.error(message, cx),
+                }
+            }
+            Request::Close => Window::close(cx),
+        }
+    }
+    fn open(
+        extension: &str,
+        view: &Model<protocol::View>,
+        actions: &ActionsModel,
+        cx: &mut WindowContext,
+    ) {
+        let id = view.read(cx).id.clone();
+        let key = (extension.to_string(), id.clone());
+        cx.update_global::<Self, _>(|this, _| {
+            this.views.insert(
+                key.clone(),
+                OpenView {
+                    view: view.downgrade(),
+                    actions: actions.clone(),
+                },
+            );
+        });
+        cx.observe_release(view, move |_, cx| {
+            cx.update_global::<Self, _>(|this, _| {
+                // The same ID might have been pushed again in the meantime
+                if this
+                    .views
+                    .get(&key)
+                    .is_some_and(|open| open.view.upgrade().is_none())
+                {
+                    this.views.remove(&key);
+                }
+                if let Some(extension) = this.extensions.get_mut(&key.0) {
+                    if let Ok(message) = Message::notification(&Event::Closed { view: id }) {
+                        let _ = extension.write(&message);
+                    }
+                }
+            });
+        })
+        .detach();
+    }
+}
+
+impl Global for Extensions {}
+
+pub struct ExtensionCommandBuilder {
+    extension: String,
+    dir: PathBuf,
+    command: ManifestCommand,
+}
+
+impl RootCommandBuilder for ExtensionCommandBuilder {
+    fn build(&self, _cx: &mut WindowContext) -> RootCommand {
+        let extension = self.extension.clone();
+        let command = self.command.id.clone();
+        RootCommand::new(
+            format!("{}.{}", self.extension, self.command.id),
+            &self.command.title,
+            self.command
+                .subtitle
+                .clone()
+                .unwrap_or("Extension".to_string()),
+            icon(&self.command.icon, &self.dir).unwrap_or(Img::default().icon(Icon::Puzzle)),
+            self.command.keywords.clone(),
+            None,
+            Box::new(move |actions, cx| {
+                let event = Event::Run {
+                    command: command.clone(),
+                };
+                if let Err(err) = Extensions::send(&extension, event, cx) {
+                    error!("Failed to run extension {}: {}", extension, err);
+                    actions.toast.error("Failed to run extension", cx);
+                }
+            }),
+        )
+    }
+}
@@ -0,0 +1,148 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin, Command, Stdio},
+    thread,
+};
+
+use anyhow::anyhow;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use serde_json::{json, Value};
+
+use super::protocol::{self, Manifest, Message, Request, RpcError};
+
+struct Process {
+    child: Child,
+    stdin: ChildStdin,
+}
+
+pub struct Extension {
+    pub manifest: Manifest,
+    pub dir: PathBuf,
+    process: Option<Process>,
+    next_id: u64,
+}
+
+impl Extension {
+    pub fn load(dir: PathBuf) -> anyhow::Result<Self> {
+        let manifest = fs::read_to_string(dir.join("manifest.json"))?;
+        Ok(Self {
+            manifest: serde_json::from_str(&manifest)?,
+            dir,
+            process: None,
+            next_id: 0,
+        })
+    }
+    /// Starts the extension process, returns the lines it writes to stdout until it exits.
+    pub fn start(&mut self) -> anyhow::Result<UnboundedReceiver<String>> {
+        let (program, args) = self
+            .manifest
+            .command
+            .split_first()
+            .ok_or(anyhow!("Manifest has no command"))?;
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(&self.dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let (sender, receiver) = unbounded();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.unbounded_send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        self.process = Some(Process { child, stdin });
+        let id = self.next_id();
+        self.write(&Message::request(
+            id,
+            "initialize",
+            json!({ "version": protocol::VERSION }),
+        ))?;
+        Ok(receiver)
+    }
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+    pub fn write(&mut self, message: &Message) -> anyhow::Result<()> {
+        let process = self
+            .process
+            .as_mut()
+            .ok_or(anyhow!("Extension is not running"))?;
+        writeln!(process.stdin, "{}", serde_json::to_string(message)?)?;
+        process.stdin.flush()?;
+        Ok(())
+    }
+    pub fn exited(&mut self) -> bool {
+        self.process
+            .as_mut()
+            .map(|process| !matches!(process.child.try_wait(), Ok(None)))
+            .unwrap_or(true)
+    }
+    /// Forgets the process once it exited, so it gets started again next time.
+    pub fn reap(&mut self) {
+        if self.exited() {
+            self.process = None;
+        }
+    }
+}
+
+impl Drop for Extension {
+    fn drop(&mut self) {
+        if let Some(process) = self.process.as_mut() {
+            let _ = process.child.kill();
+            let _ = process.child.wait();
+        }
+    }
+}
+
+/// A method called by the extension, `id` is set if it expects a response.
+pub struct Call {
+    pub id: Option<Value>,
+    pub request: Result<Request, RpcError>,
+}
+
+/// Reads a line written by the extension, responses don't call anything.
+pub fn decode(line: &str) -> anyhow::Result<Option<Call>> {
+    let message = serde_json::from_str::<Message>(line)?;
+    let Some(method) = message.method else {
+        // Only the response to initialize is expected
+        if let Some(err) = message.error {
+            return Err(anyhow!("Returned an error: {}", err.message));
+        }
+        return Ok(None);
+    };
+    let request = if !Request::METHODS.contains(&method.as_str()) {
+        Err(RpcError {
+            code: protocol::METHOD_NOT_FOUND,
+            message: format!("Unknown method {}", method),
+        })
+    } else {
+        serde_json::from_value::<Request>(json!({ "method": method, "params": message.params }))
+            .map_err(|err| RpcError {
+                code: protocol::INVALID_PARAMS,
+                message: err.to_string(),
+            })
+    };
+    Ok(Some(Call {
+        id: message.id,
+        request,
+    }))
+}
@@ -0,0 +1,252 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const VERSION: u32 = 1;
+
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// `manifest.json` in the extension's folder.
+#[derive(Deserialize, Clone)]
+pub struct Manifest {
+    pub id: String,
+    pub name: String,
+    /// Program and arguments to start the extension with, run from the extension's folder
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub commands: Vec<ManifestCommand>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ManifestCommand {
+    pub id: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+/// Extensions talk newline delimited JSON-RPC 2.0 over their stdin and stdout, see `extensions/README.md`.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Message {
+    pub jsonrpc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+impl Message {
+    pub fn request(id: u64, method: impl ToString, params: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: Some(id.into()),
+            method: Some(method.to_string()),
+            params: Some(params),
+            ..Default::default()
+        }
+    }
+    pub fn notification(event: &Event) -> anyhow::Result<Self> {
+        let Value::Object(mut event) = serde_json::to_value(event)? else {
+            unreachable!()
+        };
+        Ok(Self {
+            jsonrpc: "2.0".to_string(),
+            method: event
+                .remove("method")
+                .and_then(|m| m.as_str().map(|m| m.to_string())),
+            params: event.remove("params"),
+            ..Default::default()
+        })
+    }
+    pub fn response(id: Value, result: Result<Value, RpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: Some(id),
+            result,
+            error,
+            ..Default::default()
+        }
+    }
+}
+
+/// Notifications sent to the extension.
+#[derive(Serialize, Clone)]
+#[serde(tag = "method", content = "params", rename_all = "lowercase")]
+pub enum Event {
+    /// One of the manifest's root commands was launched
+    Run { command: String },
+    /// The query of a view changed
+    Query { view: String, query: String },
+    /// An action was triggered, `item` is unset for the view's own actions
+    Action {
+        view: String,
+        item: Option<String>,
+        action: String,
+    },
+    /// A form was submitted
+    Submit {
+        view: String,
+        values: HashMap<String, String>,
+    },
+    /// A view was popped from the stack, or the window was reset
+    Closed { view: String },
+}
+
+/// Methods the extension can call, either as requests or as notifications.
+#[derive(Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "lowercase")]
+pub enum Request {
+    Push {
+        view: View,
+    },
+    Replace {
+        view: View,
+    },
+    /// Replaces the contents of an open list or detail view with the same ID
+    Update {
+        view: View,
+    },
+    Pop,
+    Toast {
+        style: ToastStyle,
+        message: String,
+    },
+    Close,
+}
+
+impl Request {
+    pub const METHODS: [&'static str; 6] = ["push", "replace", "update", "pop", "toast", "close"];
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToastStyle {
+    Loading,
+    Success,
+    Error,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct View {
+    pub id: String,
+    #[serde(flatten)]
+    pub kind: ViewKind,
+    pub placeholder: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ViewKind {
+    List {
+        /// Fuzzy match the items against the query, extensions that search on their own should disable this and
+        /// listen to query events instead
+        #[serde(default = "enabled")]
+        filter: bool,
+        items: Vec<Item>,
+    },
+    Form {
+        inputs: Vec<Input>,
+    },
+    Detail {
+        text: String,
+    },
+}
+
+fn enabled() -> bool {
+    true
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Item {
+    pub id: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub accessories: Vec<Accessory>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(untagged)]
+pub enum Accessory {
+    Tag { tag: String, icon: Option<String> },
+    Shortcut { shortcut: String },
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Action {
+    pub id: String,
+    pub title: String,
+    pub icon: Option<String>,
+    /// Keystroke like `cmd-shift-d`
+    pub shortcut: Option<String>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Input {
+    pub id: String,
+    pub label: String,
+    #[serde(flatten)]
+    pub kind: InputKind,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum InputKind {
+    Text {
+        #[serde(default)]
+        placeholder: String,
+        #[serde(default)]
+        value: String,
+        #[serde(default)]
+        required: bool,
+    },
+    Password {
+        #[serde(default)]
+        placeholder: String,
+        #[serde(default)]
+        value: String,
+        #[serde(default)]
+        required: bool,
+    },
+    Shortcut {
+        value: Option<String>,
+    },
+}
@@ -0,0 +1,227 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{collections::HashMap, fs, path::PathBuf, time::Duration};
+
+use async_std::{future::timeout, task::block_on};
+use futures::{channel::mpsc::UnboundedReceiver, StreamExt};
+use serde_json::{json, Value};
+
+use super::{
+    process::{decode, Call, Extension},
+    protocol::{self, Event, Message, Request, ToastStyle, ViewKind},
+};
+
+// Logs every line it gets and answers each event with fixed calls
+static FAKE: &str = r#"
+while IFS= read -r line; do
+    printf '%s\n' "$line" >> received.jsonl
+    case "$line" in
+    *'"method":"initialize"'*)
+        echo '{"jsonrpc":"2.0","id":1,"result":null}' ;;
+    *'"method":"run"'*)
+        echo '{"jsonrpc":"2.0","id":1,"method":"push","params":{"view":{"id":"main","type":"list","items":[{"id":"a","title":"A","actions":[{"id":"open","title":"Open"}]}]}}}' ;;
+    *'"method":"query"'*)
+        echo '{"jsonrpc":"2.0","method":"update","params":{"view":{"id":"main","type":"list","filter":false,"items":[]}}}' ;;
+    *'"method":"action"'*)
+        echo '{"jsonrpc":"2.0","id":2,"method":"replace","params":{"view":{"id":"form","type":"form","inputs":[{"id":"name","label":"Name","type":"text"}]}}}' ;;
+    *'"method":"submit"'*)
+        echo '{"jsonrpc":"2.0","method":"toast","params":{"style":"success","message":"Saved"}}'
+        echo '{"jsonrpc":"2.0","method":"pop"}' ;;
+    *'"method":"closed"'*)
+        echo '{"jsonrpc":"2.0","id":3,"method":"close"}' ;;
+    esac
+done
+"#;
+
+struct Fake {
+    extension: Extension,
+    receiver: UnboundedReceiver<String>,
+    dir: PathBuf,
+}
+
+impl Fake {
+    fn start(name: &str) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("loungy-extension-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let manifest = json!({
+            "id": "fake",
+            "name": "Fake",
+            "command": ["sh", "fake.sh"],
+            "commands": [{ "id": "main", "title": "Fake" }],
+        });
+        fs::write(dir.join("manifest.json"), manifest.to_string()).unwrap();
+        fs::write(dir.join("fake.sh"), FAKE).unwrap();
+        let mut extension = Extension::load(dir.clone()).unwrap();
+        let receiver = extension.start().unwrap();
+        Self {
+            extension,
+            receiver,
+            dir,
+        }
+    }
+    fn send(&mut self, event: Event) {
+        let message = Message::notification(&event).unwrap();
+        self.extension.write(&message).unwrap();
+    }
+    // The next call of the extension, skipping responses
+    fn next(&mut self) -> Call {
+        loop {
+            let line = block_on(timeout(Duration::from_secs(5), self.receiver.next()))
+                .expect("The extension didn't answer")
+                .expect("The extension exited");
+            if let Some(call) = decode(&line).unwrap() {
+                return call;
+            }
+        }
+    }
+    fn received(&self) -> Vec<Value> {
+        fs::read_to_string(self.dir.join("received.jsonl"))
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+}
+
+impl Drop for Fake {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[test]
+fn initializes_before_the_first_event() {
+    let mut fake = Fake::start("initialize");
+    fake.send(Event::Run {
+        command: "main".to_string(),
+    });
+    fake.next();
+    assert_eq!(
+        fake.received(),
+        vec![
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "version": protocol::VERSION },
+            }),
+            json!({ "jsonrpc": "2.0", "method": "run", "params": { "command": "main" } }),
+        ]
+    );
+}
+
+#[test]
+fn drives_views_through_events() {
+    let mut fake = Fake::start("views");
+
+    fake.send(Event::Run {
+        command: "main".to_string(),
+    });
+    let call = fake.next();
+    assert_eq!(call.id, Some(json!(1)));
+    let Ok(Request::Push { view }) = call.request else {
+        panic!("Expected a push");
+    };
+    assert_eq!(view.id, "main");
+    let ViewKind::List { filter, items } = view.kind else {
+        panic!("Expected a list");
+    };
+    assert!(filter);
+    assert_eq!(items[0].actions[0].id, "open");
+
+    fake.send(Event::Query {
+        view: "main".to_string(),
+        query: "abc".to_string(),
+    });
+    let call = fake.next();
+    assert_eq!(call.id, None);
+    let Ok(Request::Update { view }) = call.request else {
+        panic!("Expected an update");
+    };
+    assert!(matches!(view.kind, ViewKind::List { filter: false, .. }));
+
+    fake.send(Event::Action {
+        view: "main".to_string(),
+        item: Some("a".to_string()),
+        action: "open".to_string(),
+    });
+    let Ok(Request::Replace { view }) = fake.next().request else {
+        panic!("Expected a replace");
+    };
+    assert!(matches!(view.kind, ViewKind::Form { inputs } if inputs[0].id == "name"));
+
+    fake.send(Event::Submit {
+        view: "form".to_string(),
+        values: HashMap::from([("name".to_string(), "Milk".to_string())]),
+    });
+    let Ok(Request::Toast { style, message }) = fake.next().request else {
+        panic!("Expected a toast");
+    };
+    assert!(matches!(style, ToastStyle::Success));
+    assert_eq!(message, "Saved");
+    assert!(matches!(fake.next().request, Ok(Request::Pop)));
+
+    fake.send(Event::Closed {
+        view: "form".to_string(),
+    });
+    let call = fake.next();
+    assert_eq!(call.id, Some(json!(3)));
+    assert!(matches!(call.request, Ok(Request::Close)));
+
+    let received = fake.received();
+    assert_eq!(
+        received[1..]
+            .iter()
+            .map(|message| (message["method"].clone(), message["params"].clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (json!("run"), json!({ "command": "main" })),
+            (json!("query"), json!({ "view": "main", "query": "abc" })),
+            (
+                json!("action"),
+                json!({ "view": "main", "item": "a", "action": "open" })
+            ),
+            (
+                json!("submit"),
+                json!({ "view": "form", "values": { "name": "Milk" } })
+            ),
+            (json!("closed"), json!({ "view": "form" })),
+        ]
+    );
+}
+
+#[test]
+fn rejects_unknown_methods_and_invalid_params() {
+    let call = decode(r#"{"jsonrpc":"2.0","id":7,"method":"explode"}"#)
+        .unwrap()
+        .unwrap();
+    assert_eq!(call.id, Some(json!(7)));
+    let Err(err) = call.request else {
+        panic!("Expected an error");
+    };
+    assert_eq!(err.code, protocol::METHOD_NOT_FOUND);
+
+    let call = decode(r#"{"jsonrpc":"2.0","id":8,"method":"push","params":{}}"#)
+        .unwrap()
+        .unwrap();
+    let Err(err) = call.request else {
+        panic!("Expected an error");
+    };
+    assert_eq!(err.code, protocol::INVALID_PARAMS);
+
+    assert!(decode(r#"{"jsonrpc":"2.0","id":1,"result":null}"#)
+        .unwrap()
+        .is_none());
+    assert!(decode("not json").is_err());
+}
@@ -0,0 +1,276 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{collections::HashMap, path::Path};
+
+use gpui::*;
+use log::error;
+
+use crate::{
+    components::{
+        form::{Form, Input, InputKind},
+        list::{Accessory, Item, ItemBuilder, ListBuilder, ListItem},
+        shared::{Icon, Img},
+    },
+    query::TextEvent,
+    state::{Action, Shortcut, StateViewBuilder, StateViewContext},
+    theme::Theme,
+};
+
+use super::{
+    icon,
+    protocol::{self, Event, ViewKind},
+    Extensions,
+};
+
+fn shortcut(keystroke: &str) -> Option<Shortcut> {
+    Keystroke::parse(keystroke)
+        .ok()
+        .map(|keystroke| Shortcut::from(&keystroke))
+}
+
+fn required(value: &str) -> Option<&str> {
+    value.is_empty().then_some("Required")
+}
+
+fn action(
+    extension: &str,
+    view: &str,
+    item: Option<&str>,
+    dir: &Path,
+    action: &protocol::Action,
+) -> Action {
+    let extension = extension.to_string();
+    let event = Event::Action {
+        view: view.to_string(),
+        item: item.map(|item| item.to_string()),
+        action: action.id.clone(),
+    };
+    Action::new(
+        icon(&action.icon, dir).unwrap_or(Img::default().icon(Icon::ArrowRight)),
+        &action.title,
+        action.shortcut.as_deref().and_then(shortcut),
+        move |actions, cx| {
+            let event = event.clone();
+            if let Err(err) = Extensions::send(&extension, event, cx) {
+                error!("Failed to send action to extension {}: {}", extension, err);
+                actions.toast.error("Extension is not responding", cx);
+            }
+        },
+        false,
+    )
+}
+
+/// The view's own actions, which are available regardless of the selected item.
+pub(super) fn actions(
+    extension: &str,
+    view: &str,
+    actions: &[protocol::Action],
+    cx: &AppContext,
+) -> Vec<Action> {
+    let dir = Extensions::dir(extension, cx);
+    actions
+        .iter()
+        .map(|a| action(extension, view, None, &dir, a))
+        .collect()
+}
+
+fn item(extension: &str, view: &str, dir: &Path, item: &protocol::Item) -> Item {
+    let accessories = item
+        .accessories
+        .iter()
+        .filter_map(|accessory| match accessory {
+            protocol::Accessory::Tag { tag, icon: img } => {
+                Some(Accessory::new(tag, icon(img, dir)))
+            }
+            protocol::Accessory::Shortcut { shortcut: s } => shortcut(s).map(Accessory::shortcut),
+        })
+        .collect();
+    let mut keywords = vec![item.title.clone()];
+    keywords.extend(item.keywords.clone());
+    ItemBuilder::new(
+        item.id.clone(),
+        ListItem::new(
+            icon(&item.icon, dir),
+            &item.title,
+            item.subtitle.clone(),
+            accessories,
+        ),
+    )
+    .keywords(keywords)
+    .actions(
+        item.actions
+            .iter()
+            .map(|a| action(extension, view, Some(&item.id), dir, a))
+            .collect(),
+    )
+    .build()
+}
+
+/// Static text view.
+struct ExtensionDetail {
+    view: Model<protocol::View>,
+}
+
+impl Render for ExtensionDetail {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let text = match &self.view.read(cx).kind {
+            ViewKind::Detail { text } => text.clone(),
+            _ => String::new(),
+        };
+        div()
+            .id("extension-detail")
+            .size_full()
+            .overflow_y_scroll()
+            .p_2()
+            .text_color(theme.text)
+            .child(text)
+    }
+}
+
+#[derive(Clone)]
+pub struct ExtensionViewBuilder {
+    pub extension: String,
+    pub view: protocol::View,
+}
+
+impl StateViewBuilder for ExtensionViewBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        if let Some(placeholder) = &self.view.placeholder {
+            context.query.set_placeholder(placeholder, cx);
+        }
+        let extension = self.extension.clone();
+        let dir = Extensions::dir(&extension, cx);
+        let id = self.view.id.clone();
+        // Updates from the extension replace the contents of this model
+        let model = cx.new_model(|_| self.view.clone());
+        Extensions::open(&extension, &model, &context.actions, cx);
+        context
+            .actions
+            .update_global(actions(&extension, &id, &self.view.actions, cx), cx);
+        match &self.view.kind {
+            ViewKind::List { filter, .. } => {
+                if let Some(query) = context.query.view.upgrade() {
+                    let extension = extension.clone();
+                    let id = id.clone();
+                    cx.subscribe(&query, move |_, event, cx| {
+                        if let TextEvent::Input { text } = event {
+                            let event = Event::Query {
+                                view: id.clone(),
+                                query: text.clone(),
+                            };
+                            if let Err(err) = Extensions::send(&extension, event, cx) {
+                                error!("Failed to send query to extension {}: {}", extension, err);
+                            }
+                        }
+                    })
+                    .detach();
+                }
+                let mut list = ListBuilder::new();
+                if !filter {
+                    list = list.filter(|this, _| this.items_all.clone());
+                }
+                list.build(
+                    move |_, _, cx| {
+                        let view = model.read(cx);
+                        let ViewKind::List { items, .. } = &view.kind else {
+                            return Ok(Some(vec![]));
+                        };
+                        Ok(Some(
+                            items
+                                .iter()
+                                .map(|i| item(&extension, &view.id, &dir, i))
+                                .collect(),
+                        ))
+                    },
+                    context,
+                    cx,
+                )
+                .into()
+            }
+            ViewKind::Form { inputs } => {
+                let fields = inputs
+                    .iter()
+                    .map(|input| {
+                        let kind = match &input.kind {
+                            protocol::InputKind::Text {
+                                placeholder,
+                                value,
+                                required: r,
+                            }
+                            | protocol::InputKind::Password {
+                                placeholder,
+                                value,
+                                required: r,
+                            } => InputKind::TextField {
+                                placeholder: placeholder.clone(),
+                                value: value.clone(),
+                                password: matches!(
+                                    input.kind,
+                                    protocol::InputKind::Password { .. }
+                                ),
+                                validate: if *r { Some(required) } else { None },
+                            },
+                            protocol::InputKind::Shortcut { value } => {
+                                let value = value.as_deref().and_then(shortcut);
+                                InputKind::Shortcut {
+                                    tmp: value.clone(),
+                                    value,
+                                }
+                            }
+                        };
+                        Input::new(&input.id, &input.label, kind, cx)
+                    })
+                    .collect();
+                Form::new(
+                    fields,
+                    move |values, actions, cx| {
+                        let view = model.read(cx).clone();
+                        let ViewKind::Form { inputs } = &view.kind else {
+                            return;
+                        };
+                        let values: HashMap<String, String> = inputs
+                            .iter()
+                            .map(|input| {
+                                let value = match input.kind {
+                                    protocol::InputKind::Shortcut { .. } => values[&input.id]
+                                        .value::<Option<Shortcut>>()
+                                        .map(|shortcut| shortcut.to_string())
+                                        .unwrap_or_default(),
+                                    _ => values[&input.id].value::<String>(),
+                                };
+                                (input.id.clone(), value)
+                            })
+                            .collect();
+                        let event = Event::Submit {
+                            view: view.id.clone(),
+                            values,
+                        };
+                        if let Err(err) = Extensions::send(&extension, event, cx) {
+                            error!("Failed to submit form to extension {}: {}", extension, err);
+                            actions.toast.error("Extension is not responding", cx);
+                        }
+                    },
+                    context,
+                    cx,
+                )
+                .into()
+            }
+            ViewKind::Detail { .. } => cx
+                .new_view(|cx| {
+                    cx.observe(&model, |_, _, cx| cx.notify()).detach();
+                    ExtensionDetail { view: model }
+                })
+                .into(),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::sync::OnceLock;
+
+use bonsaidb::{
+    core::schema::{Collection, SerializedCollection},
+    local::Database,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::db::Db;
+
+/// History ID of the root list, which isn't opened by a command.
+pub const ROOT: &str = "root";
+static MAX_QUERIES: usize = 50;
+
+#[derive(Clone, Serialize, Deserialize, Collection)]
+#[collection(name = "query-history")]
+struct CommandHistory {
+    // ID of the root command
+    #[natural_id]
+    id: String,
+    // Most recent first
+    queries: Vec<String>,
+    // Unix timestamp of the last time the command was opened
+    opened: i64,
+}
+
+fn db() -> &'static Database {
+    static DB: OnceLock<Database> = OnceLock::new();
+    DB.get_or_init(Db::init_collection::<CommandHistory>)
+}
+
+/// Queries typed into the views of each command, so they survive the state being reset.
+pub struct History;
+
+impl History {
+    fn load(id: &str) -> anyhow::Result<CommandHistory> {
+        Ok(CommandHistory::get(&id.to_string(), db())?
+            .map(|doc| doc.contents)
+            .unwrap_or_else(|| CommandHistory {
+                id: id.to_string(),
+                queries: vec![],
+                opened: 0,
+            }))
+    }
+    fn save(history: CommandHistory) -> anyhow::Result<()> {
+        let id = history.id.clone();
+        history.overwrite_into(&id, db())?;
+        Ok(())
+    }
+    /// The queries of a command, most recent first.
+    pub fn queries(id: &str) -> Vec<String> {
+        Self::load(id)
+            .map(|history| history.queries)
+            .unwrap_or_default()
+    }
+    pub fn record(id: &str, query: &str) -> anyhow::Result<()> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(());
+        }
+        let mut history = Self::load(id)?;
+        history.queries.retain(|q| q != query);
+        history.queries.insert(0, query.to_string());
+        history.queries.truncate(MAX_QUERIES);
+        Self::save(history)
+    }
+    pub fn opened(id: &str) -> anyhow::Result<()> {
+        let mut history = Self::load(id)?;
+        history.opened = OffsetDateTime::now_utc().unix_timestamp();
+        Self::save(history)
+    }
+    /// The command that was opened last along with its last query.
+    pub fn last() -> anyhow::Result<Option<(String, Option<String>)>> {
+        Ok(CommandHistory::all(db())
+            .query()?
+            .into_iter()
+            .map(|doc| doc.contents)
+            .filter(|history| history.id != ROOT && history.opened > 0)
+            .max_by_key(|history| history.opened)
+            .map(|history| (history.id, history.queries.into_iter().next())))
+    }
+}
+
+/// Steps through the history of a command from its query input.
+pub struct Recall {
+    id: String,
+    queries: Vec<String>,
+    index: Option<usize>,
+    // What is being searched for with ctrl+r
+    search: Option<String>,
+}
+
+impl Recall {
+    pub fn new(id: impl ToString) -> Self {
+        Self {
+            id: id.to_string(),
+            queries: vec![],
+            index: None,
+            search: None,
+        }
+    }
+    // Editing a recalled query ends the recall
+    fn sync(&mut self, text: &str) {
+        if let Some(index) = self.index {
+            if self.queries.get(index).map(String::as_str) != Some(text) {
+                self.index = None;
+            }
+        }
+        if self.index.is_none() {
+            self.search = None;
+        }
+    }
+    // The history is only read when a recall starts, it can't change while stepping through it
+    fn start(&mut self) {
+        if self.index.is_none() {
+            self.queries = History::queries(&self.id);
+        }
+    }
+    /// Returns the older query, only starting when the input is empty.
+    pub fn older(&mut self, text: &str) -> Option<String> {
+        self.sync(text);
+        if self.index.is_none() && !text.is_empty() {
+            return None;
+        }
+        self.start();
+        let next = self.index.map_or(0, |i| i + 1);
+        let query = self.queries.get(next)?.clone();
+        self.index = Some(next);
+        Some(query)
+    }
+    /// Returns the newer query, or an empty one after the most recent.
+    pub fn newer(&mut self, text: &str) -> Option<String> {
+        self.sync(text);
+        let index = self.index?;
+        if index == 0 {
+            self.index = None;
+            return Some(String::new());
+        }
+        self.index = Some(index - 1);
+        self.queries.get(index - 1).cloned()
+    }
+    /// Returns the next older query containing what was typed before the search started.
+    pub fn search(&mut self, text: &str) -> Option<String> {
+        self.sync(text);
+        self.start();
+        let search = self
+            .search
+            .get_or_insert_with(|| text.to_lowercase())
+            .clone();
+        let start = self.index.map_or(0, |i| i + 1);
+        let (index, query) = self
+            .queries
+            .iter()
+            .enumerate()
+            .skip(start)
+            .find(|(_, query)| query.to_lowercase().contains(&search))?;
+        self.index = Some(index);
+        Some(query.clone())
+    }
+}
@@ -0,0 +1,370 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{fmt, str::FromStr};
+
+use anyhow::anyhow;
+use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+use gpui::Keystroke;
+
+// Key names as gpui reports them, along with the physical key they are on and its xkb keysym
+static KEYS: &[(&str, Code, &str)] = &[
+    ("a", Code::KeyA, "a"),
+    ("b", Code::KeyB, "b"),
+    ("c", Code::KeyC, "c"),
+    ("d", Code::KeyD, "d"),
+    ("e", Code::KeyE, "e"),
+    ("f", Code::KeyF, "f"),
+    ("g", Code::KeyG, "g"),
+    ("h", Code::KeyH, "h"),
+    ("i", Code::KeyI, "i"),
+    ("j", Code::KeyJ, "j"),
+    ("k", Code::KeyK, "k"),
+    ("l", Code::KeyL, "l"),
+    ("m", Code::KeyM, "m"),
+    ("n", Code::KeyN, "n"),
+    ("o", Code::KeyO, "o"),
+    ("p", Code::KeyP, "p"),
+    ("q", Code::KeyQ, "q"),
+    ("r", Code::KeyR, "r"),
+    ("s", Code::KeyS, "s"),
+    ("t", Code::KeyT, "t"),
+    ("u", Code::KeyU, "u"),
+    ("v", Code::KeyV, "v"),
+    ("w", Code::KeyW, "w"),
+    ("x", Code::KeyX, "x"),
+    ("y", Code::KeyY, "y"),
+    ("z", Code::KeyZ, "z"),
+    ("0", Code::Digit0, "0"),
+    ("1", Code::Digit1, "1"),
+    ("2", Code::Digit2, "2"),
+    ("3", Code::Digit3, "3"),
+    ("4", Code::Digit4, "4"),
+    ("5", Code::Digit5, "5"),
+    ("6", Code::Digit6, "6"),
+    ("7", Code::Digit7, "7"),
+    ("8", Code::Digit8, "8"),
+    ("9", Code::Digit9, "9"),
+    ("`", Code::Backquote, "grave"),
+    ("-", Code::Minus, "minus"),
+    ("=", Code::Equal, "equal"),
+    ("[", Code::BracketLeft, "bracketleft"),
+    ("]", Code::BracketRight, "bracketright"),
+    ("\\", Code::Backslash, "backslash"),
+    (";", Code::Semicolon, "semicolon"),
+    ("'", Code::Quote, "apostrophe"),
+    (",", Code::Comma, "comma"),
+    (".", Code::Period, "period"),
+    ("/", Code::Slash, "slash"),
+    ("space", Code::Space, "space"),
+    ("enter", Code::Enter, "Return"),
+    ("tab", Code::Tab, "Tab"),
+    ("escape", Code::Escape, "Escape"),
+    ("backspace", Code::Backspace, "BackSpace"),
+    ("delete", Code::Delete, "Delete"),
+    ("insert", Code::Insert, "Insert"),
+    ("home", Code::Home, "Home"),
+    ("end", Code::End, "End"),
+    ("pageup", Code::PageUp, "Page_Up"),
+    ("pagedown", Code::PageDown, "Page_Down"),
+    ("up", Code::ArrowUp, "Up"),
+    ("down", Code::ArrowDown, "Down"),
+    ("left", Code::ArrowLeft, "Left"),
+    ("right", Code::ArrowRight, "Right"),
+    ("capslock", Code::CapsLock, "Caps_Lock"),
+    ("printscreen", Code::PrintScreen, "Print"),
+    ("scrolllock", Code::ScrollLock, "Scroll_Lock"),
+    ("pause", Code::Pause, "Pause"),
+    ("f1", Code::F1, "F1"),
+    ("f2", Code::F2, "F2"),
+    ("f3", Code::F3, "F3"),
+    ("f4", Code::F4, "F4"),
+    ("f5", Code::F5, "F5"),
+    ("f6", Code::F6, "F6"),
+    ("f7", Code::F7, "F7"),
+    ("f8", Code::F8, "F8"),
+    ("f9", Code::F9, "F9"),
+    ("f10", Code::F10, "F10"),
+    ("f11", Code::F11, "F11"),
+    ("f12", Code::F12, "F12"),
+    ("f13", Code::F13, "F13"),
+    ("f14", Code::F14, "F14"),
+    ("f15", Code::F15, "F15"),
+    ("f16", Code::F16, "F16"),
+    ("f17", Code::F17, "F17"),
+    ("f18", Code::F18, "F18"),
+    ("f19", Code::F19, "F19"),
+    ("f20", Code::F20, "F20"),
+    ("f21", Code::F21, "F21"),
+    ("f22", Code::F22, "F22"),
+    ("f23", Code::F23, "F23"),
+    ("f24", Code::F24, "F24"),
+    ("numlock", Code::NumLock, "Num_Lock"),
+    ("numpad0", Code::Numpad0, "KP_0"),
+    ("numpad1", Code::Numpad1, "KP_1"),
+    ("numpad2", Code::Numpad2, "KP_2"),
+    ("numpad3", Code::Numpad3, "KP_3"),
+    ("numpad4", Code::Numpad4, "KP_4"),
+    ("numpad5", Code::Numpad5, "KP_5"),
+    ("numpad6", Code::Numpad6, "KP_6"),
+    ("numpad7", Code::Numpad7, "KP_7"),
+    ("numpad8", Code::Numpad8, "KP_8"),
+    ("numpad9", Code::Numpad9, "KP_9"),
+    ("numpadadd", Code::NumpadAdd, "KP_Add"),
+    ("numpadsubtract", Code::NumpadSubtract, "KP_Subtract"),
+    ("numpadmultiply", Code::NumpadMultiply, "KP_Multiply"),
+    ("numpaddivide", Code::NumpadDivide, "KP_Divide"),
+    ("numpaddecimal", Code::NumpadDecimal, "KP_Decimal"),
+    ("numpadenter", Code::NumpadEnter, "KP_Enter"),
+    ("numpadequal", Code::NumpadEqual, "KP_Equal"),
+    ("volumeup", Code::AudioVolumeUp, "XF86AudioRaiseVolume"),
+    ("volumedown", Code::AudioVolumeDown, "XF86AudioLowerVolume"),
+    ("mute", Code::AudioVolumeMute, "XF86AudioMute"),
+    ("playpause", Code::MediaPlayPause, "XF86AudioPlay"),
+    ("stop", Code::MediaStop, "XF86AudioStop"),
+    ("nexttrack", Code::MediaTrackNext, "XF86AudioNext"),
+    ("previoustrack", Code::MediaTrackPrevious, "XF86AudioPrev"),
+];
+
+// Characters typed with shift on a US layout, which gpui reports instead of the key
+static SHIFTED: &[(&str, Code)] = &[
+    ("~", Code::Backquote),
+    ("!", Code::Digit1),
+    ("@", Code::Digit2),
+    ("#", Code::Digit3),
+    ("$", Code::Digit4),
+    ("%", Code::Digit5),
+    ("^", Code::Digit6),
+    ("&", Code::Digit7),
+    ("*", Code::Digit8),
+    ("(", Code::Digit9),
+    (")", Code::Digit0),
+    ("_", Code::Minus),
+    ("+", Code::Equal),
+    ("{", Code::BracketLeft),
+    ("}", Code::BracketRight),
+    ("|", Code::Backslash),
+    (":", Code::Semicolon),
+    ("\"", Code::Quote),
+    ("<", Code::Comma),
+    (">", Code::Period),
+    ("?", Code::Slash),
+];
+
+fn lookup(key: &str) -> Option<Code> {
+    KEYS.iter()
+        .find(|(name, _, _)| *name == key)
+        .map(|(_, code, _)| *code)
+}
+
+/// A global hotkey, which is a physical key with modifiers, unlike a gpui keystroke which is the typed key.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Binding {
+    modifiers: Modifiers,
+    code: Code,
+}
+
+impl TryFrom<&Keystroke> for Binding {
+    type Error = anyhow::Error;
+
+    fn try_from(keystroke: &Keystroke) -> anyhow::Result<Self> {
+        let key = keystroke.key.as_str();
+        let (code, shifted) = if let Some(code) = lookup(key) {
+            (code, false)
+        } else if let Some((_, code)) = SHIFTED.iter().find(|(name, _)| *name == key) {
+            (*code, true)
+        } else if key.len() == 1 && key.chars().all(|c| c.is_ascii_uppercase()) {
+            (lookup(&key.to_lowercase()).unwrap(), true)
+        } else {
+            return Err(anyhow!("Unsupported key {}", key));
+        };
+        let mut modifiers = Modifiers::empty();
+        modifiers.set(Modifiers::CONTROL, keystroke.modifiers.control);
+        modifiers.set(Modifiers::ALT, keystroke.modifiers.alt);
+        modifiers.set(Modifiers::SHIFT, keystroke.modifiers.shift || shifted);
+        modifiers.set(Modifiers::SUPER, keystroke.modifiers.command);
+        Ok(Self { modifiers, code })
+    }
+}
+
+impl From<&Binding> for Keystroke {
+    fn from(binding: &Binding) -> Self {
+        let key = KEYS
+            .iter()
+            .find(|(_, code, _)| *code == binding.code)
+            .map(|(name, _, _)| name.to_string())
+            .unwrap_or_else(|| binding.code.to_string());
+        Keystroke {
+            modifiers: gpui::Modifiers {
+                control: binding.modifiers.contains(Modifiers::CONTROL),
+                alt: binding.modifiers.contains(Modifiers::ALT),
+                shift: binding.modifiers.contains(Modifiers::SHIFT),
+                command: binding.modifiers.contains(Modifiers::SUPER),
+                function: false,
+            },
+            key,
+            ime_key: None,
+        }
+    }
+}
+
+impl From<&Binding> for HotKey {
+    fn from(binding: &Binding) -> Self {
+        HotKey::new(Some(binding.modifiers), binding.code)
+    }
+}
+
+// Same format as global_hotkey parses, e.g. `control+alt+super+Space`
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (Modifiers::CONTROL, "control"),
+            (Modifiers::ALT, "alt"),
+            (Modifiers::SHIFT, "shift"),
+            (Modifiers::SUPER, "super"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", self.code)
+    }
+}
+
+impl FromStr for Binding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut modifiers = Modifiers::empty();
+        let mut tokens = s.split('+').collect::<Vec<_>>();
+        let key = tokens.pop().unwrap_or_default();
+        for token in tokens {
+            match token.to_lowercase().as_str() {
+                "control" | "ctrl" => modifiers.insert(Modifiers::CONTROL),
+                "alt" | "option" => modifiers.insert(Modifiers::ALT),
+                "shift" => modifiers.insert(Modifiers::SHIFT),
+                "super" | "command" | "cmd" | "meta" => modifiers.insert(Modifiers::SUPER),
+                _ => return Err(anyhow!("Unknown modifier {}", token)),
+            }
+        }
+        // Hotkeys used to be stored with gpui key names
+        let code = Code::from_str(key)
+            .ok()
+            .filter(|code| *code != Code::Unidentified)
+            .or_else(|| lookup(&key.to_lowercase()))
+            .ok_or_else(|| anyhow!("Unknown key {}", key))?;
+        Ok(Self { modifiers, code })
+    }
+}
+
+impl Binding {
+    /// The binding as a trigger of the XDG shortcuts spec, e.g. `CTRL+ALT+LOGO+space`.
+    pub fn trigger(&self) -> String {
+        let mut tokens = vec![];
+        for (modifier, name) in [
+            (Modifiers::CONTROL, "CTRL"),
+            (Modifiers::ALT, "ALT"),
+            (Modifiers::SHIFT, "SHIFT"),
+            (Modifiers::SUPER, "LOGO"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                tokens.push(name.to_string());
+            }
+        }
+        tokens.push(
+            KEYS.iter()
+                .find(|(_, code, _)| *code == self.code)
+                .map(|(_, _, keysym)| keysym.to_string())
+                .unwrap_or_else(|| self.code.to_string()),
+        );
+        tokens.join("+")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keystroke(key: &str, control: bool, alt: bool, shift: bool, command: bool) -> Keystroke {
+        Keystroke {
+            modifiers: gpui::Modifiers {
+                control,
+                alt,
+                shift,
+                command,
+                function: false,
+            },
+            key: key.to_string(),
+            ime_key: None,
+        }
+    }
+
+    #[test]
+    fn keystroke_round_trip() {
+        for keystroke in [
+            keystroke("space", true, true, false, true),
+            keystroke("a", true, false, true, false),
+            keystroke(";", false, false, false, true),
+            keystroke("f5", false, false, false, false),
+            keystroke("numpadenter", false, true, false, false),
+        ] {
+            let binding = Binding::try_from(&keystroke).unwrap();
+            assert_eq!(Keystroke::from(&binding), keystroke);
+        }
+    }
+
+    #[test]
+    fn shifted_characters() {
+        let binding = Binding::try_from(&keystroke("!", false, false, false, true)).unwrap();
+        assert_eq!(binding.to_string(), "shift+super+Digit1");
+        assert_eq!(
+            Keystroke::from(&binding),
+            keystroke("1", false, false, true, true)
+        );
+        let binding = Binding::try_from(&keystroke("A", true, false, false, false)).unwrap();
+        assert_eq!(binding.to_string(), "control+shift+KeyA");
+    }
+
+    #[test]
+    fn display_and_from_str() {
+        let binding = Binding::try_from(&keystroke("space", true, true, true, true)).unwrap();
+        assert_eq!(binding.to_string(), "control+alt+shift+super+Space");
+        assert_eq!(
+            Binding::from_str("control+alt+shift+super+Space").unwrap(),
+            binding
+        );
+        // Stored with gpui key names and modifier aliases
+        assert_eq!(
+            Binding::from_str("ctrl+cmd+k").unwrap(),
+            Binding::from_str("control+super+KeyK").unwrap()
+        );
+        assert_eq!(Binding::from_str("Escape").unwrap().to_string(), "Escape");
+    }
+
+    #[test]
+    fn rejects_unsupported_keys() {
+        assert!(Binding::try_from(&keystroke("hyper", true, false, false, false)).is_err());
+        assert!(Binding::try_from(&keystroke("é", false, false, false, false)).is_err());
+        assert!(Binding::from_str("control+nokey").is_err());
+        assert!(Binding::from_str("hyper+a").is_err());
+        assert!(Binding::from_str("").is_err());
+    }
+
+    #[test]
+    fn trigger() {
+        let binding = Binding::from_str("control+alt+super+Space").unwrap();
+        assert_eq!(binding.trigger(), "CTRL+ALT+LOGO+space");
+        let binding = Binding::from_str("shift+Enter").unwrap();
+        assert_eq!(binding.trigger(), "SHIFT+Return");
+        let binding = Binding::from_str("super+Semicolon").unwrap();
+        assert_eq!(binding.trigger(), "LOGO+semicolon");
+    }
+}
@@ -0,0 +1,111 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::time::Duration;
+
+use async_std::task::sleep;
+use crossbeam_channel::Sender;
+use gpui::*;
+
+use crate::{state::Shortcut, theme::Theme, window::WindowStyle};
+
+/// A key that can follow the leader hotkey.
+#[derive(Clone)]
+pub struct Sequence {
+    pub key: String,
+    pub name: String,
+    // Sent to the hotkey event loop like the ID of a regular hotkey
+    pub id: u32,
+}
+
+/// Lists the keys that can follow the leader, and waits for one of them until it times out.
+pub struct LeaderOverlay {
+    focus_handle: FocusHandle,
+    sequences: Vec<Sequence>,
+    sender: Sender<u32>,
+}
+
+impl LeaderOverlay {
+    pub fn open(
+        sequences: Vec<Sequence>,
+        sender: Sender<u32>,
+        timeout: Duration,
+        cx: &mut WindowContext,
+    ) {
+        let mut options = WindowStyle::Toast {
+            width: 320.0,
+            height: 24.0 + 36.0 * sequences.len().max(1) as f64,
+        }
+        .options(cx);
+        // Unlike other toasts this one has to receive the next key
+        options.focus = true;
+        cx.open_window(options, |cx| {
+            cx.spawn(|mut cx| async move {
+                sleep(timeout).await;
+                let _ = cx.update_window(cx.window_handle(), |_, cx| {
+                    cx.remove_window();
+                });
+            })
+            .detach();
+            cx.new_view(|cx| Self {
+                focus_handle: cx.focus_handle(),
+                sequences,
+                sender,
+            })
+        });
+    }
+}
+
+impl Render for LeaderOverlay {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        cx.focus(&self.focus_handle);
+        let theme = cx.global::<Theme>();
+        let mut rows = div().flex().flex_col().w_full();
+        if self.sequences.is_empty() {
+            rows = rows.child(
+                div()
+                    .h_9()
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .text_color(theme.subtext0)
+                    .child("No leader keys set"),
+            );
+        }
+        for sequence in &self.sequences {
+            rows = rows.child(
+                div()
+                    .h_9()
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .child(sequence.name.clone())
+                    .child(Shortcut::new(&sequence.key)),
+            );
+        }
+        div()
+            .track_focus(&self.focus_handle)
+            // Any other key cancels the sequence
+            .on_key_down(cx.listener(|this, ev: &KeyDownEvent, cx| {
+                let key = ev.keystroke.key.as_str();
+                if let Some(sequence) = this.sequences.iter().find(|s| s.key == key) {
+                    let _ = this.sender.send(sequence.id);
+                }
+                cx.remove_window();
+            }))
+            .bg(theme.base)
+            .text_color(theme.text)
+            .size_full()
+            .px_4()
+            .py_3()
+            .child(rows)
+    }
+}
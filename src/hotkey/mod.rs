@@ -0,0 +1,438 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{collections::HashMap, str::FromStr, sync::OnceLock, time::Duration};
+
+use anyhow::anyhow;
+use async_std::task::sleep;
+use bonsaidb::{
+    core::schema::{Collection, SerializedCollection},
+    local::Database,
+};
+use crossbeam_channel::{unbounded, Sender};
+use global_hotkey::{hotkey::HotKey, GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use gpui::*;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commands::{root::apps::ApplicationIndex, RootCommands},
+    components::list::item_id,
+    db::Db,
+    settings::Settings,
+    state::{Actions, CloneableFn, StateModel},
+    window::Window,
+};
+
+mod keys;
+mod leader;
+#[cfg(target_os = "linux")]
+mod portal;
+
+use keys::Binding;
+use leader::{LeaderOverlay, Sequence};
+
+#[derive(Clone)]
+struct Target {
+    name: String,
+    action: Box<dyn CloneableFn>,
+    // Commands usually show a view, while apps are launched without opening the window
+    open: bool,
+}
+
+enum Backend {
+    GlobalHotKey(GlobalHotKeyManager),
+    // global-hotkey grabs keys through X11, which Wayland compositors don't allow
+    #[cfg(target_os = "linux")]
+    Portal(portal::Portal),
+}
+
+impl Backend {
+    // Both backends report the IDs of triggered hotkeys to `sender`
+    fn new(sender: Sender<u32>) -> Self {
+        #[cfg(target_os = "linux")]
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            match async_std::task::block_on(portal::Portal::new(sender.clone())) {
+                Ok(portal) => return Self::Portal(portal),
+                Err(err) => error!(
+                    "GlobalShortcuts portal unavailable, falling back to X11: {}",
+                    err
+                ),
+            }
+        }
+        GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+            if event.state == HotKeyState::Released {
+                let _ = sender.send(event.id);
+            }
+        }));
+        Self::GlobalHotKey(GlobalHotKeyManager::new().unwrap())
+    }
+}
+
+pub struct HotkeyManager {
+    backend: Backend,
+    // Bound hotkeys along with what they do, which the portal shows to the user
+    hotkeys: Vec<(Binding, String)>,
+    map: HashMap<u32, Target>,
+    // ID of the leader hotkey and the keys that can follow it
+    leader: Option<u32>,
+    sequences: Vec<Sequence>,
+    sender: Sender<u32>,
+}
+
+impl Global for HotkeyManager {}
+
+fn db() -> &'static Database {
+    static DB: OnceLock<Database> = OnceLock::new();
+    DB.get_or_init(Db::init_collection::<CommandHotkeys>)
+}
+
+fn leader_db() -> &'static Database {
+    static DB: OnceLock<Database> = OnceLock::new();
+    DB.get_or_init(Db::init_collection::<CommandLeaderKeys>)
+}
+
+// Sequences are dispatched like hotkeys, so they need an ID that doesn't clash with the hotkey IDs
+fn sequence_id(id: &str) -> u32 {
+    item_id(format!("leader-{}", id)) as u32
+}
+
+/// ID to check the leader hotkey for conflicts with.
+pub const LEADER: &str = "leader";
+
+// Falls back to the default when the configured hotkey can't be used globally
+fn main_binding(settings: &Settings) -> Binding {
+    Keystroke::parse(&settings.hotkey)
+        .and_then(|keystroke| Binding::try_from(&keystroke))
+        .unwrap_or_else(|err| {
+            error!("Invalid hotkey {}: {}", settings.hotkey, err);
+            let keystroke = Keystroke::parse(&Settings::default().hotkey).unwrap();
+            Binding::try_from(&keystroke).unwrap()
+        })
+}
+
+impl HotkeyManager {
+    pub fn init(cx: &mut WindowContext) {
+        let (sender, receiver) = unbounded();
+
+        cx.set_global::<HotkeyManager>(HotkeyManager {
+            backend: Backend::new(sender.clone()),
+            hotkeys: vec![],
+            map: HashMap::new(),
+            leader: None,
+            sequences: vec![],
+            sender,
+        });
+
+        Self::update(cx);
+        cx.observe_global::<Settings>(Self::update).detach();
+        // Apps are indexed in the background, so their hotkeys can only be bound once they are known
+        cx.observe(&ApplicationIndex::view(cx), |_, cx| Self::update(cx))
+            .detach();
+        cx.spawn(|mut cx| async move {
+            loop {
+                if let Ok(id) = receiver.try_recv() {
+                    let _ = cx.update_global::<HotkeyManager, _>(|manager, cx| {
+                        if manager.leader == Some(id) {
+                            let timeout =
+                                Duration::from_secs(cx.global::<Settings>().leader_timeout);
+                            LeaderOverlay::open(
+                                manager.sequences.clone(),
+                                manager.sender.clone(),
+                                timeout,
+                                cx,
+                            );
+                            return;
+                        }
+                        match manager.map.get(&id) {
+                            Some(target) if !target.open => {
+                                (target.action)(&mut Actions::default(cx), cx);
+                            }
+                            Some(target) => {
+                                StateModel::update(|this, cx| this.reset(cx), cx);
+                                (target.action)(&mut Actions::default(cx), cx);
+                                Window::open(cx);
+                            }
+                            None => Window::open(cx),
+                        }
+                    });
+                }
+                sleep(Duration::from_millis(50)).await;
+                // cx.background_executor()
+                //     .timer(Duration::from_millis(50))
+                //     .await;
+            }
+        })
+        .detach();
+    }
+    pub fn update(cx: &mut WindowContext) {
+        // Any root list item with a stable ID can have a hotkey, which runs its default action
+        let mut targets = HashMap::<u64, Target>::new();
+        for item in ApplicationIndex::view(cx).read(cx).items.values().flatten() {
+            if let Some(action) = item.get_actions().first() {
+                targets.insert(
+                    item.get_id(),
+                    Target {
+                        name: item.get_keywords().first().cloned().unwrap_or_default(),
+                        action: action.action.clone(),
+                        open: false,
+                    },
+                );
+            }
+        }
+        for (id, command) in &cx.global::<RootCommands>().commands {
+            targets.insert(
+                item_id(id),
+                Target {
+                    name: command.title.clone(),
+                    action: command.action.clone(),
+                    open: true,
+                },
+            );
+        }
+        cx.update_global::<HotkeyManager, _>(|manager, cx| {
+            let settings = cx.global::<Settings>();
+            let hotkeys = CommandHotkeys::all(db()).query().unwrap_or_default();
+            let previous = std::mem::take(&mut manager.hotkeys);
+            manager.map.clear();
+            manager.leader = None;
+            manager.sequences.clear();
+            // The main hotkey only opens the window
+            manager
+                .hotkeys
+                .push((main_binding(settings), "Open Loungy".to_string()));
+            for hotkey in hotkeys {
+                let hotkey = hotkey.contents;
+                if !settings.enabled(&hotkey.id) {
+                    continue;
+                }
+                let binding = match Binding::from_str(&hotkey.hotkey) {
+                    Ok(binding) => binding,
+                    Err(err) => {
+                        error!(
+                            "Invalid hotkey {} for {}: {}",
+                            hotkey.hotkey, hotkey.id, err
+                        );
+                        continue;
+                    }
+                };
+                if let Some(target) = targets.get(&item_id(&hotkey.id)) {
+                    manager.hotkeys.push((binding, target.name.clone()));
+                    manager
+                        .map
+                        .insert(HotKey::from(&binding).id(), target.clone());
+                }
+            }
+            if let Some(leader) = &settings.leader {
+                match Keystroke::parse(leader).and_then(|keystroke| Binding::try_from(&keystroke)) {
+                    Ok(binding) => {
+                        manager
+                            .hotkeys
+                            .push((binding, "Show leader keys".to_string()));
+                        manager.leader = Some(HotKey::from(&binding).id());
+                    }
+                    Err(err) => error!("Invalid leader hotkey {}: {}", leader, err),
+                }
+            }
+            for sequence in CommandLeaderKeys::all(leader_db())
+                .query()
+                .unwrap_or_default()
+            {
+                let sequence = sequence.contents;
+                if !settings.enabled(&sequence.id) {
+                    continue;
+                }
+                if let Some(target) = targets.get(&item_id(&sequence.id)) {
+                    let id = sequence_id(&sequence.id);
+                    manager.sequences.push(Sequence {
+                        key: sequence.key,
+                        name: target.name.clone(),
+                        id,
+                    });
+                    manager.map.insert(id, target.clone());
+                }
+            }
+            manager.sequences.sort_by(|a, b| a.key.cmp(&b.key));
+            // Rebinding through the portal may ask the user again, so only do it when something changed
+            if previous == manager.hotkeys {
+                return;
+            }
+            match &manager.backend {
+                Backend::GlobalHotKey(backend) => {
+                    for (binding, _) in &previous {
+                        let _ = backend.unregister(HotKey::from(binding));
+                    }
+                    // Registering one by one, so a single grabbed key doesn't take the others down with it
+                    for (binding, _) in &manager.hotkeys {
+                        if let Err(err) = backend.register(HotKey::from(binding)) {
+                            error!("Failed to register hotkey {}: {}", binding, err);
+                        }
+                    }
+                }
+                #[cfg(target_os = "linux")]
+                Backend::Portal(backend) => {
+                    let backend = backend.clone();
+                    let hotkeys = manager.hotkeys.clone();
+                    cx.background_executor()
+                        .spawn(async move {
+                            if let Err(err) = backend.bind(hotkeys).await {
+                                error!("Failed to bind shortcuts: {}", err);
+                            }
+                        })
+                        .detach();
+                }
+            }
+        });
+    }
+    /// Checks whether a keystroke can be bound for `id`, or as the main hotkey if `id` is `None`.
+    pub fn check(
+        id: Option<&str>,
+        keystroke: &Keystroke,
+        cx: &AppContext,
+    ) -> anyhow::Result<Binding> {
+        let binding = Binding::try_from(keystroke)?;
+        if id.is_some() && binding == main_binding(cx.global::<Settings>()) {
+            return Err(anyhow!("Hotkey already opens Loungy"));
+        }
+        let leader = cx
+            .global::<Settings>()
+            .leader
+            .as_deref()
+            .and_then(|leader| {
+                Keystroke::parse(leader)
+                    .and_then(|keystroke| Binding::try_from(&keystroke))
+                    .ok()
+            });
+        if id != Some(LEADER) && leader == Some(binding) {
+            return Err(anyhow!("Hotkey already starts leader sequences"));
+        }
+        for hotkey in CommandHotkeys::all(db()).query()? {
+            let hotkey = hotkey.contents;
+            if id == Some(hotkey.id.as_str()) {
+                continue;
+            }
+            if Binding::from_str(&hotkey.hotkey).is_ok_and(|other| other == binding) {
+                let name = cx
+                    .global::<RootCommands>()
+                    .commands
+                    .get(&hotkey.id)
+                    .map(|command| command.title.clone())
+                    .unwrap_or(hotkey.id);
+                return Err(anyhow!("Hotkey already used by {}", name));
+            }
+        }
+        // Keys grabbed by another application fail to register, the portal resolves conflicts itself
+        let manager = cx.global::<HotkeyManager>();
+        match &manager.backend {
+            Backend::GlobalHotKey(backend) => {
+                let hotkey = HotKey::from(&binding);
+                if !manager.hotkeys.iter().any(|(other, _)| *other == binding) {
+                    backend
+                        .register(hotkey)
+                        .map_err(|_| anyhow!("Hotkey already used by another application"))?;
+                    let _ = backend.unregister(hotkey);
+                }
+            }
+            #[cfg(target_os = "linux")]
+            Backend::Portal(_) => {}
+        }
+        Ok(binding)
+    }
+    pub fn set(id: &str, keystroke: Keystroke, cx: &mut WindowContext) -> anyhow::Result<()> {
+        let binding = Self::check(Some(id), &keystroke, cx)?;
+
+        CommandHotkeys {
+            id: id.to_string(),
+            hotkey: binding.to_string(),
+        }
+        .overwrite_into(&id.to_string(), db())?;
+        Self::update(cx);
+        Ok(())
+    }
+    pub fn unset(id: &str, cx: &mut WindowContext) -> anyhow::Result<()> {
+        if let Some(hk) = CommandHotkeys::get(&id.to_string(), db())? {
+            hk.delete(db())?;
+        }
+        Self::update(cx);
+        Ok(())
+    }
+    pub fn get(id: &str) -> Option<Keystroke> {
+        let hotkey = CommandHotkeys::get(&id.to_string(), db()).ok()??;
+        let binding = Binding::from_str(&hotkey.contents.hotkey).ok()?;
+        Some(Keystroke::from(&binding))
+    }
+    /// Hotkeys of every command and app, for lists that show all of them.
+    pub fn get_all() -> HashMap<String, Keystroke> {
+        CommandHotkeys::all(db())
+            .query()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|hotkey| {
+                let hotkey = hotkey.contents;
+                let binding = Binding::from_str(&hotkey.hotkey).ok()?;
+                Some((hotkey.id, Keystroke::from(&binding)))
+            })
+            .collect()
+    }
+    /// Sets the key following the leader hotkey for `id`, an empty key removes it.
+    pub fn set_leader_key(id: &str, key: &str, cx: &mut WindowContext) -> anyhow::Result<()> {
+        let key = key.trim().to_lowercase();
+        if key.is_empty() {
+            if let Some(doc) = CommandLeaderKeys::get(&id.to_string(), leader_db())? {
+                doc.delete(leader_db())?;
+            }
+        } else {
+            for other in CommandLeaderKeys::all(leader_db()).query()? {
+                let other = other.contents;
+                if other.key == key && other.id != id {
+                    return Err(anyhow!("Leader key {} already used by {}", key, other.id));
+                }
+            }
+            CommandLeaderKeys {
+                id: id.to_string(),
+                key,
+            }
+            .overwrite_into(&id.to_string(), leader_db())?;
+        }
+        Self::update(cx);
+        Ok(())
+    }
+    pub fn get_leader_key(id: &str) -> Option<String> {
+        let sequence = CommandLeaderKeys::get(&id.to_string(), leader_db()).ok()??;
+        Some(sequence.contents.key)
+    }
+    pub fn get_all_leader_keys() -> HashMap<String, String> {
+        CommandLeaderKeys::all(leader_db())
+            .query()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|sequence| (sequence.contents.id, sequence.contents.key))
+            .collect()
+    }
+}
+
+/// Hotkeys of root list items, keyed by the ID of the command or app.
+#[derive(Serialize, Deserialize, Collection, Debug)]
+#[collection(name = "command-hotkeys")]
+pub struct CommandHotkeys {
+    #[natural_id]
+    id: String,
+    hotkey: String,
+}
+
+/// Keys following the leader hotkey, keyed by the ID of the command or app.
+#[derive(Serialize, Deserialize, Collection, Debug)]
+#[collection(name = "command-leader-keys")]
+pub struct CommandLeaderKeys {
+    #[natural_id]
+    id: String,
+    key: String,
+}
@@ -0,0 +1,373 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::anyhow;
+use async_std::{stream::StreamExt, sync::Mutex, task::spawn};
+use crossbeam_channel::Sender;
+use global_hotkey::hotkey::HotKey;
+use zbus::{
+    proxy,
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value},
+    Connection,
+};
+
+use super::keys::Binding;
+
+#[proxy(
+    interface = "org.freedesktop.portal.GlobalShortcuts",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop"
+)]
+trait GlobalShortcuts {
+    fn create_session(&self, options: HashMap<&str, Value<'_>>) -> zbus::Result<OwnedObjectPath>;
+
+    fn bind_shortcuts(
+        &self,
+        session_handle: &ObjectPath<'_>,
+        shortcuts: &[(&str, HashMap<&str, Value<'_>>)],
+        parent_window: &str,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(signal)]
+    fn activated(
+        &self,
+        session_handle: OwnedObjectPath,
+        shortcut_id: String,
+        timestamp: u64,
+        options: HashMap<String, OwnedValue>,
+    ) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn version(&self) -> zbus::Result<u32>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.portal.Request",
+    default_service = "org.freedesktop.portal.Desktop"
+)]
+trait Request {
+    #[zbus(signal)]
+    fn response(&self, response: u32, results: HashMap<String, OwnedValue>) -> zbus::Result<()>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.portal.Session",
+    default_service = "org.freedesktop.portal.Desktop"
+)]
+trait Session {
+    fn close(&self) -> zbus::Result<()>;
+}
+
+fn handle_token() -> String {
+    format!("loungy{}", rand::random::<u32>())
+}
+
+/// Registers hotkeys through the `org.freedesktop.portal.GlobalShortcuts` D-Bus interface, since Wayland compositors don't let clients grab keys.
+#[derive(Clone)]
+pub struct Portal {
+    connection: Connection,
+    proxy: GlobalShortcutsProxy<'static>,
+    // Shortcuts can only be bound once per session, so every change starts a new one
+    session: Arc<Mutex<Option<OwnedObjectPath>>>,
+}
+
+impl Portal {
+    /// Connects to the portal and forwards the hotkey IDs of activated shortcuts to `sender`.
+    pub async fn new(sender: Sender<u32>) -> anyhow::Result<Self> {
+        Self::connect(Connection::session().await?, sender).await
+    }
+    async fn connect(connection: Connection, sender: Sender<u32>) -> anyhow::Result<Self> {
+        let proxy = GlobalShortcutsProxy::new(&connection).await?;
+        // Fails if there is no portal implementing the interface
+        proxy.version().await?;
+        let session = Arc::new(Mutex::new(None::<OwnedObjectPath>));
+        let mut activated = proxy.receive_activated().await?;
+        let current = session.clone();
+        spawn(async move {
+            while let Some(signal) = activated.next().await {
+                let Ok(args) = signal.args() else {
+                    continue;
+                };
+                // Sessions that were replaced still have the old shortcuts bound
+                if current.lock().await.as_ref() != Some(&args.session_handle) {
+                    continue;
+                }
+                // The shortcut IDs are the IDs of the equivalent global-hotkey hotkeys
+                if let Ok(id) = args.shortcut_id.parse::<u32>() {
+                    let _ = sender.send(id);
+                }
+            }
+        });
+        Ok(Self {
+            connection,
+            proxy,
+            session,
+        })
+    }
+    // Portal objects are named after the unique name of the connection and a token picked by the caller
+    fn path(&self, kind: &str, token: &str) -> anyhow::Result<OwnedObjectPath> {
+        let sender = self
+            .connection
+            .unique_name()
+            .ok_or_else(|| anyhow!("Not connected to the session bus"))?
+            .trim_start_matches(':')
+            .replace('.', "_");
+        let path = format!(
+            "/org/freedesktop/portal/desktop/{}/{}/{}",
+            kind, sender, token
+        );
+        Ok(OwnedObjectPath::try_from(path)?)
+    }
+    // Calls answer through a request object, which has to be subscribed to before the call to not miss the response
+    async fn request(&self, token: &str) -> anyhow::Result<ResponseStream<'static>> {
+        let request = RequestProxy::new(&self.connection, self.path("request", token)?).await?;
+        Ok(request.receive_response().await?)
+    }
+    async fn response(mut responses: ResponseStream<'static>) -> anyhow::Result<()> {
+        let response = responses
+            .next()
+            .await
+            .ok_or_else(|| anyhow!("Portal didn't respond"))?;
+        match response.args()?.response {
+            0 => Ok(()),
+            1 => Err(anyhow!("Cancelled by the user")),
+            _ => Err(anyhow!("Portal request failed")),
+        }
+    }
+    /// Replaces all bound shortcuts with the given hotkeys and their descriptions.
+    pub async fn bind(&self, hotkeys: Vec<(Binding, String)>) -> anyhow::Result<()> {
+        let mut session = self.session.lock().await;
+        if let Some(handle) = session.take() {
+            let _ = SessionProxy::new(&self.connection, handle)
+                .await?
+                .close()
+                .await;
+        }
+
+        let token = handle_token();
+        let responses = self.request(&token).await?;
+        self.proxy
+            .create_session(HashMap::from([
+                ("handle_token", Value::from(token.as_str())),
+                ("session_handle_token", Value::from(token.as_str())),
+            ]))
+            .await?;
+        Self::response(responses).await?;
+        let handle = self.path("session", &token)?;
+        *session = Some(handle.clone());
+
+        let ids = hotkeys
+            .iter()
+            .map(|(binding, _)| HotKey::from(binding).id().to_string())
+            .collect::<Vec<_>>();
+        let shortcuts = hotkeys
+            .iter()
+            .zip(&ids)
+            .map(|((binding, description), id)| {
+                (
+                    id.as_str(),
+                    HashMap::from([
+                        ("description", Value::from(description.as_str())),
+                        ("preferred_trigger", Value::from(binding.trigger())),
+                    ]),
+                )
+            })
+            .collect::<Vec<_>>();
+        let token = handle_token();
+        let responses = self.request(&token).await?;
+        self.proxy
+            .bind_shortcuts(
+                &handle,
+                &shortcuts,
+                "",
+                HashMap::from([("handle_token", Value::from(token.as_str()))]),
+            )
+            .await?;
+        Self::response(responses).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, ErrorKind},
+        process::{Child, Command, Stdio},
+        str::FromStr,
+        time::Duration,
+    };
+
+    use async_std::task::block_on;
+    use crossbeam_channel::unbounded;
+    use zbus::{interface, message::Header, names::BusName, ConnectionBuilder};
+
+    use super::*;
+
+    // A private session bus, so the test doesn't depend on the desktop it runs on
+    struct Bus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl Bus {
+        /// Nothing without dbus-daemon installed.
+        fn start() -> Option<Self> {
+            let mut daemon = match Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .spawn()
+            {
+                Ok(daemon) => daemon,
+                Err(err) if err.kind() == ErrorKind::NotFound => return None,
+                Err(err) => panic!("Failed to start dbus-daemon: {}", err),
+            };
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take().unwrap())
+                .read_line(&mut address)
+                .unwrap();
+            Some(Self {
+                daemon,
+                address: address.trim().to_string(),
+            })
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    // Accepts every request right away
+    struct MockPortal;
+
+    impl MockPortal {
+        async fn respond(
+            connection: &Connection,
+            header: &Header<'_>,
+            options: &HashMap<String, OwnedValue>,
+        ) -> zbus::fdo::Result<OwnedObjectPath> {
+            let sender = header
+                .sender()
+                .unwrap()
+                .trim_start_matches(':')
+                .replace('.', "_");
+            let token = String::try_from(options["handle_token"].try_clone().unwrap()).unwrap();
+            let path = OwnedObjectPath::try_from(format!(
+                "/org/freedesktop/portal/desktop/request/{}/{}",
+                sender, token
+            ))
+            .unwrap();
+            connection
+                .emit_signal(
+                    header.sender().cloned(),
+                    &path,
+                    "org.freedesktop.portal.Request",
+                    "Response",
+                    &(0u32, HashMap::<&str, Value>::new()),
+                )
+                .await?;
+            Ok(path)
+        }
+    }
+
+    #[interface(name = "org.freedesktop.portal.GlobalShortcuts")]
+    impl MockPortal {
+        async fn create_session(
+            &self,
+            #[zbus(connection)] connection: &Connection,
+            #[zbus(header)] header: Header<'_>,
+            options: HashMap<String, OwnedValue>,
+        ) -> zbus::fdo::Result<OwnedObjectPath> {
+            Self::respond(connection, &header, &options).await
+        }
+
+        async fn bind_shortcuts(
+            &self,
+            #[zbus(connection)] connection: &Connection,
+            #[zbus(header)] header: Header<'_>,
+            _session_handle: OwnedObjectPath,
+            _shortcuts: Vec<(String, HashMap<String, OwnedValue>)>,
+            _parent_window: String,
+            options: HashMap<String, OwnedValue>,
+        ) -> zbus::fdo::Result<OwnedObjectPath> {
+            Self::respond(connection, &header, &options).await
+        }
+
+        #[zbus(property)]
+        fn version(&self) -> u32 {
+            1
+        }
+    }
+
+    async fn activate(portal: &Connection, session: &str, id: u32) {
+        portal
+            .emit_signal(
+                None::<BusName>,
+                "/org/freedesktop/portal/desktop",
+                "org.freedesktop.portal.GlobalShortcuts",
+                "Activated",
+                &(
+                    ObjectPath::try_from(session).unwrap(),
+                    id.to_string(),
+                    0u64,
+                    HashMap::<&str, Value>::new(),
+                ),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn ignores_other_sessions() {
+        let Some(bus) = Bus::start() else {
+            eprintln!("Skipped, dbus-daemon isn't installed");
+            return;
+        };
+        block_on(async {
+            let mock = ConnectionBuilder::address(bus.address.as_str())
+                .unwrap()
+                .name("org.freedesktop.portal.Desktop")
+                .unwrap()
+                .serve_at("/org/freedesktop/portal/desktop", MockPortal)
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+            let connection = ConnectionBuilder::address(bus.address.as_str())
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+            let (sender, receiver) = unbounded();
+            let portal = Portal::connect(connection, sender).await.unwrap();
+            let binding = Binding::from_str("control+Space").unwrap();
+            portal
+                .bind(vec![(binding, "Open Loungy".to_string())])
+                .await
+                .unwrap();
+            let session = portal.session.lock().await.clone().unwrap();
+
+            activate(
+                &mock,
+                "/org/freedesktop/portal/desktop/session/other/old",
+                1,
+            )
+            .await;
+            activate(&mock, session.as_str(), 2).await;
+            assert_eq!(receiver.recv_timeout(Duration::from_secs(5)), Ok(2));
+            assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+        });
+    }
+}
@@ -0,0 +1,146 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, ErrorKind, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    thread,
+};
+
+use anyhow::anyhow;
+use clap::Subcommand;
+use futures::{
+    channel::mpsc::{unbounded, UnboundedSender},
+    StreamExt,
+};
+use gpui::*;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commands::RootCommands,
+    paths::{paths, NAME},
+    state::{Actions, StateModel},
+    window::Window,
+};
+
+/// Commands a running instance can be controlled with, either from the command line or through the socket.
+#[derive(Subcommand, Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "lowercase", tag = "command")]
+pub enum Command {
+    /// Show the window if it is hidden, hide it otherwise
+    Toggle,
+    /// Show the window
+    Show {
+        /// Search for this in the root list
+        #[arg(short, long)]
+        query: Option<String>,
+    },
+    /// Hide the window
+    Hide,
+    /// Run a root command by its ID, like a hotkey would
+    Run { id: String },
+}
+
+fn socket_path() -> PathBuf {
+    paths().runtime.join(format!("{}.sock", NAME))
+}
+
+/// Forwards a command to the running instance, fails if there is none.
+pub fn send(command: &Command) -> anyhow::Result<()> {
+    let mut stream = UnixStream::connect(socket_path())?;
+    writeln!(stream, "{}", serde_json::to_string(command)?)?;
+    Ok(())
+}
+
+fn listen(sender: UnboundedSender<Command>) -> anyhow::Result<()> {
+    let path = socket_path();
+    if path.exists() {
+        match UnixStream::connect(&path) {
+            // Another instance started at the same time and got there first
+            Ok(_) => return Err(anyhow!("{} is already in use", path.display())),
+            // Nobody is listening, so the socket is left over from a crash
+            Err(err) if err.kind() == ErrorKind::ConnectionRefused => fs::remove_file(&path)?,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                match serde_json::from_str::<Command>(&line) {
+                    Ok(command) => {
+                        let _ = sender.unbounded_send(command);
+                    }
+                    Err(err) => error!("Invalid IPC command: {}", err),
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+pub struct Ipc;
+
+impl Ipc {
+    /// Listens on the control socket, `initial` is what the instance itself was launched with.
+    pub fn init(initial: Option<Command>, cx: &mut WindowContext) {
+        let (sender, mut receiver) = unbounded();
+        if let Err(err) = listen(sender.clone()) {
+            error!("Failed to open IPC socket: {}", err);
+        }
+        // The window is already shown on launch, so toggling it would only hide it again
+        if let Some(command) = initial.filter(|c| !matches!(c, Command::Toggle)) {
+            let _ = sender.unbounded_send(command);
+        }
+        cx.spawn(|mut cx| async move {
+            while let Some(command) = receiver.next().await {
+                let open = Window::is_open(&cx);
+                let _ = cx.update(|cx| Self::handle(command, open, cx));
+            }
+        })
+        .detach();
+    }
+    fn handle(command: Command, open: bool, cx: &mut WindowContext) {
+        match command {
+            Command::Toggle if open => Window::close(cx),
+            Command::Toggle => Window::open(cx),
+            Command::Show { query } => {
+                StateModel::update(
+                    |this, cx| {
+                        this.reset(cx);
+                        if let Some(query) = query {
+                            let input = this.inner.read(cx).stack[0].query.downgrade();
+                            input.set_text(query, cx);
+                        }
+                    },
+                    cx,
+                );
+                Window::open(cx);
+            }
+            Command::Hide => Window::close(cx),
+            Command::Run { id } => {
+                let Some(command) = cx.global::<RootCommands>().commands.get(&id).cloned() else {
+                    error!("Unknown command: {}", id);
+                    return;
+                };
+                StateModel::update(|this, cx| this.reset(cx), cx);
+                (command.action)(&mut Actions::default(cx), cx);
+                Window::open(cx);
+            }
+        }
+    }
+}
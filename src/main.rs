@@ -10,27 +10,62 @@
  */
 
 use app::run_app;
+use clap::Parser;
+use dmenu::{DmenuArgs, DmenuListBuilder};
 use gpui::App;
+use ipc::Command;
 
 mod app;
 mod assets;
 mod commands;
 mod components;
 mod db;
+mod dmenu;
+mod extensions;
+mod history;
 mod hotkey;
+mod ipc;
 mod paths;
 mod platform;
+#[cfg(feature = "plugins")]
+mod plugins;
 mod query;
+mod settings;
 mod state;
 mod theme;
 mod window;
 mod workspace;
 mod date;
 
+#[derive(Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    dmenu: DmenuArgs,
+}
+
 #[async_std::main]
 async fn main() {
     env_logger::init();
+    let cli = Cli::parse();
+    // Entries are read before the window opens, like dmenu does
+    let dmenu = cli.dmenu.dmenu.then(|| DmenuListBuilder::new(cli.dmenu));
+    // Only a single instance runs at a time, launching another one forwards to it instead
+    if dmenu.is_none() {
+        let command = cli.command.clone().unwrap_or(Command::Show { query: None });
+        match ipc::send(&command) {
+            Ok(()) => return,
+            // A new instance would only show the window, which isn't what was asked for
+            Err(err) if matches!(command, Command::Hide | Command::Toggle) => {
+                eprintln!("Loungy is not running: {}", err);
+                std::process::exit(1);
+            }
+            Err(_) => {}
+        }
+    }
     let app = App::new();
 
-    run_app(app)
+    run_app(app, dmenu, cli.command)
 }
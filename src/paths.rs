@@ -9,36 +9,90 @@
  *
  */
 
-use std::{path::PathBuf, sync::OnceLock};
+use std::{env, path::PathBuf, sync::OnceLock};
 
 pub struct Paths {
+    pub home: PathBuf,
     pub cache: PathBuf,
     pub config: PathBuf,
     pub data: PathBuf,
+    // Where sockets and other per-session files live
+    pub runtime: PathBuf,
+    // $XDG_DATA_HOME followed by $XDG_DATA_DIRS, in order of precedence
+    #[cfg(target_os = "linux")]
+    pub data_dirs: Vec<PathBuf>,
 }
 
 pub static NAME: &str = "loungy";
 
+// The XDG base directory spec requires paths to be absolute, relative ones are ignored
+#[cfg(target_os = "linux")]
+fn env_path(key: &str) -> Option<PathBuf> {
+    env::var_os(key)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+#[cfg(target_os = "linux")]
+fn env_paths(key: &str, default: &[&str]) -> Vec<PathBuf> {
+    let paths: Vec<PathBuf> = env::var_os(key)
+        .map(|value| {
+            env::split_paths(&value)
+                .filter(|path| path.is_absolute())
+                .collect()
+        })
+        .unwrap_or_default();
+    if paths.is_empty() {
+        default.iter().map(PathBuf::from).collect()
+    } else {
+        paths
+    }
+}
+
 impl Paths {
     pub fn new() -> Self {
         let username = whoami::username();
         #[cfg(target_os = "macos")]
-        let user_dir = PathBuf::from("/Users").join(username);
+        let fallback = PathBuf::from("/Users").join(username);
+        #[cfg(target_os = "linux")]
+        let fallback = PathBuf::from("/home").join(username);
+        let home = env::var_os("HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .unwrap_or(fallback);
+
+        #[cfg(target_os = "macos")]
+        {
+            let cache = home.join("Library/Caches").join(NAME);
+            Self {
+                config: home.join(".config").join(NAME),
+                data: home.join("Library/Application Support").join(NAME),
+                runtime: cache.clone(),
+                cache,
+                home,
+            }
+        }
         #[cfg(target_os = "linux")]
-        let user_dir = PathBuf::from("/home").join(username);
-        Self {
-            #[cfg(target_os = "macos")]
-            cache: user_dir.clone().join("Library/Caches").join(NAME),
-            #[cfg(target_os = "linux")]
-            cache: user_dir.clone().join(".cache").join(NAME),
-            config: user_dir.clone().join(".config").join(NAME),
-            #[cfg(target_os = "macos")]
-            data: user_dir
-                .clone()
-                .join("Library/Application Support")
-                .join(NAME),
-            #[cfg(target_os = "linux")]
-            data: user_dir.clone().join(".local/share").join(NAME),
+        {
+            let data_home = env_path("XDG_DATA_HOME").unwrap_or(home.join(".local/share"));
+            let mut data_dirs = vec![data_home.clone()];
+            data_dirs.append(&mut env_paths(
+                "XDG_DATA_DIRS",
+                &["/usr/local/share", "/usr/share"],
+            ));
+            let cache = env_path("XDG_CACHE_HOME")
+                .unwrap_or(home.join(".cache"))
+                .join(NAME);
+            Self {
+                runtime: env_path("XDG_RUNTIME_DIR").unwrap_or(cache.clone()),
+                cache,
+                config: env_path("XDG_CONFIG_HOME")
+                    .unwrap_or(home.join(".config"))
+                    .join(NAME),
+                data: data_home.join(NAME),
+                data_dirs,
+                home,
+            }
         }
     }
 }
@@ -0,0 +1,321 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use evdev::{
+    uinput::{VirtualDevice, VirtualDeviceBuilder},
+    AttributeSet, Device, EventType, InputEvent, InputEventKind, Key,
+};
+use futures::channel::mpsc::UnboundedSender;
+use log::error;
+use notify::{RecursiveMode, Watcher};
+
+use super::{KeyboardMonitor, Stroke, Typed};
+
+// Events of the virtual keyboard are skipped by name, otherwise the replacement would be read back
+static NAME: &str = "Loungy Virtual Keyboard";
+
+static INPUT: &str = "/dev/input";
+
+// Characters of keys without and with shift, assuming a US layout
+static LAYOUT: &[(Key, char, char)] = &[
+    (Key::KEY_A, 'a', 'A'),
+    (Key::KEY_B, 'b', 'B'),
+    (Key::KEY_C, 'c', 'C'),
+    (Key::KEY_D, 'd', 'D'),
+    (Key::KEY_E, 'e', 'E'),
+    (Key::KEY_F, 'f', 'F'),
+    (Key::KEY_G, 'g', 'G'),
+    (Key::KEY_H, 'h', 'H'),
+    (Key::KEY_I, 'i', 'I'),
+    (Key::KEY_J, 'j', 'J'),
+    (Key::KEY_K, 'k', 'K'),
+    (Key::KEY_L, 'l', 'L'),
+    (Key::KEY_M, 'm', 'M'),
+    (Key::KEY_N, 'n', 'N'),
+    (Key::KEY_O, 'o', 'O'),
+    (Key::KEY_P, 'p', 'P'),
+    (Key::KEY_Q, 'q', 'Q'),
+    (Key::KEY_R, 'r', 'R'),
+    (Key::KEY_S, 's', 'S'),
+    (Key::KEY_T, 't', 'T'),
+    (Key::KEY_U, 'u', 'U'),
+    (Key::KEY_V, 'v', 'V'),
+    (Key::KEY_W, 'w', 'W'),
+    (Key::KEY_X, 'x', 'X'),
+    (Key::KEY_Y, 'y', 'Y'),
+    (Key::KEY_Z, 'z', 'Z'),
+    (Key::KEY_1, '1', '!'),
+    (Key::KEY_2, '2', '@'),
+    (Key::KEY_3, '3', '#'),
+    (Key::KEY_4, '4', '$'),
+    (Key::KEY_5, '5', '%'),
+    (Key::KEY_6, '6', '^'),
+    (Key::KEY_7, '7', '&'),
+    (Key::KEY_8, '8', '*'),
+    (Key::KEY_9, '9', '('),
+    (Key::KEY_0, '0', ')'),
+    (Key::KEY_MINUS, '-', '_'),
+    (Key::KEY_EQUAL, '=', '+'),
+    (Key::KEY_LEFTBRACE, '[', '{'),
+    (Key::KEY_RIGHTBRACE, ']', '}'),
+    (Key::KEY_SEMICOLON, ';', ':'),
+    (Key::KEY_APOSTROPHE, '\'', '"'),
+    (Key::KEY_GRAVE, '`', '~'),
+    (Key::KEY_BACKSLASH, '\\', '|'),
+    (Key::KEY_COMMA, ',', '<'),
+    (Key::KEY_DOT, '.', '>'),
+    (Key::KEY_SLASH, '/', '?'),
+    (Key::KEY_SPACE, ' ', ' '),
+];
+
+static SHIFT: &[Key] = &[Key::KEY_LEFTSHIFT, Key::KEY_RIGHTSHIFT];
+
+static MODIFIERS: &[Key] = &[
+    Key::KEY_LEFTCTRL,
+    Key::KEY_RIGHTCTRL,
+    Key::KEY_LEFTALT,
+    Key::KEY_RIGHTALT,
+    Key::KEY_LEFTMETA,
+    Key::KEY_RIGHTMETA,
+];
+
+fn character(key: Key, shift: bool) -> Option<char> {
+    LAYOUT
+        .iter()
+        .find(|(k, _, _)| *k == key)
+        .map(|(_, lower, upper)| if shift { *upper } else { *lower })
+}
+
+// Devices with letter keys, which leaves out power buttons, mice and the like
+fn is_keyboard(device: &Device) -> bool {
+    device.name() != Some(NAME)
+        && device
+            .supported_keys()
+            .is_some_and(|keys| keys.contains(Key::KEY_A) && keys.contains(Key::KEY_SPACE))
+}
+
+// Device nodes are created before udev gives the input group access to them
+fn open(path: &Path) -> Option<Device> {
+    for _ in 0..20 {
+        if let Ok(device) = Device::open(path) {
+            return Some(device);
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    None
+}
+
+fn read(path: PathBuf, mut device: Device, sender: UnboundedSender<Typed>) {
+    let mut held = HashSet::new();
+    loop {
+        let events = match device.fetch_events() {
+            Ok(events) => events,
+            Err(err) => {
+                error!("Stopped reading {}: {}", path.display(), err);
+                return;
+            }
+        };
+        for event in events {
+            let InputEventKind::Key(key) = event.kind() else {
+                continue;
+            };
+            // 0 is a release, 1 a press and 2 a repeat
+            if event.value() == 0 {
+                held.remove(&key);
+                continue;
+            }
+            if SHIFT.contains(&key) || MODIFIERS.contains(&key) {
+                held.insert(key);
+                continue;
+            }
+            let typed = if MODIFIERS.iter().any(|m| held.contains(m)) {
+                Typed::Reset
+            } else if key == Key::KEY_BACKSPACE {
+                Typed::Backspace
+            } else {
+                let shift = SHIFT.iter().any(|s| held.contains(s));
+                character(key, shift)
+                    .map(Typed::Char)
+                    .unwrap_or(Typed::Reset)
+            };
+            if sender.unbounded_send(typed).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Reads keyboards through evdev and types through uinput, which needs read access to
+/// `/dev/input/event*` and write access to `/dev/uinput`, usually through the `input` group.
+/// Any other virtual keyboard created through uinput is read like a physical one.
+pub struct EvdevMonitor {
+    device: VirtualDevice,
+}
+
+impl EvdevMonitor {
+    pub fn new() -> anyhow::Result<Self> {
+        let mut keys = AttributeSet::<Key>::new();
+        for key in [
+            Key::KEY_BACKSPACE,
+            Key::KEY_LEFT,
+            Key::KEY_LEFTCTRL,
+            Key::KEY_LEFTSHIFT,
+            Key::KEY_V,
+        ] {
+            keys.insert(key);
+        }
+        let device = VirtualDeviceBuilder::new()
+            .map_err(|err| anyhow!("Failed to open /dev/uinput: {}", err))?
+            .name(NAME)
+            .with_keys(&keys)?
+            .build()?;
+        Ok(Self { device })
+    }
+}
+
+impl KeyboardMonitor for EvdevMonitor {
+    fn listen(&mut self, sender: UnboundedSender<Typed>) -> anyhow::Result<()> {
+        // Keyboards plugged in later show up as new event devices
+        let (created, receiver) = crossbeam_channel::unbounded();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                if event.kind.is_create() {
+                    for path in event.paths {
+                        let _ = created.send(path);
+                    }
+                }
+            })?;
+        watcher.watch(Path::new(INPUT), RecursiveMode::NonRecursive)?;
+        let keyboards: Vec<_> = evdev::enumerate()
+            .filter(|(_, device)| is_keyboard(device))
+            .collect();
+        if keyboards.is_empty() {
+            error!("No readable keyboards in {} yet", INPUT);
+        }
+        for (path, device) in keyboards {
+            let sender = sender.clone();
+            thread::spawn(move || read(path, device, sender));
+        }
+        thread::spawn(move || {
+            // Events stop once the watcher is dropped
+            let _watcher = watcher;
+            for path in receiver {
+                let is_event = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("event"));
+                if !is_event {
+                    continue;
+                }
+                if sender.is_closed() {
+                    return;
+                }
+                match open(&path) {
+                    Some(device) if is_keyboard(&device) => {
+                        let sender = sender.clone();
+                        thread::spawn(move || read(path, device, sender));
+                    }
+                    Some(_) => {}
+                    None => error!("Failed to open {}", path.display()),
+                }
+            }
+        });
+        Ok(())
+    }
+    fn press(&mut self, strokes: &[Stroke]) -> anyhow::Result<()> {
+        for stroke in strokes {
+            let keys: &[Key] = match stroke {
+                Stroke::Backspace => &[Key::KEY_BACKSPACE],
+                Stroke::Left => &[Key::KEY_LEFT],
+                Stroke::Paste { shift: false } => &[Key::KEY_LEFTCTRL, Key::KEY_V],
+                Stroke::Paste { shift: true } => {
+                    &[Key::KEY_LEFTCTRL, Key::KEY_LEFTSHIFT, Key::KEY_V]
+                }
+            };
+            // Each key is sent on its own, some applications miss keys pressed in the same report
+            for key in keys {
+                self.device
+                    .emit(&[InputEvent::new(EventType::KEY, key.code(), 1)])?;
+            }
+            for key in keys.iter().rev() {
+                self.device
+                    .emit(&[InputEvent::new(EventType::KEY, key.code(), 0)])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_std::{future::timeout, task::block_on};
+    use futures::{channel::mpsc::unbounded, StreamExt};
+
+    use super::*;
+
+    #[test]
+    #[ignore = "needs read access to /dev/input and write access to /dev/uinput"]
+    fn reads_keyboards_plugged_in_later() {
+        let (sender, mut receiver) = unbounded();
+        let mut monitor = EvdevMonitor::new().unwrap();
+        monitor.listen(sender).unwrap();
+
+        // Created after listening, so it is only read if new devices are picked up
+        let mut keys = AttributeSet::<Key>::new();
+        for (key, _, _) in LAYOUT {
+            keys.insert(*key);
+        }
+        let mut keyboard = VirtualDeviceBuilder::new()
+            .unwrap()
+            .name("Loungy Test Keyboard")
+            .with_keys(&keys)
+            .unwrap()
+            .build()
+            .unwrap();
+        thread::sleep(Duration::from_secs(1));
+        for key in [Key::KEY_SEMICOLON, Key::KEY_S, Key::KEY_I, Key::KEY_G] {
+            for value in [1, 0] {
+                keyboard
+                    .emit(&[InputEvent::new(EventType::KEY, key.code(), value)])
+                    .unwrap();
+            }
+        }
+
+        let typed = block_on(async {
+            let mut typed = vec![];
+            while typed.len() < 4 {
+                let next = timeout(Duration::from_secs(5), receiver.next()).await;
+                typed.push(next.expect("Nothing was typed").unwrap());
+            }
+            typed
+        });
+        assert_eq!(
+            typed,
+            vec![
+                Typed::Char(';'),
+                Typed::Char('s'),
+                Typed::Char('i'),
+                Typed::Char('g')
+            ]
+        );
+    }
+}
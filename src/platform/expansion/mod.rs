@@ -0,0 +1,262 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::time::Duration;
+
+use async_std::task::sleep;
+use futures::{
+    channel::mpsc::{unbounded, UnboundedSender},
+    StreamExt,
+};
+use gpui::*;
+use log::error;
+
+use crate::{
+    commands::root::snippets::{Snippet, Snippets},
+    settings::Settings,
+    state::StateModel,
+    window::{Frontmost, Window},
+};
+
+use super::ClipboardWatcher;
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+// Applications read the clipboard after getting the paste keys, so it is only restored after this
+static RESTORE_DELAY: Duration = Duration::from_millis(500);
+
+/// What was typed on a physical keyboard.
+#[derive(Debug, PartialEq)]
+pub enum Typed {
+    Char(char),
+    Backspace,
+    // Anything that moves the cursor or isn't text, like enter, arrows or shortcuts
+    Reset,
+}
+
+/// Keys pressed on the virtual keyboard to replace a keyword.
+#[derive(Clone, Copy)]
+pub enum Stroke {
+    Backspace,
+    Left,
+    /// Ctrl+V, or Ctrl+Shift+V in terminals
+    Paste {
+        shift: bool,
+    },
+}
+
+/// Watches every keyboard and types on a virtual one.
+pub trait KeyboardMonitor {
+    /// Starts sending what is typed to `sender` in the background.
+    fn listen(&mut self, sender: UnboundedSender<Typed>) -> anyhow::Result<()>;
+    fn press(&mut self, strokes: &[Stroke]) -> anyhow::Result<()>;
+}
+
+/// Adds what was typed to `buffer` and returns the position of the keyword it now ends with.
+/// Keywords are sorted longest first, so the buffer only has to keep as much as the first one.
+fn matched<'a>(
+    buffer: &mut String,
+    typed: Typed,
+    keywords: impl IntoIterator<Item = &'a str>,
+) -> Option<usize> {
+    match typed {
+        Typed::Char(c) => buffer.push(c),
+        Typed::Backspace => {
+            buffer.pop();
+            return None;
+        }
+        Typed::Reset => {
+            buffer.clear();
+            return None;
+        }
+    }
+    let mut keywords = keywords.into_iter().peekable();
+    let longest = keywords
+        .peek()
+        .map(|keyword| keyword.chars().count())
+        .unwrap_or_default();
+    let count = buffer.chars().count();
+    if count > longest {
+        *buffer = buffer.chars().skip(count - longest).collect();
+    }
+    let index = keywords.position(|keyword| buffer.ends_with(keyword))?;
+    buffer.clear();
+    Some(index)
+}
+
+#[cfg(target_os = "linux")]
+fn monitor() -> anyhow::Result<Box<dyn KeyboardMonitor>> {
+    Ok(Box::new(linux::EvdevMonitor::new()?))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn monitor() -> anyhow::Result<Box<dyn KeyboardMonitor>> {
+    Err(anyhow::anyhow!("No keyboard monitor on this platform"))
+}
+
+/// Expands snippet keywords as they are typed in any application.
+pub struct Expansion {
+    monitor: Box<dyn KeyboardMonitor>,
+    // Snippets with a keyword, longest first so `e;sig` wins over `;sig`
+    snippets: Vec<Snippet>,
+    typed: String,
+}
+
+impl Expansion {
+    pub fn init(cx: &mut WindowContext) {
+        let (sender, mut receiver) = unbounded();
+        let monitor = monitor().and_then(|mut monitor| {
+            monitor.listen(sender)?;
+            Ok(monitor)
+        });
+        let monitor = match monitor {
+            Ok(monitor) => monitor,
+            Err(err) => {
+                error!("Text expansion unavailable: {}", err);
+                return;
+            }
+        };
+        cx.set_global(Self {
+            monitor,
+            snippets: vec![],
+            typed: String::new(),
+        });
+        Self::reload(cx);
+        cx.spawn(|mut cx| async move {
+            while let Some(typed) = receiver.next().await {
+                // Typing into Loungy itself never expands anything
+                let typed = if Window::is_open(&cx) {
+                    Typed::Reset
+                } else {
+                    typed
+                };
+                let _ = cx.update_global::<Self, _>(|this, cx| this.handle(typed, cx));
+            }
+        })
+        .detach();
+    }
+    pub fn reload(cx: &mut AppContext) {
+        if !cx.has_global::<Self>() {
+            return;
+        }
+        let mut snippets: Vec<Snippet> = Snippets::all()
+            .into_iter()
+            .filter(|snippet| !snippet.keyword.is_empty())
+            .collect();
+        snippets.sort_by_key(|snippet| std::cmp::Reverse(snippet.keyword.chars().count()));
+        cx.global_mut::<Self>().snippets = snippets;
+    }
+    fn handle(&mut self, typed: Typed, cx: &mut WindowContext) {
+        let keywords = self.snippets.iter().map(|snippet| snippet.keyword.as_str());
+        let Some(index) = matched(&mut self.typed, typed, keywords) else {
+            return;
+        };
+        let snippet = self.snippets[index].clone();
+        cx.spawn(|mut cx| async move {
+            let app = Frontmost::id().await.unwrap_or_default();
+            let _ = cx.update_global::<Self, _>(|this, cx| this.expand(snippet, app, cx));
+        })
+        .detach();
+    }
+    fn expand(&mut self, snippet: Snippet, app: String, cx: &mut WindowContext) {
+        let settings = cx.global::<Settings>();
+        if settings.expansion_exclude.contains(&app) {
+            return;
+        }
+        let shift = settings.expansion_shift_paste.contains(&app);
+        let erase = vec![Stroke::Backspace; snippet.keyword.chars().count()];
+        if let Err(err) = self.monitor.press(&erase) {
+            error!("Failed to remove keyword: {}", err);
+            return;
+        }
+        if !snippet.arguments().is_empty() {
+            // The snippet is pasted from the form once it is submitted
+            StateModel::update(|this, cx| this.reset(cx), cx);
+            snippet.ask(false, cx);
+            Window::open(cx);
+            return;
+        }
+        let expanded = snippet.expand(&Default::default(), cx);
+        let mut paste = vec![Stroke::Paste { shift }];
+        paste.extend(vec![Stroke::Left; expanded.cursor]);
+        let previous = cx.read_from_clipboard();
+        cx.spawn(|mut cx| async move {
+            ClipboardWatcher::disabled(&mut cx);
+            let _ = cx.update(|cx| cx.write_to_clipboard(ClipboardItem::new(expanded.text)));
+            let _ = cx.update_global::<Self, _>(|this, _| {
+                if let Err(err) = this.monitor.press(&paste) {
+                    error!("Failed to expand snippet: {}", err);
+                }
+            });
+            // The snippet shouldn't replace what was copied before
+            let Some(previous) = previous else {
+                return;
+            };
+            sleep(RESTORE_DELAY).await;
+            ClipboardWatcher::disabled(&mut cx);
+            let _ = cx.update(|cx| cx.write_to_clipboard(previous));
+        })
+        .detach();
+    }
+}
+
+impl Global for Expansion {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typing(text: &str, keywords: &[&str]) -> Vec<Option<usize>> {
+        let mut buffer = String::new();
+        text.chars()
+            .map(|c| {
+                let typed = match c {
+                    '<' => Typed::Backspace,
+                    '|' => Typed::Reset,
+                    c => Typed::Char(c),
+                };
+                matched(&mut buffer, typed, keywords.iter().copied())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn longest_keyword_first() {
+        let keywords = ["e;sig", ";sig"];
+        assert_eq!(typing("e;sig", &keywords).last(), Some(&Some(0)));
+        assert_eq!(typing("x;sig", &keywords).last(), Some(&Some(1)));
+        assert_eq!(typing(";si", &keywords).last(), Some(&None));
+    }
+
+    #[test]
+    fn backspace_and_reset() {
+        let keywords = [";sig"];
+        assert_eq!(typing(";sx<ig", &keywords).last(), Some(&Some(0)));
+        assert_eq!(typing(";s|ig", &keywords).last(), Some(&None));
+        // A match starts over, so the keyword isn't found again right away
+        assert_eq!(
+            typing(";sig;sig", &keywords),
+            vec![None, None, None, Some(0), None, None, None, Some(0)]
+        );
+    }
+
+    #[test]
+    fn keeps_the_longest_keyword() {
+        let mut buffer = String::new();
+        for c in "a long sentence ;".chars() {
+            matched(&mut buffer, Typed::Char(c), [";sig"]);
+        }
+        assert_eq!(buffer, "ce ;");
+        matched(&mut buffer, Typed::Char('x'), []);
+        assert_eq!(buffer, "");
+    }
+}
@@ -9,14 +9,32 @@
  *
  */
 
+use anyhow::anyhow;
 use freedesktop_entry_parser::{parse_entry, AttrSelector};
 use freedesktop_icons::lookup;
-use std::path::PathBuf;
+use std::{env, path::PathBuf, sync::OnceLock};
+
+use super::find_executable;
+
+pub(crate) struct DesktopAction {
+    pub id: String,
+    pub name: String,
+    pub exec: Option<String>,
+}
 
 pub(crate) struct ApplicationDesktopFile {
     pub name: String,
+    pub generic_name: Option<String>,
+    pub comment: Option<String>,
     pub icon: Option<String>,
     pub keywords: Vec<String>,
+    pub actions: Vec<DesktopAction>,
+    pub exec: Option<String>,
+    pub try_exec: Option<String>,
+    pub path: Option<PathBuf>,
+    pub terminal: bool,
+    pub startup_wm_class: Option<String>,
+    pub location: PathBuf,
 }
 
 pub(crate) enum DesktopFileError {
@@ -24,6 +42,7 @@ pub(crate) enum DesktopFileError {
     NoDesktopEntry,
     InvalidFormat,
     HiddenFile,
+    MissingExecutable,
 }
 
 impl ApplicationDesktopFile {
@@ -32,6 +51,233 @@ impl ApplicationDesktopFile {
 
         lookup(icon_name).with_cache().find()
     }
+    /// Splits the `Exec` key of the entry, or of one of its desktop actions, into the program and its arguments,
+    /// with all field codes expanded. `%f`, `%F`, `%u` and `%U` expand to `target`, or to nothing without one.
+    /// Entries without any of them can't open a `target`.
+    pub(crate) fn arguments(
+        &self,
+        action: Option<&str>,
+        target: Option<&str>,
+    ) -> anyhow::Result<Vec<String>> {
+        let exec = match action {
+            Some(id) => self
+                .actions
+                .iter()
+                .find(|a| a.id == id)
+                .and_then(|action| action.exec.as_ref()),
+            None => self.exec.as_ref(),
+        }
+        .ok_or_else(|| anyhow!("No Exec key"))?;
+        let mut args = Vec::new();
+        let mut passed = false;
+        for arg in split_exec(&unescape(exec)).ok_or_else(|| anyhow!("Invalid Exec key"))? {
+            match arg.as_str() {
+                // Codes that stand for a whole list of arguments must be on their own
+                "%F" | "%U" => {
+                    if let Some(target) = target {
+                        args.push(target.to_string());
+                        passed = true;
+                    }
+                }
+                "%D" | "%N" => {}
+                "%i" => {
+                    if let Some(icon) = &self.icon {
+                        args.push("--icon".to_string());
+                        args.push(icon.clone());
+                    }
+                }
+                _ => {
+                    let expanded = self.expand_field_codes(&arg, target, &mut passed);
+                    // An argument consisting only of an empty field code is dropped entirely
+                    if !expanded.is_empty() || arg.is_empty() {
+                        args.push(expanded);
+                    }
+                }
+            }
+        }
+        if args.is_empty() {
+            return Err(anyhow!("Invalid Exec key"));
+        }
+        if target.is_some() && !passed {
+            return Err(anyhow!("{} can't open files or URLs", self.name));
+        }
+        Ok(args)
+    }
+    fn expand_field_codes(&self, arg: &str, target: Option<&str>, passed: &mut bool) -> String {
+        let mut expanded = String::new();
+        let mut chars = arg.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                expanded.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('%') => expanded.push('%'),
+                Some('c') => expanded.push_str(&self.name),
+                Some('k') => expanded.push_str(&self.location.to_string_lossy()),
+                Some('f') | Some('u') => {
+                    if let Some(target) = target {
+                        expanded.push_str(target);
+                        *passed = true;
+                    }
+                }
+                // The deprecated %d, %n, %v and %m expand to nothing
+                _ => {}
+            }
+        }
+        expanded
+    }
+}
+
+/// Locale keys to look up localized values with, from the most to the least specific.
+/// `lang_COUNTRY.ENCODING@MODIFIER` yields `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`, `lang@MODIFIER` and `lang`.
+fn locales() -> &'static Vec<String> {
+    static LOCALES: OnceLock<Vec<String>> = OnceLock::new();
+    LOCALES.get_or_init(|| {
+        let Some(locale) = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|key| env::var(key).ok())
+            .find(|value| !value.is_empty())
+        else {
+            return vec![];
+        };
+        let (locale, modifier) = match locale.split_once('@') {
+            Some((locale, modifier)) => (locale, Some(modifier)),
+            None => (locale.as_str(), None),
+        };
+        let locale = locale.split('.').next().unwrap_or_default();
+        if matches!(locale, "C" | "POSIX" | "") {
+            return vec![];
+        }
+        let (lang, country) = match locale.split_once('_') {
+            Some((lang, country)) => (lang, Some(country)),
+            None => (locale, None),
+        };
+        let mut keys = vec![];
+        if let (Some(country), Some(modifier)) = (country, modifier) {
+            keys.push(format!("{}_{}@{}", lang, country, modifier));
+        }
+        if let Some(country) = country {
+            keys.push(format!("{}_{}", lang, country));
+        }
+        if let Some(modifier) = modifier {
+            keys.push(format!("{}@{}", lang, modifier));
+        }
+        keys.push(lang.to_string());
+        keys
+    })
+}
+
+fn localized<T: AsRef<str>>(section: &AttrSelector<T>, key: &str) -> Option<String> {
+    locales()
+        .iter()
+        .find_map(|locale| section.attr_with_param(key, locale))
+        .or_else(|| section.attr(key))
+        .map(|s| s.to_string())
+}
+
+fn list<T: AsRef<str>>(section: &AttrSelector<T>, key: &str) -> Vec<String> {
+    section
+        .attr(key)
+        .map(|s| {
+            s.split(';')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn current_desktops() -> &'static Vec<String> {
+    static DESKTOPS: OnceLock<Vec<String>> = OnceLock::new();
+    DESKTOPS.get_or_init(|| {
+        env::var("XDG_CURRENT_DESKTOP")
+            .map(|value| {
+                value
+                    .split(':')
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// The locale, desktops and `$PATH` that entries are localized and filtered with.
+pub(crate) fn environment() -> String {
+    format!(
+        "{};{};{}",
+        locales().join(":"),
+        current_desktops().join(":"),
+        env::var("PATH").unwrap_or_default()
+    )
+}
+
+/// Reverses the escape sequences of string values from the desktop entry spec.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Splits an `Exec` value into arguments following the quoting rules of the desktop entry spec.
+fn split_exec(exec: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => match chars.next()? {
+                            c @ ('"' | '`' | '$' | '\\') => current.push(c),
+                            c => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                        },
+                        c => current.push(c),
+                    }
+                }
+            }
+            ' ' | '\t' | '\n' => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Some(args)
 }
 
 impl TryFrom<&PathBuf> for ApplicationDesktopFile {
@@ -41,16 +287,19 @@ impl TryFrom<&PathBuf> for ApplicationDesktopFile {
         let entry = parse_entry(value).map_err(|_| DesktopFileError::InvalidFormat)?;
 
         let content_section: AttrSelector<&str> = entry.section("Desktop Entry");
-        let name = content_section
-            .attr("Name")
-            .ok_or(DesktopFileError::NoDesktopEntry)?
-            .to_string();
+        let name = localized(&content_section, "Name").ok_or(DesktopFileError::NoDesktopEntry)?;
+        let generic_name = localized(&content_section, "GenericName");
+        let comment = localized(&content_section, "Comment");
 
         let icon = content_section.attr("Icon").map(|s| s.to_string());
 
-        let keywords = content_section
-            .attr("Keywords")
-            .map(|s| s.split(';').map(|s| s.to_string()).collect())
+        let keywords = localized(&content_section, "Keywords")
+            .map(|s| {
+                s.split(';')
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.to_string())
+                    .collect()
+            })
             .unwrap_or_default();
 
         let no_display = content_section
@@ -64,10 +313,198 @@ impl TryFrom<&PathBuf> for ApplicationDesktopFile {
             return Err(DesktopFileError::HiddenFile);
         }
 
+        let hidden = content_section
+            .attr("Hidden")
+            .map_or(Ok(false), |s| s.parse::<bool>())
+            .map_err(|_| DesktopFileError::InvalidFormat)?;
+
+        if hidden {
+            // Hidden means the entry was deleted, usually by a user override shadowing a system entry
+            return Err(DesktopFileError::HiddenFile);
+        }
+
+        let desktops = current_desktops();
+        let only_show_in = list(&content_section, "OnlyShowIn");
+        let not_show_in = list(&content_section, "NotShowIn");
+        if (!only_show_in.is_empty() && !only_show_in.iter().any(|d| desktops.contains(d)))
+            || not_show_in.iter().any(|d| desktops.contains(d))
+        {
+            return Err(DesktopFileError::HiddenFile);
+        }
+
+        let try_exec = content_section.attr("TryExec").map(|s| s.to_string());
+        if let Some(try_exec) = &try_exec {
+            if find_executable(try_exec).is_none() {
+                return Err(DesktopFileError::MissingExecutable);
+            }
+        }
+
+        let actions = list(&content_section, "Actions")
+            .into_iter()
+            .filter_map(|id| {
+                let section = entry.section(format!("Desktop Action {}", id));
+                Some(DesktopAction {
+                    name: localized(&section, "Name")?,
+                    exec: section.attr("Exec").map(|s| s.to_string()),
+                    id,
+                })
+            })
+            .collect();
+
+        let exec = content_section.attr("Exec").map(|s| s.to_string());
+        let path = content_section
+            .attr("Path")
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from);
+        let terminal = content_section
+            .attr("Terminal")
+            .map_or(Ok(false), |s| s.parse::<bool>())
+            .map_err(|_| DesktopFileError::InvalidFormat)?;
+        let startup_wm_class = content_section
+            .attr("StartupWMClass")
+            .map(|s| s.to_string());
+
         Ok(ApplicationDesktopFile {
             name,
+            generic_name,
+            comment,
             icon,
             keywords,
+            actions,
+            exec,
+            try_exec,
+            path,
+            terminal,
+            startup_wm_class,
+            location: value.clone(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn desktop_file(exec: &str) -> ApplicationDesktopFile {
+        ApplicationDesktopFile {
+            name: "Editor".to_string(),
+            generic_name: None,
+            comment: None,
+            icon: Some("editor-icon".to_string()),
+            keywords: vec![],
+            actions: vec![],
+            exec: Some(exec.to_string()),
+            try_exec: None,
+            path: None,
+            terminal: false,
+            startup_wm_class: None,
+            location: PathBuf::from("/usr/share/applications/editor.desktop"),
+        }
+    }
+
+    fn arguments(exec: &str, target: Option<&str>) -> anyhow::Result<Vec<String>> {
+        desktop_file(exec).arguments(None, target)
+    }
+
+    #[test]
+    fn quoted_arguments() {
+        // Backslashes are unescaped as a string value first, so a literal one takes four
+        assert_eq!(
+            arguments(r#"sh -c "echo \"a\" \`b\` \$c \\\\d""#, None).unwrap(),
+            vec!["sh", "-c", r#"echo "a" `b` $c \d"#]
+        );
+        assert_eq!(
+            arguments(r#"editor "two words"  spaced"#, None).unwrap(),
+            vec!["editor", "two words", "spaced"]
+        );
+    }
+
+    #[test]
+    fn space_escape() {
+        assert_eq!(
+            arguments(r#"editor "a\sb""#, None).unwrap(),
+            vec!["editor", "a b"]
+        );
+        assert_eq!(
+            arguments(r"editor a\sb", None).unwrap(),
+            vec!["editor", "a", "b"]
+        );
+    }
+
+    #[test]
+    fn percent_escape() {
+        assert_eq!(
+            arguments("printf 100%%", None).unwrap(),
+            vec!["printf", "100%"]
+        );
+    }
+
+    #[test]
+    fn list_field_codes() {
+        for code in ["%F", "%U"] {
+            let exec = format!("editor {}", code);
+            assert_eq!(
+                arguments(&exec, Some("/tmp/file")).unwrap(),
+                vec!["editor", "/tmp/file"]
+            );
+            assert_eq!(arguments(&exec, None).unwrap(), vec!["editor"]);
+        }
+    }
+
+    #[test]
+    fn single_field_codes() {
+        assert_eq!(
+            arguments("editor --open=%f", Some("/tmp/file")).unwrap(),
+            vec!["editor", "--open=/tmp/file"]
+        );
+        assert_eq!(arguments("editor %u", None).unwrap(), vec!["editor"]);
+    }
+
+    #[test]
+    fn icon_name_and_location() {
+        assert_eq!(
+            arguments("editor %i --title=%c %k", None).unwrap(),
+            vec![
+                "editor",
+                "--icon",
+                "editor-icon",
+                "--title=Editor",
+                "/usr/share/applications/editor.desktop"
+            ]
+        );
+        let mut file = desktop_file("editor %i");
+        file.icon = None;
+        assert_eq!(file.arguments(None, None).unwrap(), vec!["editor"]);
+    }
+
+    #[test]
+    fn unterminated_quote() {
+        assert!(arguments(r#"editor "open"#, None).is_err());
+        assert!(arguments(r#"editor "open\"#, None).is_err());
+    }
+
+    #[test]
+    fn desktop_actions() {
+        let mut file = desktop_file("editor");
+        file.actions.push(DesktopAction {
+            id: "new-window".to_string(),
+            name: "New Window".to_string(),
+            exec: Some("editor --new-window".to_string()),
+        });
+        assert_eq!(
+            file.arguments(Some("new-window"), None).unwrap(),
+            vec!["editor", "--new-window"]
+        );
+        assert!(file.arguments(Some("missing"), None).is_err());
+    }
+
+    #[test]
+    fn target_without_field_codes() {
+        let err = arguments("editor --new", Some("/tmp/file")).unwrap_err();
+        assert_eq!(err.to_string(), "Editor can't open files or URLs");
+        assert_eq!(
+            arguments("editor --new", None).unwrap(),
+            vec!["editor", "--new"]
+        );
+    }
+}
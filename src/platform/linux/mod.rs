@@ -12,12 +12,107 @@
 mod desktop_file;
 
 use crate::components::shared::{Icon, Img};
+use crate::date::get_offset;
 use crate::paths::paths;
+use crate::window::Window;
 
+use anyhow::anyhow;
+use async_std::task::spawn_blocking;
+use gpui::{Bounds, ClipboardItem, GlobalPixels, Point, Size, WindowContext};
+use log::error;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use time::{format_description, OffsetDateTime};
+use url::Url;
 
-use super::AppData;
+use super::{AppAction, AppData, ClipboardWatcher};
+
+// Application folders in order of precedence. Flatpak, Snap and Nix usually extend $XDG_DATA_DIRS
+// through their profile scripts, but those aren't sourced by every session so they are appended as a fallback.
+// User installed Flatpaks come right after $XDG_DATA_HOME, so they aren't shadowed by system ones.
+fn application_dirs() -> &'static Vec<PathBuf> {
+    static DIRS: OnceLock<Vec<PathBuf>> = OnceLock::new();
+    DIRS.get_or_init(|| {
+        let paths = paths();
+        let username = whoami::username();
+        let mut data_dirs = paths.data_dirs.clone();
+        data_dirs.insert(1, paths.data_dirs[0].join("flatpak/exports/share"));
+        data_dirs.append(&mut vec![
+            PathBuf::from("/var/lib/flatpak/exports/share"),
+            PathBuf::from("/var/lib/snapd/desktop"),
+            paths.home.join(".nix-profile/share"),
+            PathBuf::from("/etc/profiles/per-user")
+                .join(username)
+                .join("share"),
+            PathBuf::from("/nix/var/nix/profiles/default/share"),
+            PathBuf::from("/run/current-system/sw/share"),
+        ]);
+        let mut seen = HashSet::new();
+        data_dirs
+            .into_iter()
+            .map(|dir| dir.join("applications"))
+            .filter(|dir| seen.insert(dir.clone()))
+            .collect()
+    })
+}
+
+// The desktop file ID is the path relative to the applications folder with '/' replaced by '-'
+fn desktop_file_id(path: &Path) -> Option<String> {
+    let relative = application_dirs()
+        .iter()
+        .find_map(|dir| path.strip_prefix(dir).ok())
+        .unwrap_or(Path::new(path.file_name()?));
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("-"),
+    )
+}
+
+fn find_desktop_files(dir: &Path, ids: &mut HashSet<String>, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            find_desktop_files(&path, ids, files);
+        } else if path.extension().is_some_and(|ext| ext == "desktop") {
+            // Only the first desktop file with a given ID is used, the rest are shadowed by it
+            if let Some(id) = desktop_file_id(&path) {
+                if ids.insert(id) {
+                    files.push(path);
+                }
+            }
+        }
+    }
+}
+
+pub fn get_application_folders() -> Vec<PathBuf> {
+    application_dirs().clone()
+}
+
+pub fn get_application_files() -> Vec<PathBuf> {
+    let mut ids = HashSet::new();
+    let mut files = Vec::new();
+    for dir in application_dirs() {
+        find_desktop_files(dir, &mut ids, &mut files);
+    }
+    files
+}
+
+/// Whatever the data of an application depends on besides its desktop file.
+pub fn get_application_environment() -> String {
+    desktop_file::environment()
+}
 
 pub fn get_application_data(path: &PathBuf) -> Option<AppData> {
     let cache_dir = paths().cache.join("apps");
@@ -25,12 +120,7 @@ pub fn get_application_data(path: &PathBuf) -> Option<AppData> {
         fs::create_dir_all(cache_dir.clone()).unwrap();
     }
     let cache = cache_dir.to_string_lossy().to_string();
-    let last = path.components().last();
-    if last.is_none() {
-        return None;
-    }
-
-    let file_name = last.unwrap().as_os_str().to_string_lossy().to_string();
+    let id = desktop_file_id(path)?;
 
     let file = desktop_file::ApplicationDesktopFile::try_from(path).ok()?;
     let icon_url: Option<PathBuf> = file.resolve_icon();
@@ -42,15 +132,400 @@ pub fn get_application_data(path: &PathBuf) -> Option<AppData> {
     };
 
     Some(AppData {
-        id: file_name.clone(),
+        id,
         name: file.name.clone(),
+        description: file.generic_name.clone().or(file.comment.clone()),
         icon: icon_img,
         icon_path: icon_url.unwrap_or_else(|| PathBuf::new()),
-        keywords: file.keywords,
+        keywords: {
+            let mut keywords = file.keywords;
+            keywords.extend(file.generic_name);
+            keywords.extend(file.comment);
+            keywords
+        },
+        actions: file
+            .actions
+            .into_iter()
+            .map(|action| AppAction {
+                id: action.id,
+                name: action.name,
+            })
+            .collect(),
         tag: "Application".to_string(),
     })
 }
 
+// Terminal emulators tried in order when neither the preferences nor $TERMINAL set one, along with the flag that precedes the command
+static TERMINALS: &[(&str, &str)] = &[
+    ("x-terminal-emulator", "-e"),
+    ("gnome-terminal", "--"),
+    ("konsole", "-e"),
+    ("xfce4-terminal", "-x"),
+    ("alacritty", "-e"),
+    ("kitty", "--"),
+    ("foot", "--"),
+    ("wezterm", "start"),
+    ("xterm", "-e"),
+];
+
+pub(crate) fn find_executable(name: &str) -> Option<PathBuf> {
+    if name.contains('/') {
+        let path = PathBuf::from(name);
+        return path.is_file().then_some(path);
+    }
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+fn terminal_command(terminal: Option<&str>) -> anyhow::Result<Vec<String>> {
+    if let Some(terminal) = terminal.filter(|terminal| !terminal.trim().is_empty()) {
+        return Ok(terminal.split_whitespace().map(String::from).collect());
+    }
+    if let Ok(terminal) = std::env::var("TERMINAL") {
+        if !terminal.is_empty() {
+            return Ok(vec![terminal, "-e".to_string()]);
+        }
+    }
+    TERMINALS
+        .iter()
+        .find(|(terminal, _)| find_executable(terminal).is_some())
+        .map(|(terminal, flag)| vec![terminal.to_string(), flag.to_string()])
+        .ok_or_else(|| anyhow!("No terminal emulator found, set one in the preferences"))
+}
+
+fn spawn(args: &[String], dir: Option<PathBuf>) -> anyhow::Result<()> {
+    let mut command = Command::new(&args[0]);
+    command
+        .args(&args[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(dir) = dir.filter(|dir| dir.is_dir()) {
+        command.current_dir(dir);
+    }
+    let mut child = command.spawn()?;
+    // Reap the child once it exits so it doesn't linger as a zombie
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Terminal applications run in `terminal` if set, e.g. `kitty --`. `target` is a file or URL to open with it.
+pub fn launch_application(
+    path: &PathBuf,
+    action: Option<&str>,
+    target: Option<&str>,
+    terminal: Option<&str>,
+) -> anyhow::Result<()> {
+    let file = desktop_file::ApplicationDesktopFile::try_from(path)
+        .map_err(|_| anyhow!("Failed to parse {}", path.display()))?;
+    let mut args = file.arguments(action, target)?;
+    if file.terminal {
+        let mut terminal = terminal_command(terminal)?;
+        terminal.append(&mut args);
+        args = terminal;
+    }
+    spawn(&args, file.path)
+}
+
+/// Opens a file or URL with the application with the given ID.
+pub fn open_with(id: &str, target: &str, terminal: Option<&str>) -> anyhow::Result<()> {
+    let path = get_application_files()
+        .into_iter()
+        .find(|path| desktop_file_id(path).as_deref() == Some(id))
+        .ok_or_else(|| anyhow!("Application {} not found", id))?;
+    launch_application(&path, None, Some(target), terminal)
+}
+
+/// Selects a file or folder in the file manager, or opens its folder if the file manager can't select it.
+pub fn reveal_in_file_manager(path: &Path) -> anyhow::Result<()> {
+    let uri = Url::from_file_path(path).map_err(|_| anyhow!("Invalid path {}", path.display()))?;
+    let shown = zbus::blocking::Connection::session().and_then(|connection| {
+        connection.call_method(
+            Some("org.freedesktop.FileManager1"),
+            "/org/freedesktop/FileManager1",
+            Some("org.freedesktop.FileManager1"),
+            "ShowItems",
+            &(vec![uri.as_str()], ""),
+        )
+    });
+    if let Err(err) = shown {
+        error!(
+            "FileManager1 unavailable, opening the folder instead: {}",
+            err
+        );
+        let folder = path.parent().unwrap_or(path);
+        spawn(
+            &["xdg-open".to_string(), folder.to_string_lossy().to_string()],
+            None,
+        )?;
+    }
+    Ok(())
+}
+
+// Files go to the trash of the filesystem they are on, the home trash or `$topdir/.Trash-$uid`
+fn trash_dir(path: &Path) -> anyhow::Result<PathBuf> {
+    let home_trash = paths().data_dirs[0].join("Trash");
+    let device = fs::symlink_metadata(path)?.dev();
+    let home_device = home_trash
+        .ancestors()
+        .find_map(|dir| fs::metadata(dir).ok())
+        .map(|metadata| metadata.dev());
+    if home_device == Some(device) {
+        return Ok(home_trash);
+    }
+    let mut top = path;
+    while let Some(parent) = top.parent() {
+        if fs::metadata(parent)?.dev() != device {
+            break;
+        }
+        top = parent;
+    }
+    // /proc/self is owned by the user the process runs as
+    let uid = fs::metadata("/proc/self")?.uid();
+    Ok(top.join(format!(".Trash-{}", uid)))
+}
+
+/// Moves a file or folder to the trash following the freedesktop trash spec, so file managers can restore it.
+pub fn move_to_trash(path: &Path) -> anyhow::Result<()> {
+    let trash = trash_dir(path)?;
+    let files = trash.join("files");
+    let info = trash.join("info");
+    fs::create_dir_all(&files)?;
+    fs::create_dir_all(&info)?;
+    let name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Can't trash {}", path.display()))?
+        .to_string_lossy()
+        .to_string();
+    // Creating the info file claims the name, since there might be a trashed file with the same name already
+    let mut trashed = name.clone();
+    let mut count = 1;
+    let (info_path, mut info_file) = loop {
+        let info_path = info.join(format!("{}.trashinfo", trashed));
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(file) => break (info_path, file),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                count += 1;
+                trashed = format!("{}.{}", name, count);
+            }
+            Err(err) => return Err(err.into()),
+        }
+    };
+    let uri = Url::from_file_path(path).map_err(|_| anyhow!("Invalid path {}", path.display()))?;
+    let format = format_description::parse("[year]-[month]-[day]T[hour]:[minute]:[second]")?;
+    let date = OffsetDateTime::now_utc()
+        .checked_add(time::Duration::seconds(*get_offset() as i64))
+        .unwrap_or(OffsetDateTime::now_utc())
+        .format(&format)?;
+    let written = write!(
+        info_file,
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        uri.path(),
+        date
+    );
+    if let Err(err) = written.map_err(anyhow::Error::from).and_then(|_| {
+        fs::rename(path, files.join(&trashed))?;
+        Ok(())
+    }) {
+        let _ = fs::remove_file(info_path);
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// Runs a shell command in `terminal` if set, or in the first terminal emulator found.
+pub fn run_in_terminal(command: &str, terminal: Option<&str>) -> anyhow::Result<()> {
+    let mut args = terminal_command(terminal)?;
+    args.extend(["sh", "-c", command].map(String::from));
+    spawn(&args, None)
+}
+
+// Desktop files by the window class of their windows, which is either set as StartupWMClass or matches the ID
+fn find_desktop_file(class: &str) -> Option<PathBuf> {
+    get_application_files().into_iter().find(|path| {
+        let Ok(file) = desktop_file::ApplicationDesktopFile::try_from(path) else {
+            return false;
+        };
+        match file.startup_wm_class {
+            Some(wm_class) => wm_class.eq_ignore_ascii_case(class),
+            None => desktop_file_id(path)
+                .is_some_and(|id| id.trim_end_matches(".desktop").eq_ignore_ascii_case(class)),
+        }
+    })
+}
+
+/// The application of the active window, only on X11 since Wayland doesn't expose it to clients.
 pub fn get_frontmost_application_data() -> Option<AppData> {
-    None
+    // Each expanded keyword looks it up, so the desktop file lookup is only done once per window class
+    static APPS: OnceLock<Mutex<HashMap<String, Option<AppData>>>> = OnceLock::new();
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        return None;
+    }
+    let output = Command::new("xdotool")
+        .args(["getactivewindow", "getwindowclassname"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let class = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let mut apps = APPS.get_or_init(Default::default).lock().ok()?;
+    apps.entry(class.clone())
+        .or_insert_with(|| get_application_data(&find_desktop_file(&class)?))
+        .clone()
+}
+
+// Key presses in xdotool's syntax like `ctrl+v`, which wtype gets as separate modifiers on Wayland
+fn send_keys(keys: &[&str]) -> anyhow::Result<()> {
+    let status = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let mut args = vec![];
+        for key in keys {
+            let mut modifiers: Vec<&str> = key.split('+').collect();
+            let key = modifiers.pop().unwrap_or_default();
+            for modifier in &modifiers {
+                args.extend(["-M", *modifier]);
+            }
+            args.extend(["-k", key]);
+            for modifier in modifiers.iter().rev() {
+                args.extend(["-m", *modifier]);
+            }
+        }
+        Command::new("wtype").args(args).status()?
+    } else {
+        Command::new("xdotool")
+            .args(["key", "--clearmodifiers"])
+            .args(keys)
+            .status()?
+    };
+    if !status.success() {
+        return Err(anyhow!("Failed to send keys {}", keys.join(" ")));
+    }
+    Ok(())
+}
+
+pub fn close_and_paste(value: &str, formatting: bool, cx: &mut WindowContext) {
+    close_and_paste_at(value, formatting, 0, cx);
+}
+
+/// Pastes through the clipboard once the window is closed, then moves the cursor back by `left` characters.
+/// The clipboard only holds plain text, so `formatting` has no effect.
+pub fn close_and_paste_at(value: &str, _formatting: bool, left: usize, cx: &mut WindowContext) {
+    Window::close(cx);
+    let value = value.to_string();
+    cx.spawn(move |mut cx| async move {
+        Window::wait_for_close(&mut cx).await;
+        ClipboardWatcher::disabled(&mut cx);
+        let _ = cx.update(|cx| cx.write_to_clipboard(ClipboardItem::new(value)));
+        let mut keys = vec!["ctrl+v"];
+        keys.extend(std::iter::repeat("Left").take(left));
+        if let Err(err) = send_keys(&keys) {
+            error!("Failed to paste: {}", err);
+        }
+    })
+    .detach();
+}
+
+// Wayland doesn't expose global positions to clients, and xdotool only sees XWayland windows there
+fn xdotool(args: &[&str]) -> Option<HashMap<String, f64>> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        return None;
+    }
+    let output = Command::new("xdotool").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                Some((key.to_string(), value.parse().ok()?))
+            })
+            .collect(),
+    )
+}
+
+pub fn get_cursor_position() -> Option<Point<GlobalPixels>> {
+    let values = xdotool(&["getmouselocation", "--shell"])?;
+    Some(Point::new(
+        GlobalPixels::from(*values.get("X")?),
+        GlobalPixels::from(*values.get("Y")?),
+    ))
+}
+
+// Reads the output of `getwindowgeometry --shell`
+fn geometry(values: HashMap<String, f64>) -> Option<Bounds<GlobalPixels>> {
+    Some(Bounds::new(
+        Point::new(
+            GlobalPixels::from(*values.get("X")?),
+            GlobalPixels::from(*values.get("Y")?),
+        ),
+        Size {
+            width: GlobalPixels::from(*values.get("WIDTH")?),
+            height: GlobalPixels::from(*values.get("HEIGHT")?),
+        },
+    ))
+}
+
+pub fn get_focused_window_bounds() -> Option<Bounds<GlobalPixels>> {
+    geometry(xdotool(&[
+        "getactivewindow",
+        "getwindowgeometry",
+        "--shell",
+    ])?)
+}
+
+fn main_window() -> &'static OnceLock<String> {
+    static WINDOW: OnceLock<String> = OnceLock::new();
+    &WINDOW
+}
+
+/// Remembers the main window while it is the only window, so it can be moved and resized later.
+/// Whether it can be resized by the user is up to the window manager, `resizable` isn't supported here.
+pub fn register_main_window(_resizable: bool) {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        return;
+    }
+    let pid = std::process::id().to_string();
+    let Ok(output) = Command::new("xdotool")
+        .args(["search", "--pid", &pid])
+        .output()
+    else {
+        return;
+    };
+    if let Some(id) = String::from_utf8_lossy(&output.stdout).lines().next() {
+        let _ = main_window().set(id.to_string());
+    }
+}
+
+/// Where the main window is right now, the user might have moved it.
+pub async fn get_window_bounds() -> Option<Bounds<GlobalPixels>> {
+    let id = main_window().get()?;
+    spawn_blocking(move || geometry(xdotool(&["getwindowgeometry", "--shell", id])?)).await
+}
+
+/// Whether the main window can be resized after it opened, which compact windows need.
+/// Wayland compositors size windows themselves, so it only works on X11.
+pub fn can_resize_window() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_none()
+}
+
+/// Moves and resizes the main window, since GPUI can't once it is open.
+pub fn set_window_bounds(bounds: Bounds<GlobalPixels>) {
+    let Some(id) = main_window().get() else {
+        return;
+    };
+    let x = f64::from(bounds.origin.x).to_string();
+    let y = f64::from(bounds.origin.y).to_string();
+    let width = f64::from(bounds.size.width).to_string();
+    let height = f64::from(bounds.size.height).to_string();
+    let _ = Command::new("xdotool")
+        .args(["windowmove", id, &x, &y, "windowsize", id, &width, &height])
+        .output();
 }
@@ -12,9 +12,13 @@
 use crate::components::shared::Img;
 use crate::paths::paths;
 use crate::window::Window;
-use gpui::WindowContext;
-use std::{fs, path::Path};
-use swift_rs::{swift, Bool, SRObject, SRString};
+use gpui::{Bounds, GlobalPixels, Point, Size, WindowContext};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use swift_rs::{swift, Bool, Int, SRObject, SRString};
 
 use super::{AppData, ClipboardWatcher};
 
@@ -24,6 +28,58 @@ struct AppDataMac {
     name: SRString,
 }
 
+#[repr(C)]
+struct FrameMac {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl FrameMac {
+    fn bounds(&self) -> Bounds<GlobalPixels> {
+        Bounds::new(
+            Point::new(GlobalPixels::from(self.x), GlobalPixels::from(self.y)),
+            Size {
+                width: GlobalPixels::from(self.width),
+                height: GlobalPixels::from(self.height),
+            },
+        )
+    }
+}
+
+pub fn get_application_folders() -> Vec<PathBuf> {
+    let user_dir = paths().home.join("Applications");
+    vec![
+        PathBuf::from("/Applications"),
+        PathBuf::from("/Applications/Chromium Apps"),
+        PathBuf::from("/System/Applications/Utilities"),
+        PathBuf::from("/System/Applications"),
+        PathBuf::from("/System/Library/CoreServices/Applications"),
+        PathBuf::from("/Library/PreferencePanes"),
+        PathBuf::from("/System/Library/ExtensionKit/Extensions"),
+        user_dir.clone(),
+        user_dir.clone().join("Chromium Apps.localized"),
+        // Not sure about the correct path for PWAs
+        user_dir.clone().join("Chrome Apps.localized"),
+        user_dir.clone().join("Brave Apps.localized"),
+    ]
+}
+
+pub fn get_application_files() -> Vec<PathBuf> {
+    get_application_folders()
+        .into_iter()
+        .filter_map(|folder| folder.read_dir().ok())
+        .flat_map(|dir| dir.flatten().map(|entry| entry.path()))
+        .collect()
+}
+
+/// Whatever the data of an application depends on besides its bundle.
+pub fn get_application_environment() -> String {
+    // Bundles are read the same way in every session
+    String::new()
+}
+
 pub fn get_application_data(path: &Path) -> Option<AppData> {
     let cache_dir = paths().cache.join("apps");
     if !cache_dir.exists() {
@@ -53,9 +109,11 @@ pub fn get_application_data(path: &Path) -> Option<AppData> {
         AppData {
             id: data.id.to_string(),
             name: data.name.to_string(),
+            description: None,
             icon: Img::default().file(icon_path.clone()),
             icon_path,
             keywords: vec![],
+            actions: vec![],
             tag: tag.to_string(),
         }
     })
@@ -73,9 +131,11 @@ pub fn get_frontmost_application_data() -> Option<AppData> {
         AppData {
             id: data.id.to_string(),
             name: data.name.to_string(),
+            description: None,
             icon: Img::default().file(icon_path.clone()),
             icon_path,
             keywords: vec![],
+            actions: vec![],
             tag: "".to_string(),
         }
     })
@@ -86,8 +146,14 @@ swift!( fn paste(value: SRString, formatting: Bool));
 swift!( fn copy_file(path: SRString));
 
 swift!( fn paste_file(path: SRString));
+swift!( fn press_left(count: Int));
 
 pub fn close_and_paste(value: &str, formatting: bool, cx: &mut WindowContext) {
+    close_and_paste_at(value, formatting, 0, cx);
+}
+
+/// Pastes like `close_and_paste`, then moves the cursor back by `left` characters.
+pub fn close_and_paste_at(value: &str, formatting: bool, left: usize, cx: &mut WindowContext) {
     Window::close(cx);
     let value = value.to_string();
     cx.spawn(move |mut cx| async move {
@@ -95,6 +161,9 @@ pub fn close_and_paste(value: &str, formatting: bool, cx: &mut WindowContext) {
         ClipboardWatcher::disabled(&mut cx);
         unsafe {
             paste(SRString::from(value.as_str()), Bool::from(formatting));
+            if left > 0 {
+                press_left(left as Int);
+            }
         }
     })
     .detach();
@@ -130,3 +199,86 @@ pub fn ocr(path: &Path) {
     swift!( fn ocr(path: SRString));
     unsafe { ocr(SRString::from(path.to_string_lossy().to_string().as_str())) }
 }
+
+pub fn get_cursor_position() -> Option<Point<GlobalPixels>> {
+    swift!( fn get_cursor_position() -> SRObject<FrameMac>);
+    Some(unsafe { get_cursor_position() }.bounds().origin)
+}
+
+pub fn get_focused_window_bounds() -> Option<Bounds<GlobalPixels>> {
+    swift!( fn get_focused_window_frame() -> Option<SRObject<FrameMac>>);
+    unsafe { get_focused_window_frame() }.map(|frame| frame.bounds())
+}
+
+/// Remembers the main window while it is the only window, so it can be moved and resized later.
+pub fn register_main_window(resizable: bool) {
+    swift!( fn register_main_window(resizable: Bool));
+    unsafe { register_main_window(Bool::from(resizable)) }
+}
+
+/// Where the main window is right now, the user might have moved it.
+pub async fn get_window_bounds() -> Option<Bounds<GlobalPixels>> {
+    swift!( fn get_window_frame() -> Option<SRObject<FrameMac>>);
+    unsafe { get_window_frame() }.map(|frame| frame.bounds())
+}
+
+/// Whether the main window can be resized after it opened, which compact windows need.
+pub fn can_resize_window() -> bool {
+    true
+}
+
+/// Moves and resizes the main window, since GPUI can't once it is open.
+pub fn set_window_bounds(bounds: Bounds<GlobalPixels>) {
+    swift!( fn set_window_frame(x: f64, y: f64, width: f64, height: f64));
+    unsafe {
+        set_window_frame(
+            f64::from(bounds.origin.x),
+            f64::from(bounds.origin.y),
+            f64::from(bounds.size.width),
+            f64::from(bounds.size.height),
+        )
+    }
+}
+
+/// Opens a file or URL with the application with the given bundle ID.
+pub fn open_with(id: &str, target: &str, _terminal: Option<&str>) -> anyhow::Result<()> {
+    Command::new("open").args(["-b", id, target]).spawn()?;
+    Ok(())
+}
+
+/// Selects a file or folder in a new Finder window.
+pub fn reveal_in_file_manager(path: &Path) -> anyhow::Result<()> {
+    Command::new("open").arg("-R").arg(path).spawn()?;
+    Ok(())
+}
+
+/// Moves a file or folder to the trash through Finder, so it can be put back from there.
+pub fn move_to_trash(path: &Path) -> anyhow::Result<()> {
+    let path = path
+        .to_string_lossy()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    let script = format!(
+        "tell application \"Finder\" to delete POSIX file \"{}\"",
+        path
+    );
+    let output = Command::new("osascript").args(["-e", &script]).output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "{}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Runs a shell command in a new Terminal window.
+pub fn run_in_terminal(command: &str, _terminal: Option<&str>) -> anyhow::Result<()> {
+    let command = command.replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        "tell application \"Terminal\"\n\tactivate\n\tdo script \"{}\"\nend tell",
+        command
+    );
+    Command::new("osascript").args(["-e", &script]).spawn()?;
+    Ok(())
+}
@@ -11,24 +11,41 @@
 
 use crate::components::shared::Img;
 use gpui::{AppContext, AsyncAppContext, Global};
+use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "expansion")]
+pub mod expansion;
 #[cfg(target_os = "linux")]
 mod linux;
 #[cfg(target_os = "linux")]
 pub use linux::*;
-use std::path::PathBuf;
+use std::{
+    io::Read,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
 #[cfg(target_os = "macos")]
 mod mac;
 #[cfg(target_os = "macos")]
 pub use mac::*;
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AppAction {
+    pub id: String,
+    pub name: String,
+}
+
 #[derive(Clone)]
 pub struct AppData {
     pub id: String,
     pub name: String,
+    pub description: Option<String>,
     pub icon: Img,
     pub icon_path: PathBuf,
     pub keywords: Vec<String>,
+    pub actions: Vec<AppAction>,
     pub tag: String,
 }
 
@@ -45,6 +62,10 @@ impl ClipboardWatcher {
         });
     }
     pub fn disabled(cx: &mut AsyncAppContext) {
+        // Only exists with the clipboard feature, but pasting works without it
+        if cx.try_read_global::<Self, _>(|_, _| ()).is_none() {
+            return;
+        }
         let _ = cx.update_global::<Self, _>(|this, _| {
             this.enabled = false;
         });
@@ -55,3 +76,43 @@ impl ClipboardWatcher {
     }
 }
 impl Global for ClipboardWatcher {}
+
+// Pipes are drained while the process runs, it would block on a full one otherwise
+fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = vec![];
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Runs the command to completion like `Command::output`, but kills it once it takes longer than `timeout`.
+pub fn output_with_timeout(command: &mut Command, timeout: Duration) -> anyhow::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() > timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            // Processes it started can hold on to the pipes, so the readers are left behind
+            return Err(anyhow::anyhow!("Timed out after {:?}", timeout));
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
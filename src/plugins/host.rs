@@ -0,0 +1,423 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{
+    fs,
+    path::{Component, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use async_std::{future::timeout, task::block_on};
+use reqwest::{redirect, Client};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use wasmtime::{StoreLimits, StoreLimitsBuilder};
+
+use crate::{components::shared::Icon, paths::paths, platform::output_with_timeout};
+
+use super::bindings::{ListView, Output, PluginImports, ToastStyle};
+
+// Plugins can't grow their memory beyond this
+static MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+// Requests have to finish within this, including following redirects
+static REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+static MAX_REDIRECTS: usize = 5;
+// Programs are killed after this, the plugin can't be interrupted while it waits for them
+static PROCESS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Capabilities a plugin asks for in its `plugin.toml`, they are granted by enabling the plugin.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Permissions {
+    /// Hosts that can be requested, `*` allows any host
+    #[serde(default)]
+    pub network: Vec<String>,
+    #[serde(default)]
+    pub clipboard: bool,
+    /// Files and folders that can be read and written, `~` is the home folder
+    #[serde(default)]
+    pub filesystem: Vec<String>,
+    /// Names of the programs that can be run
+    #[serde(default)]
+    pub process: Vec<String>,
+}
+
+fn expand(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(path) => paths().home.join(path),
+        None => PathBuf::from(path),
+    }
+}
+
+impl Permissions {
+    pub fn describe(&self) -> Vec<(Icon, String)> {
+        let mut permissions = vec![];
+        for host in &self.network {
+            let host = if host == "*" { "any host" } else { host };
+            permissions.push((Icon::Globe, format!("Access {}", host)));
+        }
+        if self.clipboard {
+            permissions.push((Icon::Clipboard, "Read and write the clipboard".to_string()));
+        }
+        for path in &self.filesystem {
+            permissions.push((Icon::Folder, format!("Read and write {}", path)));
+        }
+        for program in &self.process {
+            permissions.push((Icon::Terminal, format!("Run {}", program)));
+        }
+        permissions
+    }
+    /// Whether everything in `requested` was already granted by these.
+    pub fn covers(&self, requested: &Permissions) -> bool {
+        requested
+            .network
+            .iter()
+            .all(|host| self.network.iter().any(|h| h == "*" || h == host))
+            && (!requested.clipboard || self.clipboard)
+            && requested
+                .filesystem
+                .iter()
+                .all(|path| self.filesystem.contains(path))
+            && requested
+                .process
+                .iter()
+                .all(|program| self.process.contains(program))
+    }
+    fn check_url(&self, url: &str) -> Result<Url, String> {
+        let url = Url::parse(url).map_err(|err| err.to_string())?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("Unsupported scheme {}", url.scheme()));
+        }
+        let host = url.host_str().unwrap_or_default();
+        if !self.network.iter().any(|h| h == "*" || h == host) {
+            return Err(format!("No permission to access {}", host));
+        }
+        Ok(url)
+    }
+    fn check_path(&self, path: &str) -> Result<PathBuf, String> {
+        let path = expand(path);
+        if !path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+            return Err(format!("Invalid path {}", path.display()));
+        }
+        // Symlinks could point anywhere, so what they resolve to has to be granted. Files that don't
+        // exist yet are resolved through their folder, which has to exist.
+        let invalid = || format!("Invalid path {}", path.display());
+        let resolved = if path.symlink_metadata().is_ok() {
+            path.canonicalize().map_err(|_| invalid())?
+        } else {
+            let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+                return Err(invalid());
+            };
+            parent.canonicalize().map_err(|_| invalid())?.join(name)
+        };
+        let granted = self.filesystem.iter().any(|granted| {
+            let granted = expand(granted);
+            let granted = granted.canonicalize().unwrap_or(granted);
+            resolved.starts_with(granted)
+        });
+        if !granted {
+            return Err(format!("No permission to access {}", path.display()));
+        }
+        Ok(resolved)
+    }
+    fn client(&self) -> reqwest::Result<Client> {
+        let permissions = self.clone();
+        Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            // Every hop has to go to a granted host
+            .redirect(redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    return attempt.error("Too many redirects");
+                }
+                match permissions.check_url(attempt.url().as_str()) {
+                    Ok(_) => attempt.follow(),
+                    Err(err) => attempt.error(err),
+                }
+            }))
+            .build()
+    }
+    fn get(&self, url: &str) -> Result<String, String> {
+        let url = self.check_url(url)?;
+        let client = self.client().map_err(|err| err.to_string())?;
+        let request = async {
+            client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await
+        };
+        // Bounds the whole call, whatever the client is waiting for
+        block_on(timeout(REQUEST_TIMEOUT, request))
+            .map_err(|_| "Request timed out".to_string())?
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Changes to the user interface requested by a plugin.
+pub enum Effect {
+    Toast(ToastStyle, String),
+    Push(ListView),
+    Update(ListView),
+    Pop,
+    Close,
+    Copy(String),
+}
+
+/// State of a plugin instance, which is everything the plugin can reach.
+pub struct Host {
+    permissions: Permissions,
+    /// Contents of the clipboard when the current call started
+    pub clipboard: Option<String>,
+    pub effects: Vec<Effect>,
+    pub limits: StoreLimits,
+}
+
+impl Host {
+    pub fn new(permissions: Permissions) -> Self {
+        Self {
+            permissions,
+            clipboard: None,
+            effects: vec![],
+            limits: StoreLimitsBuilder::new()
+                .memory_size(MEMORY_LIMIT)
+                .instances(1)
+                .build(),
+        }
+    }
+}
+
+impl PluginImports for Host {
+    fn toast(&mut self, style: ToastStyle, message: String) {
+        self.effects.push(Effect::Toast(style, message));
+    }
+    fn push(&mut self, view: ListView) {
+        self.effects.push(Effect::Push(view));
+    }
+    fn update(&mut self, view: ListView) {
+        self.effects.push(Effect::Update(view));
+    }
+    fn pop(&mut self) {
+        self.effects.push(Effect::Pop);
+    }
+    fn close(&mut self) {
+        self.effects.push(Effect::Close);
+    }
+    fn clipboard_read(&mut self) -> Result<String, String> {
+        if !self.permissions.clipboard {
+            return Err("No permission to access the clipboard".to_string());
+        }
+        Ok(self.clipboard.clone().unwrap_or_default())
+    }
+    fn clipboard_write(&mut self, text: String) -> Result<(), String> {
+        if !self.permissions.clipboard {
+            return Err("No permission to access the clipboard".to_string());
+        }
+        self.effects.push(Effect::Copy(text));
+        Ok(())
+    }
+    fn http_get(&mut self, url: String) -> Result<String, String> {
+        self.permissions.get(&url)
+    }
+    fn read_file(&mut self, path: String) -> Result<String, String> {
+        let path = self.permissions.check_path(&path)?;
+        fs::read_to_string(path).map_err(|err| err.to_string())
+    }
+    fn write_file(&mut self, path: String, contents: String) -> Result<(), String> {
+        let path = self.permissions.check_path(&path)?;
+        fs::write(path, contents).map_err(|err| err.to_string())
+    }
+    fn spawn(&mut self, program: String, args: Vec<String>) -> Result<Output, String> {
+        if !self.permissions.process.contains(&program) {
+            return Err(format!("No permission to run {}", program));
+        }
+        let output = output_with_timeout(Command::new(&program).args(args), PROCESS_TIMEOUT)
+            .map_err(|err| err.to_string())?;
+        Ok(Output {
+            status: output.status.code().unwrap_or(-1),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        os::unix::fs::symlink,
+        thread,
+    };
+
+    use super::*;
+
+    // A folder with a granted and a secret subfolder
+    struct Fixture {
+        dir: PathBuf,
+        permissions: Permissions,
+    }
+
+    impl Fixture {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("loungy-plugin-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            // The temporary folder can be behind a symlink itself
+            let dir = dir.canonicalize().unwrap();
+            fs::create_dir_all(dir.join("granted")).unwrap();
+            fs::create_dir_all(dir.join("secret")).unwrap();
+            fs::write(dir.join("secret/key"), "secret").unwrap();
+            fs::write(dir.join("granted/notes"), "notes").unwrap();
+            let permissions = Permissions {
+                filesystem: vec![dir.join("granted").to_string_lossy().to_string()],
+                ..Default::default()
+            };
+            Self { dir, permissions }
+        }
+        fn check(&self, path: &str) -> Result<PathBuf, String> {
+            self.permissions
+                .check_path(&self.dir.join(path).to_string_lossy())
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[test]
+    fn paths_in_granted_folders() {
+        let fixture = Fixture::new("paths");
+        assert_eq!(
+            fixture.check("granted/notes"),
+            Ok(fixture.dir.join("granted/notes"))
+        );
+        // Files are created through their folder
+        assert_eq!(
+            fixture.check("granted/new"),
+            Ok(fixture.dir.join("granted/new"))
+        );
+        assert!(fixture.check("granted/missing/new").is_err());
+        assert!(fixture.check("secret/key").is_err());
+        assert!(fixture.check("granted/../secret/key").is_err());
+        assert!(fixture.permissions.check_path("granted/notes").is_err());
+    }
+
+    #[test]
+    fn symlinks_resolve_before_checking() {
+        let fixture = Fixture::new("symlinks");
+        symlink(
+            fixture.dir.join("secret/key"),
+            fixture.dir.join("granted/key"),
+        )
+        .unwrap();
+        symlink(
+            fixture.dir.join("secret"),
+            fixture.dir.join("granted/secret"),
+        )
+        .unwrap();
+        symlink(fixture.dir.join("granted/notes"), fixture.dir.join("notes")).unwrap();
+        // Dangling symlinks would create the file they point to
+        symlink(
+            fixture.dir.join("secret/new"),
+            fixture.dir.join("granted/dangling"),
+        )
+        .unwrap();
+        assert!(fixture.check("granted/key").is_err());
+        assert!(fixture.check("granted/secret/key").is_err());
+        assert!(fixture.check("granted/secret/new").is_err());
+        assert!(fixture.check("granted/dangling").is_err());
+        assert_eq!(
+            fixture.check("notes"),
+            Ok(fixture.dir.join("granted/notes"))
+        );
+    }
+
+    #[test]
+    fn urls_of_granted_hosts() {
+        let permissions = Permissions {
+            network: vec!["example.com".to_string()],
+            ..Default::default()
+        };
+        assert!(permissions.check_url("https://example.com/a?b=c").is_ok());
+        assert!(permissions.check_url("http://example.com").is_ok());
+        assert!(permissions.check_url("https://api.example.com").is_err());
+        assert!(permissions.check_url("https://example.org").is_err());
+        assert!(permissions.check_url("ftp://example.com").is_err());
+        assert!(permissions.check_url("file:///etc/passwd").is_err());
+        assert!(permissions.check_url("example.com").is_err());
+        let any = Permissions {
+            network: vec!["*".to_string()],
+            ..Default::default()
+        };
+        assert!(any.check_url("https://example.org").is_ok());
+        assert!(any.covers(&permissions));
+        assert!(!permissions.covers(&any));
+    }
+
+    // Answers every request with a redirect to the path, or with its path once there is nowhere left to go
+    fn server(redirects: &'static [(&'static str, &'static str)]) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut line = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                reader.read_line(&mut line).unwrap();
+                // Skips the headers
+                let mut header = String::new();
+                while reader.read_line(&mut header).is_ok_and(|n| n > 2) {
+                    header.clear();
+                }
+                let path = line.split(' ').nth(1).unwrap_or_default().to_string();
+                let response = match redirects.iter().find(|(from, _)| *from == path) {
+                    Some((_, to)) => format!(
+                        "HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\n\r\n",
+                        to.replace("PORT", &port.to_string())
+                    ),
+                    None => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                        path.len(),
+                        path
+                    ),
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        port
+    }
+
+    #[test]
+    fn redirects_stay_on_granted_hosts() {
+        let port = server(&[
+            ("/same", "/final"),
+            ("/away", "http://localhost:PORT/final"),
+            ("/loop", "/loop"),
+        ]);
+        let permissions = Permissions {
+            network: vec!["127.0.0.1".to_string()],
+            ..Default::default()
+        };
+        let url = |path: &str| format!("http://127.0.0.1:{}{}", port, path);
+        assert_eq!(permissions.get(&url("/same")), Ok("/final".to_string()));
+        let err = permissions.get(&url("/away")).unwrap_err();
+        assert!(err.contains("No permission to access localhost"), "{}", err);
+        let err = permissions.get(&url("/loop")).unwrap_err();
+        assert!(err.contains("Too many redirects"), "{}", err);
+        assert!(permissions
+            .get(&format!("http://localhost:{}/final", port))
+            .is_err());
+    }
+}
@@ -0,0 +1,199 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use gpui::*;
+use log::error;
+
+use crate::{
+    commands::{RootCommand, RootCommandBuilder},
+    components::{
+        list::{Accessory, ItemBuilder, ListBuilder, ListItem},
+        shared::{Icon, Img},
+    },
+    state::{Action, Shortcut, StateModel, StateViewBuilder, StateViewContext},
+};
+
+use super::Plugins;
+
+#[derive(Clone)]
+struct PermissionListBuilder {
+    id: String,
+}
+
+impl StateViewBuilder for PermissionListBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        context.query.set_placeholder("Search permissions...", cx);
+        let id = self.id.clone();
+        ListBuilder::new()
+            .build(
+                move |_, _, cx| {
+                    let Some(plugin) = cx.global::<Plugins>().plugins.get(&id) else {
+                        return Ok(Some(vec![]));
+                    };
+                    let granted = if plugin.enabled {
+                        "Granted"
+                    } else {
+                        "Requested"
+                    };
+                    let permissions = plugin.manifest.permissions.describe();
+                    if permissions.is_empty() {
+                        return Ok(Some(vec![ItemBuilder::new(
+                            "none",
+                            ListItem::new(
+                                Some(Img::default().icon(Icon::ShieldCheck)),
+                                "No permissions",
+                                None,
+                                vec![],
+                            ),
+                        )
+                        .build()]));
+                    }
+                    Ok(Some(
+                        permissions
+                            .into_iter()
+                            .map(|(icon, permission)| {
+                                ItemBuilder::new(
+                                    permission.clone(),
+                                    ListItem::new(
+                                        Some(Img::default().icon(icon)),
+                                        permission.clone(),
+                                        None,
+                                        vec![Accessory::new(granted, None)],
+                                    ),
+                                )
+                                .keywords(vec![permission])
+                                .build()
+                            })
+                            .collect(),
+                    ))
+                },
+                context,
+                cx,
+            )
+            .into()
+    }
+}
+
+#[derive(Clone)]
+struct PluginListBuilder;
+
+impl StateViewBuilder for PluginListBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        context.query.set_placeholder("Search plugins...", cx);
+        ListBuilder::new()
+            .build(
+                move |_, _, cx| {
+                    let plugins = &cx.global::<Plugins>().plugins;
+                    Ok(Some(
+                        plugins
+                            .values()
+                            .map(|plugin| {
+                                let id = plugin.manifest.id.clone();
+                                let enabled = plugin.enabled;
+                                let status = match (&plugin.error, enabled) {
+                                    (Some(_), true) => Accessory::new(
+                                        "Failed to load",
+                                        Some(Img::default().icon(Icon::AlertTriangle)),
+                                    ),
+                                    (None, true) => Accessory::new("Enabled", None),
+                                    (_, false) => Accessory::new("Disabled", None),
+                                };
+                                ItemBuilder::new(
+                                    id.clone(),
+                                    ListItem::new(
+                                        Some(Img::default().icon(Icon::Puzzle)),
+                                        plugin.manifest.name.clone(),
+                                        plugin
+                                            .error
+                                            .clone()
+                                            .or(plugin.manifest.description.clone()),
+                                        vec![status],
+                                    ),
+                                )
+                                .keywords(vec![plugin.manifest.name.clone(), id.clone()])
+                                .actions(vec![
+                                    Action::new(
+                                        Img::default().icon(if enabled {
+                                            Icon::ToggleLeft
+                                        } else {
+                                            Icon::ToggleRight
+                                        }),
+                                        if enabled {
+                                            "Disable Plugin"
+                                        } else {
+                                            "Enable Plugin"
+                                        },
+                                        None,
+                                        {
+                                            let id = id.clone();
+                                            move |actions, cx| {
+                                                if let Err(err) =
+                                                    Plugins::set_enabled(&id, !enabled, cx)
+                                                {
+                                                    error!("Failed to update plugin: {}", err);
+                                                    actions
+                                                        .toast
+                                                        .error("Failed to update plugin", cx);
+                                                } else if enabled {
+                                                    actions.toast.success("Plugin disabled", cx);
+                                                } else {
+                                                    actions.toast.success("Plugin enabled", cx);
+                                                }
+                                                actions.update();
+                                            }
+                                        },
+                                        false,
+                                    ),
+                                    Action::new(
+                                        Img::default().icon(Icon::Shield),
+                                        "Show Permissions",
+                                        Some(Shortcut::new("p").cmd()),
+                                        move |_, cx| {
+                                            let id = id.clone();
+                                            StateModel::update(
+                                                |this, cx| {
+                                                    this.push(PermissionListBuilder { id }, cx)
+                                                },
+                                                cx,
+                                            );
+                                        },
+                                        false,
+                                    ),
+                                ])
+                                .build()
+                            })
+                            .collect(),
+                    ))
+                },
+                context,
+                cx,
+            )
+            .into()
+    }
+}
+
+pub struct ManagePluginsCommandBuilder;
+
+impl RootCommandBuilder for ManagePluginsCommandBuilder {
+    fn build(&self, _cx: &mut WindowContext) -> RootCommand {
+        RootCommand::new(
+            "plugins",
+            "Manage Plugins",
+            "Loungy",
+            Icon::Puzzle,
+            vec!["WebAssembly", "Extensions", "Permissions"],
+            None,
+            Box::new(|_, cx| {
+                StateModel::update(|this, cx| this.push(PluginListBuilder, cx), cx);
+            }),
+        )
+    }
+}
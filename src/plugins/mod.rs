@@ -0,0 +1,445 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+    thread,
+    time::Duration,
+};
+
+use async_std::task::spawn_blocking;
+use bonsaidb::{
+    core::schema::{Collection, SerializedCollection},
+    local::Database,
+};
+use gpui::*;
+use log::error;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use wasmtime::{
+    component::{Component, Linker},
+    Config, Engine, Store,
+};
+
+use crate::{
+    commands::{RootCommand, RootCommandBuilder, RootCommands},
+    components::shared::{Icon, Img},
+    db::Db,
+    extensions::icon,
+    hotkey::HotkeyManager,
+    paths::paths,
+    state::{ActionsModel, StateModel},
+    window::Window,
+};
+
+use self::{
+    bindings::{ListView, Plugin, ToastStyle},
+    host::{Effect, Host, Permissions},
+    view::PluginViewBuilder,
+};
+
+mod host;
+pub mod manage;
+mod view;
+
+mod bindings {
+    wasmtime::component::bindgen!({
+        path: "src/plugins/plugin.wit",
+        world: "plugin",
+    });
+}
+
+// The engine ticks this often, a single call into a plugin gets trapped after DEADLINE ticks
+static TICK: Duration = Duration::from_millis(100);
+static DEADLINE: u64 = 50;
+
+#[derive(Deserialize, Clone)]
+pub struct Manifest {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub permissions: Permissions,
+}
+
+#[derive(Clone, Serialize, Deserialize, Collection)]
+#[collection(name = "plugins")]
+struct PluginState {
+    #[natural_id]
+    id: String,
+    enabled: bool,
+    /// What the user agreed to when enabling the plugin
+    #[serde(default)]
+    granted: Permissions,
+}
+
+fn db() -> &'static Database {
+    static DB: OnceLock<Database> = OnceLock::new();
+    DB.get_or_init(Db::init_collection::<PluginState>)
+}
+
+fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut config = Config::new();
+        config.wasm_component_model(true).epoch_interruption(true);
+        let engine = Engine::new(&config).expect("Failed to create WebAssembly engine");
+        let ticker = engine.clone();
+        thread::spawn(move || loop {
+            thread::sleep(TICK);
+            ticker.increment_epoch();
+        });
+        engine
+    })
+}
+
+struct Instance {
+    store: Store<Host>,
+    bindings: Plugin,
+}
+
+fn instantiate(
+    dir: &Path,
+    permissions: Permissions,
+) -> anyhow::Result<(Instance, Vec<bindings::Command>)> {
+    let component = Component::from_file(engine(), dir.join("plugin.wasm"))?;
+    let mut linker = Linker::new(engine());
+    Plugin::add_to_linker(&mut linker, |host: &mut Host| host)?;
+    let mut store = Store::new(engine(), Host::new(permissions));
+    store.limiter(|host| &mut host.limits);
+    store.set_epoch_deadline(DEADLINE);
+    let bindings = Plugin::instantiate(&mut store, &component, &linker)?;
+    let commands = bindings.call_commands(&mut store)?;
+    Ok((Instance { store, bindings }, commands))
+}
+
+pub struct LoadedPlugin {
+    pub manifest: Manifest,
+    dir: PathBuf,
+    pub enabled: bool,
+    instance: Option<Arc<Mutex<Instance>>>,
+    commands: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Clone)]
+struct OpenView {
+    view: WeakModel<ListView>,
+    actions: ActionsModel,
+}
+
+/// Sandboxed WebAssembly plugins from the data folder. Plugins are disabled until they get enabled in
+/// "Manage Plugins", which grants them the permissions they ask for.
+pub struct Plugins {
+    pub plugins: BTreeMap<String, LoadedPlugin>,
+    views: HashMap<(String, String), OpenView>,
+}
+
+impl Plugins {
+    pub fn init(cx: &mut WindowContext) {
+        let states: HashMap<String, PluginState> = PluginState::all(db())
+            .query()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|doc| (doc.contents.id.clone(), doc.contents))
+            .collect();
+        let mut plugins = BTreeMap::new();
+        if let Ok(entries) = paths().data.join("plugins").read_dir() {
+            for entry in entries.flatten() {
+                let dir = entry.path();
+                let manifest = fs::read_to_string(dir.join("plugin.toml"))
+                    .map_err(anyhow::Error::from)
+                    .and_then(|manifest| Ok(toml::from_str::<Manifest>(&manifest)?));
+                match manifest {
+                    Ok(manifest) => {
+                        let state = states.get(&manifest.id);
+                        let enabled = state.is_some_and(|state| state.enabled);
+                        // An update might ask for more than the user agreed to
+                        let granted =
+                            state.is_some_and(|state| state.granted.covers(&manifest.permissions));
+                        plugins.insert(
+                            manifest.id.clone(),
+                            LoadedPlugin {
+                                enabled: enabled && granted,
+                                manifest,
+                                dir,
+                                instance: None,
+                                commands: vec![],
+                                error: (enabled && !granted).then(|| {
+                                    "Asks for new permissions, enable it again to grant them"
+                                        .to_string()
+                                }),
+                            },
+                        );
+                    }
+                    Err(err) => error!("Failed to load plugin {}: {}", dir.display(), err),
+                }
+            }
+        }
+        let enabled: Vec<String> = plugins
+            .values()
+            .filter(|plugin| plugin.enabled)
+            .map(|plugin| plugin.manifest.id.clone())
+            .collect();
+        cx.set_global(Self {
+            plugins,
+            views: HashMap::new(),
+        });
+        for id in enabled {
+            Self::load(id, cx);
+        }
+    }
+    fn load(id: String, cx: &mut WindowContext) {
+        let Some(plugin) = cx.global::<Self>().plugins.get(&id) else {
+            return;
+        };
+        let dir = plugin.dir.clone();
+        let permissions = plugin.manifest.permissions.clone();
+        cx.spawn(|mut cx| async move {
+            let result = spawn_blocking(move || instantiate(&dir, permissions)).await;
+            let _ = cx.update(|cx| Self::loaded(&id, result, cx));
+        })
+        .detach();
+    }
+    fn loaded(
+        id: &str,
+        result: anyhow::Result<(Instance, Vec<bindings::Command>)>,
+        cx: &mut WindowContext,
+    ) {
+        let (instance, commands) = match result {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                error!("Failed to load plugin {}: {}", id, err);
+                cx.update_global::<Self, _>(|this, _| {
+                    if let Some(plugin) = this.plugins.get_mut(id) {
+                        plugin.error = Some(err.to_string());
+                    }
+                });
+                return;
+            }
+        };
+        let Some(plugin) = cx.global::<Self>().plugins.get(id) else {
+            return;
+        };
+        // It might have been disabled while it was loading
+        if !plugin.enabled {
+            return;
+        }
+        let dir = plugin.dir.clone();
+        let mut ids = vec![];
+        for command in commands {
+            ids.push(format!("{}.{}", id, command.id));
+            let command = PluginCommandBuilder {
+                plugin: id.to_string(),
+                dir: dir.clone(),
+                command,
+            }
+            .build(cx);
+            RootCommands::insert(command, cx);
+        }
+        cx.update_global::<Self, _>(|this, _| {
+            if let Some(plugin) = this.plugins.get_mut(id) {
+                plugin.instance = Some(Arc::new(Mutex::new(instance)));
+                plugin.commands = ids;
+                plugin.error = None;
+            }
+        });
+        if cx.has_global::<HotkeyManager>() {
+            HotkeyManager::update(cx);
+        }
+    }
+    /// Enabling a plugin grants it the permissions its manifest currently asks for.
+    pub fn set_enabled(id: &str, enabled: bool, cx: &mut WindowContext) -> anyhow::Result<()> {
+        let granted = match cx.global::<Self>().plugins.get(id) {
+            Some(plugin) if enabled => plugin.manifest.permissions.clone(),
+            _ => Permissions::default(),
+        };
+        PluginState {
+            id: id.to_string(),
+            enabled,
+            granted,
+        }
+        .overwrite_into(&id.to_string(), db())?;
+        let commands = cx.update_global::<Self, _>(|this, _| {
+            let Some(plugin) = this.plugins.get_mut(id) else {
+                return vec![];
+            };
+            plugin.enabled = enabled;
+            plugin.error = None;
+            plugin.instance = None;
+            std::mem::take(&mut plugin.commands)
+        });
+        for command in commands {
+            RootCommands::remove(&command, cx);
+        }
+        if enabled {
+            Self::load(id.to_string(), cx);
+        }
+        Ok(())
+    }
+    /// Calls into the plugin on a background thread and applies the effects it requested afterwards.
+    fn call(
+        id: &str,
+        f: impl FnOnce(&Plugin, &mut Store<Host>) -> wasmtime::Result<Result<(), String>>
+            + Send
+            + 'static,
+        cx: &mut WindowContext,
+    ) {
+        let instance = cx
+            .global::<Self>()
+            .plugins
+            .get(id)
+            .and_then(|plugin| plugin.instance.clone());
+        let Some(instance) = instance else {
+            Self::toast(ToastStyle::Error, "Plugin is not loaded".to_string(), cx);
+            return;
+        };
+        let clipboard = cx
+            .read_from_clipboard()
+            .map(|clipboard| clipboard.text().clone());
+        let id = id.to_string();
+        cx.spawn(|mut cx| async move {
+            let (result, effects) = spawn_blocking(move || {
+                let mut instance = instance.lock();
+                let Instance { store, bindings } = &mut *instance;
+                store.data_mut().clipboard = clipboard;
+                store.set_epoch_deadline(DEADLINE);
+                let result = f(bindings, store);
+                let effects = std::mem::take(&mut store.data_mut().effects);
+                (result, effects)
+            })
+            .await;
+            let _ = cx.update(|cx| {
+                Self::apply(&id, effects, cx);
+                match result {
+                    Ok(Ok(())) => {}
+                    Ok(Err(err)) => Self::toast(ToastStyle::Error, err, cx),
+                    Err(err) => {
+                        error!("Plugin {} crashed: {}", id, err);
+                        Self::toast(ToastStyle::Error, "Plugin crashed".to_string(), cx);
+                    }
+                }
+            });
+        })
+        .detach();
+    }
+    fn toast(style: ToastStyle, message: String, cx: &mut WindowContext) {
+        let Some(item) = cx.global::<StateModel>().inner.read(cx).stack.last() else {
+            return;
+        };
+        let mut actions = item.actions.read(cx).clone();
+        match style {
+            ToastStyle::Loading => actions.toast.loading(message, cx),
+            ToastStyle::Success => actions.toast.success(message, cx),
+            ToastStyle::Error => actions.toast.error(message, cx),
+        }
+    }
+    fn apply(id: &str, effects: Vec<Effect>, cx: &mut WindowContext) {
+        let builder = |view| PluginViewBuilder {
+            plugin: id.to_string(),
+            view,
+        };
+        for effect in effects {
+            match effect {
+                Effect::Toast(style, message) => Self::toast(style, message, cx),
+                Effect::Push(view) => {
+                    StateModel::update(|this, cx| this.push(builder(view), cx), cx);
+                }
+                Effect::Update(view) => {
+                    let key = (id.to_string(), view.id.clone());
+                    let Some(open) = cx.global::<Self>().views.get(&key).cloned() else {
+                        continue;
+                    };
+                    let Some(model) = open.view.upgrade() else {
+                        continue;
+                    };
+                    open.actions
+                        .update_global(view::actions(id, &view.id, &view.actions, cx), cx);
+                    model.update(cx, |this, cx| {
+                        *this = view;
+                        cx.notify();
+                    });
+                    if let Some(actions) = open.actions.inner.upgrade() {
+                        actions.read(cx).update();
+                    }
+                }
+                Effect::Pop => StateModel::update(|this, cx| this.pop(cx), cx),
+                Effect::Close => Window::close(cx),
+                Effect::Copy(text) => cx.write_to_clipboard(ClipboardItem::new(text)),
+            }
+        }
+    }
+    fn dir(id: &str, cx: &AppContext) -> PathBuf {
+        cx.global::<Self>()
+            .plugins
+            .get(id)
+            .map(|plugin| plugin.dir.clone())
+            .unwrap_or_default()
+    }
+    fn open(id: &str, view: &Model<ListView>, actions: &ActionsModel, cx: &mut WindowContext) {
+        let key = (id.to_string(), view.read(cx).id.clone());
+        cx.update_global::<Self, _>(|this, _| {
+            this.views.insert(
+                key.clone(),
+                OpenView {
+                    view: view.downgrade(),
+                    actions: actions.clone(),
+                },
+            );
+        });
+        cx.observe_release(view, move |_, cx| {
+            cx.update_global::<Self, _>(|this, _| {
+                if this
+                    .views
+                    .get(&key)
+                    .is_some_and(|open| open.view.upgrade().is_none())
+                {
+                    this.views.remove(&key);
+                }
+            });
+        })
+        .detach();
+    }
+}
+
+impl Global for Plugins {}
+
+struct PluginCommandBuilder {
+    plugin: String,
+    dir: PathBuf,
+    command: bindings::Command,
+}
+
+impl RootCommandBuilder for PluginCommandBuilder {
+    fn build(&self, _cx: &mut WindowContext) -> RootCommand {
+        let plugin = self.plugin.clone();
+        let command = self.command.id.clone();
+        RootCommand::new(
+            format!("{}.{}", self.plugin, self.command.id),
+            &self.command.title,
+            &self.command.subtitle,
+            icon(&self.command.icon, &self.dir).unwrap_or(Img::default().icon(Icon::Puzzle)),
+            self.command.keywords.clone(),
+            None,
+            Box::new(move |_, cx| {
+                let command = command.clone();
+                Plugins::call(
+                    &plugin,
+                    move |bindings, store| bindings.call_run(store, &command),
+                    cx,
+                );
+            }),
+        )
+    }
+}
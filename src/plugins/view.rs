@@ -0,0 +1,148 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::path::Path;
+
+use gpui::*;
+
+use crate::{
+    components::{
+        list::{Accessory, Item, ItemBuilder, ListBuilder, ListItem},
+        shared::{Icon, Img},
+    },
+    extensions::icon,
+    query::TextEvent,
+    state::{Action, Shortcut, StateViewBuilder, StateViewContext},
+};
+
+use super::{bindings, Plugins};
+
+fn action(
+    plugin: &str,
+    view: &str,
+    item: Option<&str>,
+    dir: &Path,
+    action: &bindings::Action,
+) -> Action {
+    let plugin = plugin.to_string();
+    let view = view.to_string();
+    let item = item.map(|item| item.to_string());
+    let id = action.id.clone();
+    Action::new(
+        icon(&action.icon, dir).unwrap_or(Img::default().icon(Icon::ArrowRight)),
+        &action.title,
+        action
+            .shortcut
+            .as_deref()
+            .and_then(|shortcut| Keystroke::parse(shortcut).ok())
+            .map(|keystroke| Shortcut::from(&keystroke)),
+        move |_, cx| {
+            let (view, item, id) = (view.clone(), item.clone(), id.clone());
+            Plugins::call(
+                &plugin,
+                move |bindings, store| bindings.call_on_action(store, &view, item.as_deref(), &id),
+                cx,
+            );
+        },
+        false,
+    )
+}
+
+/// The view's own actions, which are available regardless of the selected item.
+pub(super) fn actions(
+    plugin: &str,
+    view: &str,
+    actions: &[bindings::Action],
+    cx: &AppContext,
+) -> Vec<Action> {
+    let dir = Plugins::dir(plugin, cx);
+    actions
+        .iter()
+        .map(|a| action(plugin, view, None, &dir, a))
+        .collect()
+}
+
+fn item(plugin: &str, view: &str, dir: &Path, item: &bindings::Item) -> Item {
+    let mut keywords = vec![item.title.clone()];
+    keywords.extend(item.keywords.clone());
+    ItemBuilder::new(
+        item.id.clone(),
+        ListItem::new(
+            icon(&item.icon, dir),
+            &item.title,
+            item.subtitle.clone(),
+            item.tag
+                .iter()
+                .map(|tag| Accessory::new(tag, None))
+                .collect(),
+        ),
+    )
+    .keywords(keywords)
+    .actions(
+        item.actions
+            .iter()
+            .map(|a| action(plugin, view, Some(&item.id), dir, a))
+            .collect(),
+    )
+    .build()
+}
+
+#[derive(Clone)]
+pub struct PluginViewBuilder {
+    pub plugin: String,
+    pub view: bindings::ListView,
+}
+
+impl StateViewBuilder for PluginViewBuilder {
+    fn build(&self, context: &mut StateViewContext, cx: &mut WindowContext) -> AnyView {
+        if let Some(placeholder) = &self.view.placeholder {
+            context.query.set_placeholder(placeholder, cx);
+        }
+        let plugin = self.plugin.clone();
+        let dir = Plugins::dir(&plugin, cx);
+        // Updates from the plugin replace the contents of this model
+        let model = cx.new_model(|_| self.view.clone());
+        Plugins::open(&plugin, &model, &context.actions, cx);
+        context
+            .actions
+            .update_global(actions(&plugin, &self.view.id, &self.view.actions, cx), cx);
+        if let Some(query) = context.query.view.upgrade() {
+            let plugin = plugin.clone();
+            let id = self.view.id.clone();
+            cx.subscribe(&query, move |_, event, cx| {
+                if let TextEvent::Input { text } = event {
+                    let (id, text) = (id.clone(), text.clone());
+                    Plugins::call(
+                        &plugin,
+                        move |bindings, store| bindings.call_on_query(store, &id, &text),
+                        cx,
+                    );
+                }
+            })
+            .detach();
+        }
+        ListBuilder::new()
+            .build(
+                move |_, _, cx| {
+                    let view = model.read(cx);
+                    Ok(Some(
+                        view.items
+                            .iter()
+                            .map(|i| item(&plugin, &view.id, &dir, i))
+                            .collect(),
+                    ))
+                },
+                context,
+                cx,
+            )
+            .into()
+    }
+}
@@ -0,0 +1,273 @@
+/*
+ *
+ *  This source file is part of the Loungy open source project
+ *
+ *  Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ *  Licensed under MIT License
+ *
+ *  See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ *
+ */
+
+use std::{collections::BTreeMap, fs, path::PathBuf, time::Duration};
+
+use async_std::task::sleep;
+use futures::{channel::mpsc::unbounded, StreamExt};
+use gpui::*;
+use log::error;
+use notify::{RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+
+use crate::paths::paths;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchScope {
+    #[default]
+    All,
+    Applications,
+    Commands,
+}
+
+impl SearchScope {
+    pub fn options() -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("all", "All"),
+            ("applications", "Applications"),
+            ("commands", "Commands"),
+        ]
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchScope::All => "all",
+            SearchScope::Applications => "applications",
+            SearchScope::Commands => "commands",
+        }
+    }
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "all" => Some(SearchScope::All),
+            "applications" => Some(SearchScope::Applications),
+            "commands" => Some(SearchScope::Commands),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Placement {
+    #[default]
+    Cursor,
+    Focused,
+    Primary,
+}
+
+impl Placement {
+    pub fn options() -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("cursor", "Cursor"),
+            ("focused", "Focused Window"),
+            ("primary", "Primary Display"),
+        ]
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Placement::Cursor => "cursor",
+            Placement::Focused => "focused",
+            Placement::Primary => "primary",
+        }
+    }
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "cursor" => Some(Placement::Cursor),
+            "focused" => Some(Placement::Focused),
+            "primary" => Some(Placement::Primary),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowMode {
+    #[default]
+    Fixed,
+    Compact,
+    Resizable,
+}
+
+impl WindowMode {
+    pub fn options() -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("fixed", "Fixed"),
+            ("compact", "Compact"),
+            ("resizable", "Resizable"),
+        ]
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WindowMode::Fixed => "fixed",
+            WindowMode::Compact => "compact",
+            WindowMode::Resizable => "resizable",
+        }
+    }
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "fixed" => Some(WindowMode::Fixed),
+            "compact" => Some(WindowMode::Compact),
+            "resizable" => Some(WindowMode::Resizable),
+            _ => None,
+        }
+    }
+}
+
+/// Preferences from `config.toml` in the config directory, the file is reloaded whenever it changes.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    /// Size of the main window, applied the next time it opens
+    pub width: f64,
+    pub height: f64,
+    /// `compact` only shows the query until something is typed, `resizable` remembers the size the window was resized to
+    pub window_mode: WindowMode,
+    /// Seconds after the window was closed until it goes back to the root search
+    pub reset_timeout: u64,
+    /// Hotkey opening the main window, e.g. `ctrl-alt-cmd-space`
+    pub hotkey: String,
+    /// Terminal emulator followed by the flag to run a command, e.g. `kitty --`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<String>,
+    /// Hotkey starting a leader sequence, which is followed by the leader key of a command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leader: Option<String>,
+    /// Seconds to wait for the key following the leader hotkey
+    pub leader_timeout: u64,
+    /// What the root search shows when it is opened, can be switched with tab
+    pub search_scope: SearchScope,
+    /// Display windows open on, the one with the mouse cursor, the one with the focused window or the primary one
+    pub placement: Placement,
+    /// Applications by ID in which snippet keywords aren't expanded as they are typed
+    pub expansion_exclude: Vec<String>,
+    /// Applications by ID that paste with ctrl+shift+v instead of ctrl+v, which most terminals do
+    pub expansion_shift_paste: Vec<String>,
+    /// Folders indexed for file search, `~` stands for the home folder
+    pub file_search_roots: Vec<String>,
+    /// Whether hidden files are indexed too, `.gitignore` files are respected either way
+    pub file_search_hidden: bool,
+    /// Vertical offset of the main window per display, keyed by the display origin like `1920,0`
+    pub display_offsets: BTreeMap<String, f64>,
+    /// Commands set to `false` are hidden and their hotkeys are disabled
+    pub commands: BTreeMap<String, bool>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            width: 800.0,
+            height: 450.0,
+            window_mode: WindowMode::Fixed,
+            reset_timeout: 90,
+            hotkey: "ctrl-alt-cmd-space".to_string(),
+            terminal: None,
+            leader: None,
+            leader_timeout: 3,
+            search_scope: SearchScope::All,
+            placement: Placement::Cursor,
+            expansion_exclude: vec![],
+            expansion_shift_paste: [
+                "org.gnome.Terminal.desktop",
+                "org.gnome.Console.desktop",
+                "org.kde.konsole.desktop",
+                "xfce4-terminal.desktop",
+                "kitty.desktop",
+                "Alacritty.desktop",
+                "org.wezfurlong.wezterm.desktop",
+                "com.mitchellh.ghostty.desktop",
+                "foot.desktop",
+            ]
+            .map(String::from)
+            .to_vec(),
+            file_search_roots: vec!["~".to_string()],
+            file_search_hidden: false,
+            display_offsets: BTreeMap::new(),
+            commands: BTreeMap::new(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn path() -> PathBuf {
+        paths().config.join("config.toml")
+    }
+    fn load() -> anyhow::Result<Self> {
+        match fs::read_to_string(Self::path()) {
+            Ok(settings) => Ok(toml::from_str(&settings)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+    pub fn init(cx: &mut AppContext) {
+        let settings = Self::load().unwrap_or_else(|err| {
+            error!("Failed to load settings: {}", err);
+            Self::default()
+        });
+        cx.set_global(settings);
+
+        let (sender, mut receiver) = unbounded();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if event.is_ok_and(|event| {
+                !event.kind.is_access() && event.paths.iter().any(|p| p.ends_with("config.toml"))
+            }) {
+                let _ = sender.unbounded_send(());
+            }
+        });
+        // Editors usually replace the file instead of writing to it, so the folder is watched
+        let watcher = watcher.and_then(|mut watcher| {
+            let _ = fs::create_dir_all(&paths().config);
+            watcher.watch(&paths().config, RecursiveMode::NonRecursive)?;
+            Ok(watcher)
+        });
+        let watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                error!("Failed to watch settings: {}", err);
+                return;
+            }
+        };
+        cx.spawn(|mut cx| async move {
+            let _watcher = watcher;
+            while receiver.next().await.is_some() {
+                // Editors write in several steps, let them settle first
+                sleep(Duration::from_millis(100)).await;
+                while let Ok(Some(_)) = receiver.try_next() {}
+                match Self::load() {
+                    Ok(settings) => {
+                        let result = cx.update(|cx| {
+                            if !cx.global::<Self>().eq(&settings) {
+                                cx.set_global(settings);
+                            }
+                        });
+                        if result.is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) => error!("Failed to reload settings: {}", err),
+                }
+            }
+        })
+        .detach();
+    }
+    /// Writes the settings to `config.toml`, which applies them once the change is picked up.
+    pub fn save(&self, cx: &mut AppContext) -> anyhow::Result<()> {
+        fs::create_dir_all(&paths().config)?;
+        fs::write(Self::path(), toml::to_string_pretty(self)?)?;
+        cx.set_global(self.clone());
+        Ok(())
+    }
+    pub fn enabled(&self, id: &str) -> bool {
+        self.commands.get(id).copied().unwrap_or(true)
+    }
+}
+
+impl Global for Settings {}
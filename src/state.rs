@@ -11,7 +11,7 @@
 
 use async_std::task::sleep;
 use gpui::*;
-use log::debug;
+use log::{debug, error};
 use serde::Deserialize;
 use std::{
     sync::{atomic::AtomicBool, Arc},
@@ -19,11 +19,11 @@ use std::{
 };
 
 use crate::{
-    commands::root::list::RootListBuilder,
     components::{
         list::{Accessory, ItemBuilder, List, ListBuilder, ListItem},
         shared::{Icon, Img, ImgMask, ImgSize},
     },
+    history::{History, Recall, ROOT},
     query::{TextEvent, TextInput, TextInputWeak},
     theme::{self, Theme},
     window::{Window, WindowStyle},
@@ -376,20 +376,13 @@ impl Toast {
        So right now I don't have a good solution. I am leaving this here for future reference investigation.
     */
     pub fn floating(&mut self, message: impl ToString, icon: Option<Icon>, cx: &mut WindowContext) {
-        let bounds = cx.display().map(|d| d.bounds()).unwrap_or(Bounds {
-            origin: Point::new(GlobalPixels::from(0.0), GlobalPixels::from(0.0)),
-            size: Size {
-                width: GlobalPixels::from(1920.0),
-                height: GlobalPixels::from(1080.0),
-            },
-        });
         Window::close(cx);
         cx.open_window(
             WindowStyle::Toast {
                 width: message.to_string().len() as f64 * 12.0,
                 height: 50.0,
             }
-            .options(bounds),
+            .options(cx),
             |cx| {
                 cx.spawn(|mut cx| async move {
                     sleep(Duration::from_secs(2)).await;
@@ -447,6 +440,8 @@ pub struct StateItem {
     pub view: AnyView,
     pub actions: View<Actions>,
     pub workspace: bool,
+    // The root command the item was opened by, whose query history it uses
+    pub command: Option<String>,
 }
 
 pub struct StateViewContext {
@@ -457,37 +452,64 @@ pub struct StateViewContext {
 
 impl StateItem {
     pub fn init(view: impl StateViewBuilder, workspace: bool, cx: &mut WindowContext) -> Self {
+        Self::new(view, workspace, None, cx)
+    }
+    fn new(
+        view: impl StateViewBuilder,
+        workspace: bool,
+        command: Option<String>,
+        cx: &mut WindowContext,
+    ) -> Self {
         let (s, r) = crossbeam_channel::unbounded::<bool>();
         let (actions_weak, actions) = ActionsModel::init(s, cx);
         let query = TextInput::new(cx);
 
         let actions_clone = actions_weak.clone();
-        cx.subscribe(&query.view, move |_, event, cx| match event {
+        let mut recall = command.as_ref().map(Recall::new);
+        cx.subscribe(&query.view, move |view, event, cx| match event {
             TextEvent::Blur => {
                 // if !actions_clone.inner.read(cx).show {
                 //     Window::close(cx);
                 // };
             }
             TextEvent::KeyDown(ev) => {
-                let _ = actions_clone.inner.update(cx, |this, cx| {
-                    if let Some(action) = this.check(&ev.keystroke, cx) {
-                        if ev.is_held {
-                            return;
+                let handled = actions_clone
+                    .inner
+                    .update(cx, |this, cx| {
+                        if let Some(action) = this.check(&ev.keystroke, cx) {
+                            if !ev.is_held {
+                                (action.action)(this, cx);
+                            }
+                            return true;
+                        };
+                        if !ev.is_held
+                            && (Keystroke {
+                                modifiers: Modifiers::default(),
+                                key: "tab".to_string(),
+                                ime_key: None,
+                            })
+                            .eq(&ev.keystroke)
+                        {
+                            this.dropdown_cycle(cx);
                         }
-                        (action.action)(this, cx);
-                        return;
+                        false
+                    })
+                    .unwrap_or_default();
+                if let Some(recall) = recall.as_mut().filter(|_| !handled) {
+                    // Up and down belong to the list, so the history uses the readline keys
+                    let text = view.read(cx).text.clone();
+                    let modifiers = &ev.keystroke.modifiers;
+                    let control = modifiers.control && !modifiers.alt && !modifiers.command;
+                    let query = match ev.keystroke.key.as_str() {
+                        "p" if control => recall.older(&text),
+                        "n" if control => recall.newer(&text),
+                        "r" if control => recall.search(&text),
+                        _ => None,
                     };
-                    if !ev.is_held
-                        && (Keystroke {
-                            modifiers: Modifiers::default(),
-                            key: "tab".to_string(),
-                            ime_key: None,
-                        })
-                        .eq(&ev.keystroke)
-                    {
-                        this.dropdown_cycle(cx);
+                    if let Some(query) = query {
+                        view.update(cx, |editor, cx| editor.set_text(query, cx));
                     }
-                });
+                }
 
                 if ev.keystroke.key.as_str() == "escape" {
                     Window::close(cx);
@@ -510,6 +532,20 @@ impl StateItem {
             view,
             actions,
             workspace,
+            command,
+        }
+    }
+    // Masked inputs hold secrets, which don't belong in the history
+    fn remember(&self, cx: &AppContext) {
+        let Some(command) = &self.command else {
+            return;
+        };
+        let editor = self.query.view.read(cx);
+        if editor.masked {
+            return;
+        }
+        if let Err(err) = History::record(command, &editor.text) {
+            error!("Failed to save query history: {}", err);
         }
     }
 }
@@ -520,6 +556,8 @@ pub trait StateViewBuilder: Clone {
 
 pub struct State {
     pub stack: Vec<StateItem>,
+    // The root command that is being run, which the next pushed item belongs to
+    command: Option<String>,
 }
 
 #[derive(Clone)]
@@ -529,12 +567,16 @@ pub struct StateModel {
 }
 
 impl StateModel {
-    pub fn init(cx: &mut WindowContext) -> Self {
+    /// One-shot prompts like dmenu pass `history: false`, their queries aren't recorded or recalled.
+    pub fn init(root: impl StateViewBuilder, history: bool, cx: &mut WindowContext) -> Self {
         let this = Self {
-            inner: cx.new_model(|_| State { stack: vec![] }),
+            inner: cx.new_model(|_| State {
+                stack: vec![],
+                command: history.then(|| ROOT.to_string()),
+            }),
             loader: ActiveLoaders::init(cx),
         };
-        this.push(RootListBuilder {}, cx);
+        this.push(root, cx);
 
         cx.set_global(this.clone());
 
@@ -558,15 +600,21 @@ impl StateModel {
         });
     }
     pub fn pop(&self, cx: &mut WindowContext) {
-        self.inner.update(cx, |model, cx| {
+        let item = self.inner.update(cx, |model, cx| {
             if model.stack.len() > 1 {
-                model.stack.pop();
                 cx.notify();
-            };
+                model.stack.pop()
+            } else {
+                None
+            }
         });
+        if let Some(item) = item {
+            item.remember(cx);
+        }
     }
     pub fn push(&self, view: impl StateViewBuilder, cx: &mut WindowContext) {
-        let item = StateItem::init(view, true, cx);
+        let command = self.inner.update(cx, |model, _| model.command.take());
+        let item = StateItem::new(view, true, command, cx);
         self.inner.update(cx, |model, cx| {
             model.stack.push(item);
             cx.notify();
@@ -584,12 +632,23 @@ impl StateModel {
         self.push(view, cx);
     }
     pub fn reset(&self, cx: &mut WindowContext) {
+        let items = self.inner.update(cx, |model, _| model.stack.split_off(1));
+        for item in items.iter().rev() {
+            item.remember(cx);
+        }
+        let root = self.inner.read(cx).stack[0].clone();
+        root.remember(cx);
+        root.query.downgrade().set_text("", cx);
+    }
+    /// Runs a root command, making the first item it pushes use the command's query history.
+    pub fn run(&self, id: &str, f: impl FnOnce(&mut WindowContext), cx: &mut WindowContext) {
+        if let Err(err) = History::opened(id) {
+            error!("Failed to save query history: {}", err);
+        }
         self.inner
-            .update(cx, |model, _| {
-                model.stack.truncate(1);
-                model.stack[0].query.downgrade()
-            })
-            .set_text("", cx);
+            .update(cx, |model, _| model.command = Some(id.to_string()));
+        f(cx);
+        self.inner.update(cx, |model, _| model.command = None);
     }
 }
 
@@ -679,6 +738,25 @@ impl Shortcut {
     }
 }
 
+// The inverse of Keystroke::parse, e.g. `ctrl-alt-space`
+impl std::fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let modifiers = &self.inner.modifiers;
+        for (enabled, name) in [
+            (modifiers.control, "ctrl"),
+            (modifiers.alt, "alt"),
+            (modifiers.shift, "shift"),
+            (modifiers.command, "cmd"),
+            (modifiers.function, "fn"),
+        ] {
+            if enabled {
+                write!(f, "{}-", name)?;
+            }
+        }
+        write!(f, "{}", self.inner.key)
+    }
+}
+
 fn key_icon(el: Div, icon: Icon) -> Div {
     el.child(
         div()
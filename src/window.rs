@@ -9,43 +9,163 @@
  *
  */
 
-use std::time::Duration;
+use std::{collections::BTreeMap, rc::Rc, time::Duration};
 
-use async_std::task::sleep;
+use async_std::task::{sleep, spawn_blocking};
 use gpui::*;
+use log::error;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     components::shared::NoView,
-    platform::{get_frontmost_application_data, AppData},
+    db::db,
+    platform::{
+        can_resize_window, get_cursor_position, get_focused_window_bounds,
+        get_frontmost_application_data, get_window_bounds, register_main_window, set_window_bounds,
+        AppData,
+    },
+    settings::{Placement, Settings, WindowMode},
     state::StateModel,
     theme::Theme,
 };
 
-pub static WIDTH: f64 = 800.0;
-pub static HEIGHT: f64 = 450.0;
+fn contains(bounds: &Bounds<GlobalPixels>, point: &Point<GlobalPixels>) -> bool {
+    point.x >= bounds.origin.x
+        && point.x < bounds.origin.x + bounds.size.width
+        && point.y >= bounds.origin.y
+        && point.y < bounds.origin.y + bounds.size.height
+}
+
+/// The display windows open on according to the placement preference, falling back to the first one.
+pub fn target_display(cx: &AppContext) -> Option<Rc<dyn PlatformDisplay>> {
+    let displays = cx.displays();
+    let point = match cx.global::<Settings>().placement {
+        Placement::Cursor => get_cursor_position(),
+        Placement::Focused => get_focused_window_bounds().map(|bounds| bounds.center()),
+        Placement::Primary => None,
+    };
+    point
+        .and_then(|point| {
+            displays
+                .iter()
+                .find(|display| contains(&display.bounds(), &point))
+                .cloned()
+        })
+        .or_else(|| displays.first().cloned())
+}
+
+/// Key of a display in the offset preferences, which is its origin like `1920,0`.
+pub fn display_key(display: &dyn PlatformDisplay) -> String {
+    let origin = display.bounds().origin;
+    format!("{},{}", f64::from(origin.x), f64::from(origin.y))
+}
+
+/// Where the main window was resized and dragged to. It is kept in the database instead of `config.toml`,
+/// which is only written by the user and the preferences, and takes precedence over the preferences.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct WindowPlacement {
+    // Size of a resizable window
+    size: Option<(f64, f64)>,
+    // Vertical offset per display, keyed like the preferences
+    display_offsets: BTreeMap<String, f64>,
+}
+
+impl WindowPlacement {
+    pub fn init(cx: &mut AppContext) {
+        cx.set_global(db().get::<Self>("window").unwrap_or_default());
+    }
+    fn save(&self, cx: &mut AppContext) {
+        if cx.global::<Self>() == self {
+            return;
+        }
+        if let Err(err) = db().set("window", self) {
+            error!("Failed to save window placement: {}", err);
+        }
+        cx.set_global(self.clone());
+    }
+    /// Size of the main window, the one it was resized to if it is resizable.
+    pub fn size(cx: &AppContext) -> (f64, f64) {
+        let settings = cx.global::<Settings>();
+        match cx.global::<Self>().size {
+            Some(size) if settings.window_mode == WindowMode::Resizable => size,
+            _ => (settings.width, settings.height),
+        }
+    }
+    /// Vertical offset of the main window on the display, the one it was dragged to if it was.
+    pub fn offset(display: &str, cx: &AppContext) -> f64 {
+        cx.global::<Self>()
+            .display_offsets
+            .get(display)
+            .or(cx.global::<Settings>().display_offsets.get(display))
+            .copied()
+            .unwrap_or_default()
+    }
+    /// Forgets where the window was resized and dragged to on the display, once the preferences set it.
+    pub fn reset(display: Option<&str>, cx: &mut AppContext) {
+        let mut placement = cx.global::<Self>().clone();
+        placement.size = None;
+        if let Some(display) = display {
+            placement.display_offsets.remove(display);
+        }
+        placement.save(cx);
+    }
+}
+
+impl Global for WindowPlacement {}
+
+// Height of the query bar, which is all a collapsed compact window shows
+pub const COMPACT_HEIGHT: f64 = 56.0;
+// Height of a list item with an icon, compact windows grow by this for every result
+const ROW_HEIGHT: f64 = 42.0;
+// Everything around the list, which is the query, the loader and the actions bar
+const CHROME_HEIGHT: f64 = COMPACT_HEIGHT + 56.0;
+
+/// Height of a window that shows `rows` list items without scrolling.
+pub fn fit_rows(rows: usize) -> f64 {
+    CHROME_HEIGHT + rows as f64 * ROW_HEIGHT
+}
 
 pub enum WindowStyle {
-    Main,
+    Main { width: f64, height: f64 },
     Toast { width: f64, height: f64 },
     Settings,
 }
 
 impl WindowStyle {
-    pub fn options(&self, bounds: Bounds<GlobalPixels>) -> WindowOptions {
+    pub fn main(cx: &AppContext) -> Self {
+        let (width, height) = WindowPlacement::size(cx);
+        WindowStyle::Main { width, height }
+    }
+    pub fn options(&self, cx: &AppContext) -> WindowOptions {
         let mut options = WindowOptions::default();
+        let display = target_display(cx);
+        let bounds = display.as_ref().map(|d| d.bounds()).unwrap_or(Bounds {
+            origin: Point::new(GlobalPixels::from(0.0), GlobalPixels::from(0.0)),
+            size: Size {
+                width: GlobalPixels::from(1920.0),
+                height: GlobalPixels::from(1080.0),
+            },
+        });
+        options.display_id = display.as_ref().map(|d| d.id());
         let center = bounds.center();
 
         let (width, height, x, y) = match self {
-            WindowStyle::Main => {
+            WindowStyle::Main { width, height } => {
                 options.focus = true;
-                let width = GlobalPixels::from(WIDTH);
-                let height = GlobalPixels::from(HEIGHT);
+                // Where it gets dragged to is remembered per display
+                options.is_movable = true;
+                let offset = display
+                    .map(|d| WindowPlacement::offset(&display_key(d.as_ref()), cx))
+                    .unwrap_or_default();
+                let width = GlobalPixels::from(*width);
+                let height = GlobalPixels::from(*height);
                 let x: GlobalPixels = center.x - width / 2.0;
-                let y: GlobalPixels = center.y - height / 2.0;
+                let y: GlobalPixels = center.y - height / 2.0 + GlobalPixels::from(offset);
                 (width, height, x, y)
             }
             WindowStyle::Toast { width, height } => {
                 options.focus = false;
+                options.is_movable = false;
                 let width = GlobalPixels::from(*width);
                 let height = GlobalPixels::from(*height);
                 let x: GlobalPixels = center.x - width / 2.0;
@@ -58,7 +178,6 @@ impl WindowStyle {
         };
         options.bounds = Some(Bounds::new(Point { x, y }, Size { width, height }));
         options.titlebar = None;
-        options.is_movable = false;
         options.kind = WindowKind::PopUp;
         options
     }
@@ -67,7 +186,14 @@ impl WindowStyle {
 #[allow(dead_code)]
 pub struct Window {
     inner: View<NoView>,
+    handle: AnyWindowHandle,
     hidden: bool,
+    // Where the main window was last moved to
+    bounds: Option<Bounds<GlobalPixels>>,
+    // Height a compact window was fitted to, it is as high as a fixed one without it
+    height: Option<f64>,
+    // One-shot windows like the dmenu prompt exit instead of hiding
+    exit_code: Option<i32>,
 }
 
 #[allow(dead_code)]
@@ -90,30 +216,117 @@ impl Window {
             .detach();
             NoView {}
         });
+        register_main_window(cx.global::<Settings>().window_mode == WindowMode::Resizable);
         cx.set_global::<Self>(Self {
             inner: view,
+            handle: cx.window_handle(),
             hidden: false,
+            bounds: None,
+            height: None,
+            exit_code: None,
+        });
+    }
+    pub fn exit_on_close(code: i32, cx: &mut WindowContext) {
+        cx.update_global::<Self, _>(|this, _| {
+            this.exit_code = Some(code);
         });
     }
+    /// The window mode in effect, compact falls back to fixed where the window can't be resized.
+    pub fn mode(cx: &AppContext) -> WindowMode {
+        match cx.global::<Settings>().window_mode {
+            WindowMode::Compact if !can_resize_window() => WindowMode::Fixed,
+            mode => mode,
+        }
+    }
     pub fn is_open(cx: &AsyncAppContext) -> bool {
         cx.read_global::<Self, _>(|w, _| !w.hidden).unwrap_or(false)
     }
+    // GPUI can't move or resize open windows, so the platform does it
+    fn place(&mut self, cx: &mut WindowContext) {
+        let Some(mut bounds) = WindowStyle::main(cx).options(cx).bounds else {
+            return;
+        };
+        if Window::mode(cx) == WindowMode::Compact {
+            if let Some(height) = self.height {
+                bounds.size.height = GlobalPixels::from(height);
+            }
+        }
+        if self.bounds != Some(bounds) {
+            set_window_bounds(bounds);
+            self.bounds = Some(bounds);
+        }
+    }
+    /// Grows a compact window to fit results, or shrinks it back to the query.
+    pub fn fit(height: f64, cx: &mut WindowContext) {
+        cx.update_global::<Self, _>(|this, cx| {
+            if this.height != Some(height) {
+                this.height = Some(height);
+                this.place(cx);
+            }
+        });
+    }
     pub fn open(cx: &mut WindowContext) {
         cx.update_global::<Self, _>(|this, cx| {
             if this.hidden {
+                // The display it should be on might have changed
+                this.place(cx);
                 cx.activate_window();
                 this.hidden = false;
             }
         });
     }
     pub fn close(cx: &mut WindowContext) {
+        if let Some(code) = cx.global::<Self>().exit_code {
+            std::process::exit(code);
+        }
+        // Remember the size the main window was resized to, close can be called from other windows
+        let handle = cx.global::<Self>().handle;
+        let size = if cx.window_handle() == handle {
+            Some(cx.viewport_size())
+        } else {
+            cx.update_window(handle, |_, cx| cx.viewport_size()).ok()
+        };
+        let mut placement = cx.global::<WindowPlacement>().clone();
+        if cx.global::<Settings>().window_mode == WindowMode::Resizable {
+            if let Some(size) = size {
+                placement.size = Some((f64::from(size.width), f64::from(size.height)));
+            }
+        }
+        placement.save(cx);
+        // Remember how far from the center of its display the window was dragged
+        cx.spawn(|mut cx| async move {
+            let Some(bounds) = get_window_bounds().await else {
+                return;
+            };
+            let _ = cx.update(|cx| {
+                let display = cx
+                    .displays()
+                    .into_iter()
+                    .find(|display| contains(&display.bounds(), &bounds.center()));
+                if let Some(display) = display {
+                    let (_, height) = WindowPlacement::size(cx);
+                    let center = f64::from(display.bounds().center().y) - height / 2.0;
+                    let offset = (f64::from(bounds.origin.y) - center).round();
+                    let key = display_key(display.as_ref());
+                    if WindowPlacement::offset(&key, cx) != offset {
+                        let mut placement = cx.global::<WindowPlacement>().clone();
+                        placement.display_offsets.insert(key, offset);
+                        placement.save(cx);
+                    }
+                }
+                // Horizontal moves aren't remembered, so the window has to be placed again
+                cx.update_global::<Self, _>(|this, _| this.bounds = Some(bounds));
+            });
+        })
+        .detach();
         cx.update_global::<Self, _>(|this, cx| {
             this.hidden = true;
             cx.hide();
         });
-        // After 90 seconds, reset the state
+        // After a while, reset the state
+        let timeout = Duration::from_secs(cx.global::<Settings>().reset_timeout);
         cx.spawn(|mut cx| async move {
-            sleep(Duration::from_secs(90)).await;
+            sleep(timeout).await;
             // cx.background_executor()
             //     .timer(Duration::from_secs(90))
             //     .await;
@@ -147,6 +360,10 @@ pub struct Frontmost {
 impl Frontmost {
     pub fn init(cx: &mut AppContext) {
         let model = cx.new_model(|cx| {
+            // Polling would spawn xdotool every 100ms on Linux, where it's only looked up when needed
+            if cfg!(target_os = "linux") {
+                return None;
+            }
             cx.spawn(|this, mut cx| async move {
                 loop {
                     let result = this.update(&mut cx, |this: &mut Option<AppData>, cx| {
@@ -171,6 +388,10 @@ impl Frontmost {
         });
         cx.set_global::<Self>(Self { inner: model });
     }
+    /// The ID of the frontmost application, looked up off the UI thread since it can spawn a process.
+    pub async fn id() -> Option<String> {
+        spawn_blocking(|| get_frontmost_application_data().map(|app| app.id)).await
+    }
     pub fn get_async(cx: &AsyncAppContext) -> Option<AppData> {
         cx.read_global::<Self, Option<AppData>>(|this, cx| {
             cx.read_model(&this.inner, |this, _| this.clone())
@@ -11,29 +11,63 @@
 
 use gpui::*;
 
+use crate::components::list::List;
 use crate::components::shared::{Icon, Img};
-use crate::state::{StateItem, StateModel};
+use crate::settings::{Settings, WindowMode};
+use crate::state::{StateItem, StateModel, StateViewBuilder};
 use crate::theme::Theme;
+use crate::window::{fit_rows, Window, COMPACT_HEIGHT};
 
 pub struct Workspace {
     state: StateModel,
+    // Last height requested from the window in compact mode
+    height: Option<f64>,
 }
 
 impl Workspace {
-    pub fn build(cx: &mut WindowContext) -> View<Self> {
+    pub fn build(root: impl StateViewBuilder, history: bool, cx: &mut WindowContext) -> View<Self> {
         cx.new_view(|cx| {
-            let state = StateModel::init(cx);
-            Workspace { state }
+            let state = StateModel::init(root, history, cx);
+            Workspace {
+                state,
+                height: None,
+            }
         })
     }
 }
 
 impl Render for Workspace {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        let theme = cx.global::<Theme>();
+        // Compact windows only show the query until something is typed or a command is opened,
+        // then lists fit their results while other views get the full height
+        let (expanded, height) = {
+            let settings = cx.global::<Settings>();
+            let stack = &self.state.inner.read(cx).stack;
+            let item = stack.last().unwrap();
+            let view = stack.iter().filter(|item| item.workspace).last().unwrap();
+            let compact = Window::mode(cx) == WindowMode::Compact;
+            let expanded = !compact || stack.len() > 1 || !item.query.view.read(cx).text.is_empty();
+            let height = if !compact {
+                None
+            } else if !expanded {
+                Some(COMPACT_HEIGHT)
+            } else {
+                Some(match view.view.clone().downcast::<List>() {
+                    Ok(list) => fit_rows(list.read(cx).items.read(cx).len()).min(settings.height),
+                    Err(_) => settings.height,
+                })
+            };
+            (expanded, height)
+        };
+        if let Some(height) = height.filter(|height| self.height != Some(*height)) {
+            self.height = Some(height);
+            cx.defer(move |_, cx| Window::fit(height, cx));
+        }
+
         let stack: &Vec<StateItem> = self.state.inner.read(cx).stack.as_ref();
         let item = stack.last().unwrap();
         let view = stack.iter().filter(|item| item.workspace).last().unwrap();
+        let theme = cx.global::<Theme>();
 
         let mut back = div();
         if stack.len() > 1 {
@@ -46,7 +80,7 @@ impl Render for Workspace {
         }
         let a = item.actions.read(cx).clone();
 
-        div()
+        let container = div()
             .rounded_xl()
             .border()
             .border_color(theme.crust)
@@ -69,7 +103,11 @@ impl Render for Workspace {
                     .child(a.dropdown.clone())
                     .p_2()
                     .w_full(),
-            )
+            );
+        if !expanded {
+            return container;
+        }
+        container
             .child(self.state.loader.clone())
             .child(div().flex_1().size_full().p_2().child(view.view.clone()))
             .child(
@@ -61,6 +61,20 @@ final class AutoFill {
         simulatePasteEvent()
     }
 
+    func simulateLeftArrow(count: Int) {
+        let sourceRef = CGEventSource(stateID: .combinedSessionState)
+        
+        for _ in 0..<count {
+            if let leftKeyDownEvent = CGEvent(keyboardEventSource: sourceRef, virtualKey: CGKeyCode(kVK_LeftArrow), keyDown: true) {
+                leftKeyDownEvent.post(tap: .cghidEventTap)
+            }
+            
+            if let leftKeyUpEvent = CGEvent(keyboardEventSource: sourceRef, virtualKey: CGKeyCode(kVK_LeftArrow), keyDown: false) {
+                leftKeyUpEvent.post(tap: .cghidEventTap)
+            }
+        }
+    }
+
     func simulatePasteEvent(formatting: Bool = true) {
         let sourceRef = CGEventSource(stateID: .combinedSessionState)
         
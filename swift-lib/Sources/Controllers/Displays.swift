@@ -0,0 +1,95 @@
+/*
+ This source file is part of the Loungy open source project
+ 
+ Copyright (c) 2024 Loungy, Matthias Grandl and the Loungy project contributors
+ Licensed under MIT License
+ 
+ See https://github.com/MatthiasGrandl/Loungy/blob/main/LICENSE.md for license information
+ */
+
+import Cocoa
+
+final class Displays {
+    
+    // Registered at launch while it is the only window, toasts are separate windows
+    private weak var mainWindow: NSWindow?
+    
+    // AppKit coordinates start at the bottom left of the primary screen, while GPUI's start at the top left
+    private var primaryHeight: Double {
+        Double(NSScreen.screens.first?.frame.height ?? 0)
+    }
+    
+    func cursorPosition() -> Frame {
+        let location = NSEvent.mouseLocation
+        return Frame(
+            x: Double(location.x),
+            y: primaryHeight - Double(location.y),
+            width: 0,
+            height: 0
+        )
+    }
+    
+    // Accessibility coordinates already start at the top left
+    func focusedWindowFrame() -> Frame? {
+        guard let app = NSWorkspace.shared.frontmostApplication else {
+            return nil
+        }
+        
+        let axApp = AXUIElementCreateApplication(app.processIdentifier)
+        var windowValue: CFTypeRef?
+        guard
+            AXUIElementCopyAttributeValue(axApp, kAXFocusedWindowAttribute as CFString, &windowValue) == .success,
+            let windowValue
+        else {
+            return nil
+        }
+        
+        let window = windowValue as! AXUIElement
+        var positionValue: CFTypeRef?
+        var sizeValue: CFTypeRef?
+        guard
+            AXUIElementCopyAttributeValue(window, kAXPositionAttribute as CFString, &positionValue) == .success,
+            AXUIElementCopyAttributeValue(window, kAXSizeAttribute as CFString, &sizeValue) == .success
+        else {
+            return nil
+        }
+        
+        var position = CGPoint.zero
+        var size = CGSize.zero
+        AXValueGetValue(positionValue as! AXValue, .cgPoint, &position)
+        AXValueGetValue(sizeValue as! AXValue, .cgSize, &size)
+        
+        return Frame(
+            x: Double(position.x),
+            y: Double(position.y),
+            width: Double(size.width),
+            height: Double(size.height)
+        )
+    }
+    
+    func registerMainWindow(resizable: Bool) {
+        mainWindow = NSApp.windows.first
+        if resizable {
+            mainWindow?.styleMask.insert(.resizable)
+        }
+    }
+    
+    func windowFrame() -> Frame? {
+        guard let frame = mainWindow?.frame else {
+            return nil
+        }
+        return Frame(
+            x: Double(frame.origin.x),
+            y: primaryHeight - Double(frame.origin.y) - Double(frame.height),
+            width: Double(frame.width),
+            height: Double(frame.height)
+        )
+    }
+    
+    func setWindowFrame(x: Double, y: Double, width: Double, height: Double) {
+        mainWindow?.setFrame(
+            NSRect(x: x, y: primaryHeight - y - height, width: width, height: height),
+            display: true
+        )
+    }
+}
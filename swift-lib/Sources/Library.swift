@@ -15,6 +15,7 @@ import SwiftRs
 final class Library {
     let apps: Apps
     let autofill: AutoFill
+    let displays: Displays
     let ocr: OCR
     
     static let shared = Library()
@@ -22,6 +23,7 @@ final class Library {
     init() {
         apps = Apps()
         autofill = AutoFill()
+        displays = Displays()
         ocr = OCR()
     }
 }
@@ -31,6 +33,7 @@ final class Library {
 let library = Library.shared
 let apps = library.apps
 let autofill = library.autofill
+let displays = library.displays
 let ocr = library.ocr
 
 // MARK: - Apps Public Methods
@@ -88,6 +91,11 @@ func pasteFile(path: SRString) {
     )
 }
 
+@_cdecl("press_left")
+func pressLeft(count: Int) {
+    autofill.simulateLeftArrow(count: count)
+}
+
 @_cdecl("simulate_paste_event")
 func simulatePasteEvent(formatting: Bool = true) {
     autofill.simulatePasteEvent(
@@ -130,6 +138,33 @@ public func selectMenuItem(data: SRData) {
     }
 }
 
+// MARK: - Displays Public Methods
+
+@_cdecl("get_cursor_position")
+public func cursorPosition() -> Frame {
+    displays.cursorPosition()
+}
+
+@_cdecl("get_focused_window_frame")
+public func focusedWindowFrame() -> Frame? {
+    displays.focusedWindowFrame()
+}
+
+@_cdecl("register_main_window")
+func registerMainWindow(resizable: Bool) {
+    displays.registerMainWindow(resizable: resizable)
+}
+
+@_cdecl("get_window_frame")
+public func windowFrame() -> Frame? {
+    displays.windowFrame()
+}
+
+@_cdecl("set_window_frame")
+func setWindowFrame(x: Double, y: Double, width: Double, height: Double) {
+    displays.setWindowFrame(x: x, y: y, width: width, height: height)
+}
+
 // MARK: - OCR Public Methods
 
 @_cdecl("ocr")